@@ -1,4 +0,0 @@
-pub mod venture_launch;
-pub mod instruction;
-pub mod associated_token;
-mod test;
\ No newline at end of file
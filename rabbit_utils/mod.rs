@@ -1,3 +0,0 @@
-pub mod consumers;
-pub mod publisher;
-pub mod rabbit_handler;
@@ -1,3 +0,0 @@
-pub fn init_publisher() {
-    unimplemented!();
-}
@@ -0,0 +1,225 @@
+use std::{env, str::FromStr};
+
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use squads_multisig::{
+    solana_client::nonblocking::rpc_client::RpcClient,
+    squads_multisig_program,
+};
+
+use super::error::ClusterProfileError;
+
+/// Address the crypto_tracker (VentureLaunch) program is deployed under.
+pub const VENTURE_LAUNCH_PROGRAM_ID: &str = "B1Lmegd5rBAAZ4nBRN9ePeMcThLdEQ5ec3yfDZZJxnBY";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cluster {
+    Localnet,
+    Devnet,
+    Mainnet,
+    Custom
+}
+
+impl FromStr for Cluster {
+    type Err = ClusterProfileError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "localnet" | "localhost" => Ok(Cluster::Localnet),
+            "devnet" => Ok(Cluster::Devnet),
+            "mainnet" | "mainnet-beta" => Ok(Cluster::Mainnet),
+            "custom" => Ok(Cluster::Custom),
+            unknown => Err(ClusterProfileError::UnknownCluster(unknown.to_string()))
+        }
+    }
+}
+
+/// Everything needed to talk to one Solana cluster: endpoints, the program ids deployed there
+/// and the commitment level used for reads and confirmations.
+#[derive(Clone, Debug)]
+pub struct ClusterProfile {
+    pub cluster: Cluster,
+    pub rpc_url: String,
+    pub ws_url: String,
+    pub squads_program_id: Pubkey,
+    pub venture_launch_program_id: Pubkey,
+    pub commitment: CommitmentConfig
+}
+
+impl ClusterProfile {
+    pub fn localnet() -> Self {
+        Self::preset(Cluster::Localnet, "http://127.0.0.1:8899", "ws://127.0.0.1:8900")
+    }
+
+    pub fn devnet() -> Self {
+        Self::preset(Cluster::Devnet, "https://api.devnet.solana.com", "wss://api.devnet.solana.com")
+    }
+
+    pub fn mainnet() -> Self {
+        Self::preset(Cluster::Mainnet, "https://api.mainnet-beta.solana.com", "wss://api.mainnet-beta.solana.com")
+    }
+
+    pub fn custom(
+        rpc_url: String,
+        ws_url: String,
+        squads_program_id: Pubkey,
+        venture_launch_program_id: Pubkey,
+        commitment: CommitmentConfig
+    ) -> Self {
+        ClusterProfile {
+            cluster: Cluster::Custom,
+            rpc_url,
+            ws_url,
+            squads_program_id,
+            venture_launch_program_id,
+            commitment
+        }
+    }
+
+    fn preset(cluster: Cluster, rpc_url: &str, ws_url: &str) -> Self {
+        ClusterProfile {
+            cluster,
+            rpc_url: rpc_url.to_string(),
+            ws_url: ws_url.to_string(),
+            squads_program_id: squads_multisig_program::ID,
+            venture_launch_program_id: Pubkey::from_str(VENTURE_LAUNCH_PROGRAM_ID).unwrap(),
+            commitment: CommitmentConfig::confirmed()
+        }
+    }
+
+    /// Builds a profile from `SOLANA_CLUSTER` (localnet by default). `SOLANA_RPC_URL`, `SOLANA_WS_URL`,
+    /// `SQUADS_PROGRAM_ID`, `VENTURE_LAUNCH_PROGRAM_ID` and `SOLANA_COMMITMENT` override the preset values.
+    /// A custom cluster has no preset, so the URLs and both program ids are mandatory for it.
+    pub fn from_env() -> Result<Self, ClusterProfileError> {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&'static str) -> Option<String>) -> Result<Self, ClusterProfileError> {
+        let required = |name: &'static str| var(name).ok_or(ClusterProfileError::MissingVariable(name));
+
+        let cluster = match var("SOLANA_CLUSTER") {
+            Some(cluster) => Cluster::from_str(&cluster)?,
+            None => Cluster::Localnet
+        };
+
+        let mut profile = match cluster {
+            Cluster::Localnet => Self::localnet(),
+            Cluster::Devnet => Self::devnet(),
+            Cluster::Mainnet => Self::mainnet(),
+            Cluster::Custom => Self::custom(
+                required("SOLANA_RPC_URL")?,
+                required("SOLANA_WS_URL")?,
+                parse_program_id(&required("SQUADS_PROGRAM_ID")?)?,
+                parse_program_id(&required("VENTURE_LAUNCH_PROGRAM_ID")?)?,
+                CommitmentConfig::confirmed()
+            )
+        };
+
+        if let Some(rpc_url) = var("SOLANA_RPC_URL") {
+            profile.rpc_url = rpc_url;
+        }
+        if let Some(ws_url) = var("SOLANA_WS_URL") {
+            profile.ws_url = ws_url;
+        }
+        if let Some(program_id) = var("SQUADS_PROGRAM_ID") {
+            profile.squads_program_id = parse_program_id(&program_id)?;
+        }
+        if let Some(program_id) = var("VENTURE_LAUNCH_PROGRAM_ID") {
+            profile.venture_launch_program_id = parse_program_id(&program_id)?;
+        }
+        if let Some(commitment) = var("SOLANA_COMMITMENT") {
+            profile.commitment = CommitmentConfig::from_str(&commitment)
+                .map_err(|_| ClusterProfileError::UnknownCommitment(commitment))?;
+        }
+
+        Ok(profile)
+    }
+
+    pub fn with_rpc_url(mut self, rpc_url: String) -> Self {
+        self.rpc_url = rpc_url;
+        self
+    }
+
+    pub fn with_squads_program_id(mut self, squads_program_id: Pubkey) -> Self {
+        self.squads_program_id = squads_program_id;
+        self
+    }
+
+    pub fn with_venture_launch_program_id(mut self, venture_launch_program_id: Pubkey) -> Self {
+        self.venture_launch_program_id = venture_launch_program_id;
+        self
+    }
+
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn rpc_client(&self) -> RpcClient {
+        RpcClient::new_with_commitment(self.rpc_url.clone(), self.commitment)
+    }
+}
+
+fn parse_program_id(program_id: &str) -> Result<Pubkey, ClusterProfileError> {
+    Pubkey::from_str(program_id).map_err(|_| ClusterProfileError::InvalidProgramId(program_id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cluster_names() {
+        assert_eq!(Cluster::Localnet, Cluster::from_str("localhost").unwrap());
+        assert_eq!(Cluster::Devnet, Cluster::from_str("Devnet").unwrap());
+        assert_eq!(Cluster::Mainnet, Cluster::from_str("mainnet-beta").unwrap());
+        assert!(Cluster::from_str("testnet").is_err());
+    }
+
+    #[test]
+    fn presets_use_squads_program() {
+        for profile in [ClusterProfile::localnet(), ClusterProfile::devnet(), ClusterProfile::mainnet()] {
+            assert_eq!(squads_multisig_program::ID, profile.squads_program_id);
+            assert_eq!(CommitmentConfig::confirmed(), profile.commitment);
+        }
+    }
+
+    #[test]
+    fn override_squads_program_id() {
+        let fork = Pubkey::new_unique();
+        let profile = ClusterProfile::localnet().with_squads_program_id(fork);
+
+        assert_eq!(fork, profile.squads_program_id);
+        assert_eq!("http://127.0.0.1:8899", profile.rpc_client().url());
+    }
+
+    #[test]
+    fn custom_cluster_requires_both_program_ids() {
+        let squads_program_id = Pubkey::new_unique().to_string();
+        let venture_launch_program_id = Pubkey::new_unique().to_string();
+        let vars = |program_ids: &[(&'static str, &str)]| {
+            let mut vars = vec![
+                ("SOLANA_CLUSTER", "custom".to_string()),
+                ("SOLANA_RPC_URL", "http://10.0.0.1:8899".to_string()),
+                ("SOLANA_WS_URL", "ws://10.0.0.1:8900".to_string())
+            ];
+            vars.extend(program_ids.iter().map(|(name, value)| (*name, value.to_string())));
+            move |name: &'static str| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.clone())
+        };
+
+        let result = ClusterProfile::from_vars(vars(&[("SQUADS_PROGRAM_ID", &squads_program_id)]));
+        assert!(matches!(result, Err(ClusterProfileError::MissingVariable("VENTURE_LAUNCH_PROGRAM_ID"))));
+
+        let result = ClusterProfile::from_vars(vars(&[("VENTURE_LAUNCH_PROGRAM_ID", &venture_launch_program_id)]));
+        assert!(matches!(result, Err(ClusterProfileError::MissingVariable("SQUADS_PROGRAM_ID"))));
+
+        let profile = ClusterProfile::from_vars(vars(&[
+            ("SQUADS_PROGRAM_ID", &squads_program_id),
+            ("VENTURE_LAUNCH_PROGRAM_ID", &venture_launch_program_id)
+        ]))
+        .unwrap();
+        assert_eq!(Cluster::Custom, profile.cluster);
+        assert_eq!("http://10.0.0.1:8899", profile.rpc_url);
+        assert_eq!(squads_program_id, profile.squads_program_id.to_string());
+        assert_eq!(venture_launch_program_id, profile.venture_launch_program_id.to_string());
+    }
+}
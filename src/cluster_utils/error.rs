@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone)]
+pub enum ClusterProfileError {
+    #[error("Unknown cluster {0}, expected one of localnet, devnet, mainnet, custom")]
+    UnknownCluster(String),
+    #[error("Unknown commitment level {0}")]
+    UnknownCommitment(String),
+    #[error("Invalid program id {0}")]
+    InvalidProgramId(String),
+    #[error("{0} must be set for a custom cluster")]
+    MissingVariable(&'static str)
+}
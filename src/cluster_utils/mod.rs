@@ -0,0 +1,2 @@
+pub mod cluster_profile;
+pub mod error;
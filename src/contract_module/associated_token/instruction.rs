@@ -0,0 +1,43 @@
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction}, pubkey::Pubkey
+};
+use super::utils::ASSOCIATED_TOKEN_PROGRAM_ID;
+
+const CREATE: u8 = 0;
+const CREATE_IDEMPOTENT: u8 = 1;
+
+pub fn create_associated_token_program_instruction(payer: &Pubkey, ata: &Pubkey, mint: &Pubkey) -> Instruction {
+    create_associated_token_account_instruction(CREATE, payer, ata, payer, mint, &spl_token::id())
+}
+
+/// Fails if the associated token account already exists.
+pub fn create_associated_token_account(payer: &Pubkey, ata: &Pubkey, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    create_associated_token_account_instruction(CREATE, payer, ata, owner, mint, token_program)
+}
+
+/// Succeeds without changes if the associated token account already exists for the same owner and mint.
+pub fn create_associated_token_account_idempotent(payer: &Pubkey, ata: &Pubkey, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    create_associated_token_account_instruction(CREATE_IDEMPOTENT, payer, ata, owner, mint, token_program)
+}
+
+fn create_associated_token_account_instruction(
+    tag: u8,
+    payer: &Pubkey,
+    ata: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        ASSOCIATED_TOKEN_PROGRAM_ID,
+        &[tag],
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*ata, false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+    )
+}
\ No newline at end of file
@@ -1,3 +1,4 @@
 pub mod instruction;
 pub mod native;
+pub mod token;
 pub mod utils;
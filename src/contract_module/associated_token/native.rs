@@ -0,0 +1,91 @@
+use solana_sdk::{
+    instruction::Instruction, message::Message, pubkey::Pubkey, signature::{Keypair, Signature, Signer}, transaction::Transaction
+};
+use crate::rpc_utils::rpc_backend::{send_and_confirm, RpcBackend};
+use crate::contract_module::error::VentureLaunchError;
+use super::utils::get_associated_token_address;
+use super::instruction;
+
+pub async fn create_native_sol_ata(rpc_client: &dyn RpcBackend, payer: &Keypair) -> Result<Signature, VentureLaunchError> {
+    let ata_ix = instruction::create_associated_token_program_instruction(
+    &payer.pubkey(), 
+    &get_associated_token_address(&spl_token::native_mint::id(), &payer.pubkey()),
+    &spl_token::native_mint::id()
+    );
+    let message = Message::new(
+        &[ata_ix],
+        Some(&payer.pubkey())
+    );
+
+    sign_and_send(rpc_client, payer, message).await
+}
+
+pub async fn deposit_to_wrapped_sol_ata(rpc_client: &dyn RpcBackend, payer: &Keypair, amount: u64) -> Result<Signature, VentureLaunchError> {
+    let wrapped_solana_ata = get_associated_token_address(&spl_token::native_mint::id(), &payer.pubkey());
+    let instructions = wrap_instructions(&payer.pubkey(), &wrapped_solana_ata, amount)?;
+
+    let message = Message::new(
+        &instructions,
+        Some(&payer.pubkey())
+    );
+
+    sign_and_send(rpc_client, payer, message).await
+}
+
+/// Instructions that wrap exactly `amount` lamports into `wrapped_sol_account`, an initialized wSOL token account.
+pub fn wrap_instructions(payer: &Pubkey, wrapped_sol_account: &Pubkey, amount: u64) -> Result<Vec<Instruction>, VentureLaunchError> {
+    let transfer_instruction = solana_sdk::system_instruction::transfer(
+        payer,
+        wrapped_sol_account,
+        amount,
+    );
+    let native_sync_instruction = match spl_token::instruction::sync_native(
+        &spl_token::id(),
+        wrapped_sol_account
+    ) {
+        Ok(ix) => ix,
+        Err(_) => return Err(VentureLaunchError::FailedToBuildTokenInstruction)
+    };
+
+    Ok(vec![transfer_instruction, native_sync_instruction])
+}
+
+/// Closing a wSOL account unwraps it: all its lamports, wrapped amount and rent, go to `destination`.
+pub fn unwrap_instruction(owner: &Pubkey, wrapped_sol_account: &Pubkey, destination: &Pubkey) -> Result<Instruction, VentureLaunchError> {
+    match spl_token::instruction::close_account(
+        &spl_token::id(),
+        wrapped_sol_account,
+        destination,
+        owner,
+        &[]
+    ) {
+        Ok(ix) => Ok(ix),
+        Err(_) => Err(VentureLaunchError::FailedToBuildTokenInstruction)
+    }
+}
+
+/// Unwraps the whole wSOL balance of `owner` back to lamports by closing its wSOL ATA.
+pub async fn unwrap_wrapped_sol_ata(rpc_client: &dyn RpcBackend, owner: &Keypair) -> Result<Signature, VentureLaunchError> {
+    let wrapped_solana_ata = get_associated_token_address(&spl_token::native_mint::id(), &owner.pubkey());
+    let close_instruction = unwrap_instruction(&owner.pubkey(), &wrapped_solana_ata, &owner.pubkey())?;
+
+    let message = Message::new(
+        &[close_instruction],
+        Some(&owner.pubkey())
+    );
+
+    sign_and_send(rpc_client, owner, message).await
+}
+
+async fn sign_and_send(rpc_client: &dyn RpcBackend, payer: &Keypair, message: Message) -> Result<Signature, VentureLaunchError> {
+    let recent_blockhash = match rpc_client.get_latest_blockhash().await {
+        Ok(hash) => hash,
+        Err(_) => return Err(VentureLaunchError::ErrorOnGettingLatestBlockHash)
+    };
+    let mut tx = Transaction::new_unsigned(message);
+    if tx.try_sign(&[payer], recent_blockhash).is_err() {
+        return Err(VentureLaunchError::FailedToSignTransaction);
+    }
+
+    Ok(send_and_confirm(rpc_client, &tx).await?)
+}
\ No newline at end of file
@@ -0,0 +1,170 @@
+use solana_sdk::{
+    message::Message, program_pack::Pack, pubkey::Pubkey, signature::{Keypair, Signature, Signer}, transaction::Transaction
+};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    state::{Account, Mint},
+};
+use crate::rpc_utils::rpc_backend::{send_and_confirm, RpcBackend};
+use crate::contract_module::error::VentureLaunchError;
+use super::utils::{get_associated_token_address_with_program_id, TOKEN_2022_PROGRAM_ID};
+use super::instruction;
+
+/// What is needed to hold and move tokens of a mint, whichever token program owns it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MintInfo {
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub decimals: u8,
+    /// Size of a token account for this mint, including the extensions the mint requires.
+    pub account_size: u64,
+}
+
+impl MintInfo {
+    pub fn parse(mint: Pubkey, token_program: Pubkey, data: &[u8]) -> Result<MintInfo, VentureLaunchError> {
+        if token_program == spl_token::id() {
+            return match spl_token::state::Mint::unpack(data) {
+                Ok(state) => Ok(MintInfo {
+                    mint,
+                    token_program,
+                    decimals: state.decimals,
+                    account_size: spl_token::state::Account::LEN as u64,
+                }),
+                Err(_) => Err(VentureLaunchError::FailedToDeserializeMintAccount)
+            };
+        }
+
+        if token_program != TOKEN_2022_PROGRAM_ID {
+            return Err(VentureLaunchError::UnsupportedTokenProgram);
+        }
+
+        let state = match StateWithExtensions::<Mint>::unpack(data) {
+            Ok(state) => state,
+            Err(_) => return Err(VentureLaunchError::FailedToDeserializeMintAccount)
+        };
+        let mint_extensions = match state.get_extension_types() {
+            Ok(extensions) => extensions,
+            Err(_) => return Err(VentureLaunchError::FailedToDeserializeMintAccount)
+        };
+        let account_extensions = ExtensionType::get_required_init_account_extensions(&mint_extensions);
+        let account_size = match ExtensionType::try_calculate_account_len::<Account>(&account_extensions) {
+            Ok(size) => size as u64,
+            Err(_) => return Err(VentureLaunchError::FailedToDeserializeMintAccount)
+        };
+
+        Ok(MintInfo {
+            mint,
+            token_program,
+            decimals: state.base.decimals,
+            account_size,
+        })
+    }
+
+    pub fn get_associated_token_address(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(&self.mint, owner, &self.token_program)
+    }
+
+    pub fn to_base_units(&self, ui_amount: &str) -> Result<u64, VentureLaunchError> {
+        ui_amount_to_base_units(ui_amount, self.decimals)
+    }
+
+    pub fn to_ui_amount(&self, amount: u64) -> String {
+        base_units_to_ui_amount(amount, self.decimals)
+    }
+}
+
+/// Reads the mint account and detects whether it belongs to SPL Token or Token-2022.
+pub async fn get_mint_info(rpc_client: &dyn RpcBackend, mint: &Pubkey) -> Result<MintInfo, VentureLaunchError> {
+    let account = match rpc_client.get_account(mint).await {
+        Ok(account) => account,
+        Err(_) => return Err(VentureLaunchError::FailedToFetchMintAccount)
+    };
+
+    MintInfo::parse(*mint, account.owner, &account.data)
+}
+
+/// Converts a decimal string such as `"12.5"` to base units, without going through floating point.
+pub fn ui_amount_to_base_units(ui_amount: &str, decimals: u8) -> Result<u64, VentureLaunchError> {
+    let (whole, fraction) = match ui_amount.trim().split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (ui_amount.trim(), "")
+    };
+
+    let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !all_digits(whole) || !all_digits(fraction) {
+        return Err(VentureLaunchError::InvalidAmount);
+    }
+    // More precision than the mint has can not be represented
+    if fraction.trim_end_matches('0').len() > decimals as usize {
+        return Err(VentureLaunchError::InvalidAmount);
+    }
+
+    let scale = match 10_u64.checked_pow(decimals as u32) {
+        Some(scale) => scale,
+        None => return Err(VentureLaunchError::InvalidAmount)
+    };
+    let whole: u64 = if whole.is_empty() { 0 } else {
+        match whole.parse() {
+            Ok(whole) => whole,
+            Err(_) => return Err(VentureLaunchError::InvalidAmount)
+        }
+    };
+    let fraction = format!("{:0<width$}", fraction.trim_end_matches('0'), width = decimals as usize);
+    let fraction: u64 = if fraction.is_empty() { 0 } else {
+        match fraction.parse() {
+            Ok(fraction) => fraction,
+            Err(_) => return Err(VentureLaunchError::InvalidAmount)
+        }
+    };
+
+    match whole.checked_mul(scale).and_then(|amount| amount.checked_add(fraction)) {
+        Some(amount) => Ok(amount),
+        None => Err(VentureLaunchError::InvalidAmount)
+    }
+}
+
+pub fn base_units_to_ui_amount(amount: u64, decimals: u8) -> String {
+    if decimals == 0 {
+        return amount.to_string();
+    }
+
+    let digits = format!("{:0>width$}", amount, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+/// Creates the associated token account of `owner` for the mint unless it already exists.
+pub async fn create_associated_token_account_idempotent(
+    rpc_client: &dyn RpcBackend,
+    payer: &Keypair,
+    owner: &Pubkey,
+    mint_info: &MintInfo,
+) -> Result<(Pubkey, Signature), VentureLaunchError> {
+    let ata = mint_info.get_associated_token_address(owner);
+    let ata_ix = instruction::create_associated_token_account_idempotent(
+        &payer.pubkey(),
+        &ata,
+        owner,
+        &mint_info.mint,
+        &mint_info.token_program
+    );
+
+    let recent_blockhash = match rpc_client.get_latest_blockhash().await {
+        Ok(hash) => hash,
+        Err(_) => return Err(VentureLaunchError::ErrorOnGettingLatestBlockHash)
+    };
+    let mut tx = Transaction::new_unsigned(Message::new(&[ata_ix], Some(&payer.pubkey())));
+    if tx.try_sign(&[payer], recent_blockhash).is_err() {
+        return Err(VentureLaunchError::FailedToSignTransaction);
+    }
+
+    let signature = send_and_confirm(rpc_client, &tx).await?;
+
+    Ok((ata, signature))
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+pub fn get_associated_token_address(mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(mint, owner, &spl_token::id())
+}
+
+pub fn get_associated_token_address_with_program_id(mint: &Pubkey, owner: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    let (addr, _) = Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID
+    );
+    addr
+}
\ No newline at end of file
@@ -0,0 +1,451 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use solana_sdk::{
+    clock::Clock, instruction::Instruction, pubkey::Pubkey, signature::{Keypair, Signature, Signer}, sysvar
+};
+
+use super::{
+    associated_token::{instruction::create_associated_token_account_idempotent, token::get_mint_info, utils::get_associated_token_address_with_program_id},
+    error::VentureLaunchError,
+    instruction,
+    ledger::{LedgerEntry, LedgerEntryKind, LedgerStore},
+    refunds::{InMemoryRefundStore, RefundAttempt, RefundPlan, RefundRecord, RefundStore},
+    venture_launch::VentureLaunch,
+};
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::{
+    multisig_utils::{base_multisig::BaseMultisig, base_multisig_trait::BaseMultisigTrait},
+    rpc_utils::rpc_backend::RpcBackend,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CampaignConfig {
+    pub startup_id: String,
+    /// Amount in base units of the mint that has to be raised by the deadline
+    pub target: u64,
+    /// Smallest accepted single contribution
+    pub min_ticket: u64,
+    /// Most a single investor may contribute in total
+    pub max_ticket: u64,
+    /// Unix timestamp, compared with the cluster clock
+    pub deadline: i64,
+}
+
+impl CampaignConfig {
+    pub fn validate(&self) -> Result<(), VentureLaunchError> {
+        if self.target == 0 || self.min_ticket == 0 || self.min_ticket > self.max_ticket {
+            return Err(VentureLaunchError::InvalidCampaignConfig);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CampaignStatus {
+    /// The deadline has not passed yet
+    Open,
+    /// The deadline passed with the target raised
+    Succeeded,
+    /// The deadline passed without the target raised
+    Failed,
+}
+
+/// Part of the vault balance returned to the token account a contribution came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Refund {
+    pub investor: Pubkey,
+    pub token_account: Pubkey,
+    pub amount: u64,
+}
+
+/// Vault account and investor a contribution lock is held for.
+type ContributionKey = (Pubkey, Pubkey);
+
+static CONTRIBUTION_LOCKS: LazyLock<Mutex<HashMap<ContributionKey, Arc<AsyncMutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the process-wide lock that serializes the contributions of `investor` to `vault_account`,
+/// so each one is checked against the maximum ticket with the earlier ones already in the ledger.
+fn contribution_lock(vault_account: &Pubkey, investor: &Pubkey) -> Arc<AsyncMutex<()>> {
+    let mut locks = match CONTRIBUTION_LOCKS.lock() {
+        Ok(locks) => locks,
+        Err(poisoned) => poisoned.into_inner()
+    };
+
+    let key = (*vault_account, *investor);
+    locks.retain(|other, lock| *other == key || Arc::strong_count(lock) > 1);
+
+    locks
+        .entry(key)
+        .or_insert_with(|| Arc::new(AsyncMutex::new(())))
+        .clone()
+}
+
+/// What became of a refund sent by an earlier settlement.
+enum AttemptOutcome {
+    Landed,
+    /// Failed, or its blockhash expired before it landed
+    Dropped,
+    MayStillLand,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CampaignSettlement {
+    /// The vault balance was moved to the token account of the startup's Squads vault
+    Transferred { token_account: Pubkey, amount: u64, signature: Signature },
+    Refunded(Vec<(Refund, Signature)>),
+}
+
+/// Splits `vault_balance` between the token accounts contributions came from, pro rata to what each
+/// still has in the vault (its deposits minus what was already refunded to it).
+/// Rounding dust stays in the vault.
+pub fn pro_rata_refunds(entries: &[LedgerEntry], vault_balance: u64) -> Vec<Refund> {
+    let mut contributions: Vec<(Pubkey, Pubkey, i128)> = Vec::new();
+
+    for entry in entries {
+        let position = contributions.iter().position(|(_, token_account, _)| *token_account == entry.token_account);
+        match (entry.kind, position) {
+            (LedgerEntryKind::Deposit, Some(position)) => contributions[position].2 += entry.amount as i128,
+            (LedgerEntryKind::Deposit, None) => contributions.push((entry.investor, entry.token_account, entry.amount as i128)),
+            (LedgerEntryKind::Withdraw, Some(position)) => contributions[position].2 -= entry.amount as i128,
+            // Withdrawals to accounts that never contributed do not change anyone's share
+            (LedgerEntryKind::Withdraw, None) => {}
+        }
+    }
+
+    let total: i128 = contributions.iter().map(|(_, _, amount)| (*amount).max(0)).sum();
+    if total == 0 {
+        return Vec::new();
+    }
+
+    contributions
+        .into_iter()
+        .filter(|(_, _, amount)| *amount > 0)
+        .map(|(investor, token_account, amount)| Refund {
+            investor,
+            token_account,
+            amount: (vault_balance as i128 * amount / total) as u64,
+        })
+        .filter(|refund| refund.amount > 0)
+        .collect()
+}
+
+/// A fundraising campaign on top of a VentureLaunch vault.
+/// Contributions are read back from the ledger of the vault, so a ledger is required. The vault authority
+/// is a plain keypair (the platform), which settles the campaign once the deadline has passed.
+/// Refunds of a failed campaign are tracked in `refund_store`, in memory unless `with_refund_store` is used.
+pub struct Campaign {
+    pub vl: VentureLaunch,
+    pub config: CampaignConfig,
+    pub refund_store: Arc<dyn RefundStore>,
+}
+
+impl Campaign {
+    pub fn new(vl: VentureLaunch, config: CampaignConfig) -> Result<Campaign, VentureLaunchError> {
+        config.validate()?;
+        if vl.ledger.is_none() {
+            return Err(VentureLaunchError::LedgerNotConfigured);
+        }
+
+        Ok(Campaign { vl, config, refund_store: Arc::new(InMemoryRefundStore::default()) })
+    }
+
+    pub fn with_refund_store(mut self, refund_store: Arc<dyn RefundStore>) -> Campaign {
+        self.refund_store = refund_store;
+        self
+    }
+
+    /// Creates the vault of `config.startup_id` for `mint` with `authority` as its authority.
+    pub async fn open(
+        rpc_client: Arc<dyn RpcBackend>,
+        program_id: Pubkey,
+        authority: &Keypair,
+        mint: Pubkey,
+        ledger: Arc<dyn LedgerStore>,
+        config: CampaignConfig,
+    ) -> Result<Campaign, VentureLaunchError> {
+        config.validate()?;
+
+        let mint_info = get_mint_info(rpc_client.as_ref(), &mint).await?;
+        let mut vl = VentureLaunch::new(rpc_client, program_id, Pubkey::default(), Pubkey::default(), mint)
+            .with_mint_info(&mint_info)
+            .with_ledger(ledger);
+        vl.invoke_create_vault(authority, &config.startup_id).await?;
+
+        Campaign::new(vl, config)
+    }
+
+    /// Reconstructs the campaign of an existing vault, see `VentureLaunch::load`.
+    pub async fn load(
+        rpc_client: Arc<dyn RpcBackend>,
+        program_id: Pubkey,
+        authority: &Pubkey,
+        mint: Pubkey,
+        ledger: Arc<dyn LedgerStore>,
+        config: CampaignConfig,
+    ) -> Result<Campaign, VentureLaunchError> {
+        let vl = VentureLaunch::load(rpc_client, program_id, authority, &config.startup_id, mint)
+            .await?
+            .with_ledger(ledger);
+
+        Campaign::new(vl, config)
+    }
+
+    /// Unix timestamp of the cluster, so the deadline does not depend on the local clock.
+    pub async fn now(&self) -> Result<i64, VentureLaunchError> {
+        let account = match self.vl.rpc_client.get_account(&sysvar::clock::id()).await {
+            Ok(account) => account,
+            Err(_) => return Err(VentureLaunchError::FailedToFetchClock)
+        };
+
+        match solana_sdk::account::from_account::<Clock, _>(&account) {
+            Some(clock) => Ok(clock.unix_timestamp),
+            None => Err(VentureLaunchError::FailedToFetchClock)
+        }
+    }
+
+    /// Total contributed so far. Refunds do not lower it, so a failed campaign stays failed.
+    pub fn raised(&self) -> Result<u64, VentureLaunchError> {
+        Ok(self.deposits()?.iter().map(|entry| entry.amount).sum())
+    }
+
+    pub fn contribution_of(&self, investor: &Pubkey) -> Result<u64, VentureLaunchError> {
+        Ok(self.deposits()?.iter().filter(|entry| entry.investor == *investor).map(|entry| entry.amount).sum())
+    }
+
+    pub async fn status(&self) -> Result<CampaignStatus, VentureLaunchError> {
+        if self.now().await? < self.config.deadline {
+            return Ok(CampaignStatus::Open);
+        }
+
+        if self.raised()? >= self.config.target {
+            Ok(CampaignStatus::Succeeded)
+        } else {
+            Ok(CampaignStatus::Failed)
+        }
+    }
+
+    /// Deposits `amount` from `token_account` of `investor`, within the ticket limits of the campaign.
+    /// Contributions of one investor are sent one at a time, so concurrent ones can not exceed the maximum ticket.
+    pub async fn contribute(
+        &self,
+        investor: &Keypair,
+        token_account: &Pubkey,
+        amount: u64,
+    ) -> Result<Signature, VentureLaunchError> {
+        if self.status().await? != CampaignStatus::Open {
+            return Err(VentureLaunchError::CampaignNotOpen);
+        }
+        if amount < self.config.min_ticket {
+            return Err(VentureLaunchError::TicketBelowMinimum);
+        }
+
+        let lock = contribution_lock(&self.vl.vault_account, &investor.pubkey());
+        let _guard = lock.lock().await;
+        let contributed = self.contribution_of(&investor.pubkey())?;
+        if contributed.saturating_add(amount) > self.config.max_ticket {
+            return Err(VentureLaunchError::TicketAboveMaximum);
+        }
+
+        self.vl.invoke_deposit(investor, token_account, amount).await
+    }
+
+    /// Refunds still owed to the contributors of a failed campaign: the pending ones of its refund plan,
+    /// or the split of the current vault balance before the campaign was settled.
+    pub async fn refunds(&self) -> Result<Vec<Refund>, VentureLaunchError> {
+        let entries = self.ledger_entries()?;
+        match self.refund_store.plan(&self.vl.vault_account)? {
+            Some(plan) => Ok(self.recover_sent_refunds(plan, &entries)?.pending()),
+            None => Ok(pro_rata_refunds(&entries, self.vl.get_vault_balance().await?))
+        }
+    }
+
+    /// Settles an ended campaign: on success the vault balance moves to the token account of the startup's
+    /// Squads vault, on failure every contributor is refunded pro rata.
+    /// The refunds are split once, on the first settlement, and the status of each is kept in the refund
+    /// store, so calling it again after a partial failure only sends the missing ones with their original
+    /// amounts. A refund whose withdrawal was sent without a known outcome is only sent again once that
+    /// withdrawal can no longer land. Returns the refunds sent by this call.
+    pub async fn settle(
+        &self,
+        authority: &Keypair,
+        multisig: &BaseMultisig,
+    ) -> Result<CampaignSettlement, VentureLaunchError> {
+        match self.status().await? {
+            CampaignStatus::Open => Err(VentureLaunchError::CampaignNotEnded),
+            CampaignStatus::Succeeded => self.transfer_to_multisig(authority, &multisig.get_vault_pda()).await,
+            CampaignStatus::Failed => {
+                let mut refunded = Vec::new();
+                for record in self.refund_plan().await?.refunds.into_iter().filter(|record| record.signature.is_none()) {
+                    let signature = self.send_refund(authority, &record).await?;
+                    refunded.push((record.refund, signature));
+                }
+
+                Ok(CampaignSettlement::Refunded(refunded))
+            }
+        }
+    }
+
+    /// Sends the refund of `record` and marks it as refunded. A withdrawal sent for it earlier is looked up
+    /// first: it is taken if it landed, and nothing is sent while it still may land.
+    async fn send_refund(&self, authority: &Keypair, record: &RefundRecord) -> Result<Signature, VentureLaunchError> {
+        let refund = record.refund;
+        let instructions = self.send_to_instructions(authority, &refund.investor, &refund.token_account, refund.amount)?;
+
+        if let Some(attempt) = record.attempt {
+            match self.attempt_outcome(&attempt).await? {
+                AttemptOutcome::Landed => {
+                    self.refund_store.mark_refunded(&self.vl.vault_account, &refund.token_account, attempt.signature)?;
+                    self.vl.record_ledger_entries(&instructions, &attempt.signature).await?;
+                    return Ok(attempt.signature);
+                }
+                AttemptOutcome::MayStillLand => return Err(VentureLaunchError::TransactionNotConfirmed(attempt.signature)),
+                AttemptOutcome::Dropped => {}
+            }
+        }
+
+        // Saved before sending, so an outcome this call does not learn is checked by the next settlement
+        let tx = self.vl.sign(&instructions, authority).await?;
+        let attempt = RefundAttempt { signature: tx.signatures[0], recent_blockhash: tx.message.recent_blockhash };
+        self.refund_store.record_attempt(&self.vl.vault_account, &refund.token_account, attempt)?;
+
+        let signature = match self.vl.send(&tx, &instructions).await {
+            Ok(signature) => signature,
+            // The ledger can not tell it was sent, so its status is all that prevents paying it twice
+            Err(VentureLaunchError::LedgerEntriesNotRecorded(signature)) => {
+                self.refund_store.mark_refunded(&self.vl.vault_account, &refund.token_account, signature)?;
+                return Err(VentureLaunchError::LedgerEntriesNotRecorded(signature));
+            }
+            Err(error) => return Err(error)
+        };
+        self.refund_store.mark_refunded(&self.vl.vault_account, &refund.token_account, signature)?;
+
+        Ok(signature)
+    }
+
+    /// Whether a refund sent by an earlier settlement went through, checked before another one is sent.
+    async fn attempt_outcome(&self, attempt: &RefundAttempt) -> Result<AttemptOutcome, VentureLaunchError> {
+        let rpc_client = self.vl.rpc_client.as_ref();
+
+        // Checked before the status, so an attempt the node does not know after that can no longer land
+        let expired = match rpc_client.is_blockhash_valid(&attempt.recent_blockhash).await {
+            Ok(valid) => !valid,
+            Err(_) => return Err(VentureLaunchError::ErrorOnGettingLatestBlockHash)
+        };
+
+        match rpc_client.get_signature_status(&attempt.signature).await {
+            Ok(Some(Ok(()))) => Ok(AttemptOutcome::Landed),
+            Ok(Some(Err(_))) => Ok(AttemptOutcome::Dropped),
+            Ok(None) if expired => Ok(AttemptOutcome::Dropped),
+            Ok(None) => Ok(AttemptOutcome::MayStillLand),
+            Err(_) => Err(VentureLaunchError::TransactionNotConfirmed(attempt.signature))
+        }
+    }
+
+    /// The refund plan of the vault, split from the ledger and the vault balance when there is none yet.
+    async fn refund_plan(&self) -> Result<RefundPlan, VentureLaunchError> {
+        let entries = self.ledger_entries()?;
+        let plan = match self.refund_store.plan(&self.vl.vault_account)? {
+            Some(plan) => plan,
+            None => {
+                let vault_balance = self.vl.get_vault_balance().await?;
+                self.refund_store.create_plan(RefundPlan {
+                    vault_account: self.vl.vault_account,
+                    ledger_len: entries.len(),
+                    refunds: pro_rata_refunds(&entries, vault_balance)
+                        .into_iter()
+                        .map(|refund| RefundRecord { refund, signature: None, attempt: None })
+                        .collect(),
+                })?
+            }
+        };
+
+        self.recover_sent_refunds(plan, &entries)
+    }
+
+    /// Marks the pending refunds of `plan` that the ledger shows as sent, e.g. when the process stopped
+    /// between sending a refund and saving its status.
+    fn recover_sent_refunds(&self, mut plan: RefundPlan, entries: &[LedgerEntry]) -> Result<RefundPlan, VentureLaunchError> {
+        let since_plan = entries.get(plan.ledger_len..).unwrap_or_default();
+        for record in plan.refunds.iter_mut().filter(|record| record.signature.is_none()) {
+            let sent = since_plan.iter().find(|entry| {
+                entry.kind == LedgerEntryKind::Withdraw
+                    && entry.token_account == record.refund.token_account
+                    && entry.amount == record.refund.amount
+            });
+            if let Some(entry) = sent {
+                self.refund_store.mark_refunded(&plan.vault_account, &record.refund.token_account, entry.signature)?;
+                record.signature = Some(entry.signature);
+            }
+        }
+
+        Ok(plan)
+    }
+
+    async fn transfer_to_multisig(
+        &self,
+        authority: &Keypair,
+        multisig_vault: &Pubkey,
+    ) -> Result<CampaignSettlement, VentureLaunchError> {
+        let token_account = get_associated_token_address_with_program_id(&self.vl.mint, multisig_vault, &self.vl.token_program);
+        let amount = self.vl.get_vault_balance().await?;
+        let signature = self.send_to(authority, multisig_vault, &token_account, amount).await?;
+
+        Ok(CampaignSettlement::Transferred { token_account, amount, signature })
+    }
+
+    /// Withdraws `amount` to `token_account`, creating it first if it is the (closed or never created)
+    /// associated token account of `owner`, as after a native SOL deposit.
+    async fn send_to(
+        &self,
+        authority: &Keypair,
+        owner: &Pubkey,
+        token_account: &Pubkey,
+        amount: u64,
+    ) -> Result<Signature, VentureLaunchError> {
+        let instructions = self.send_to_instructions(authority, owner, token_account, amount)?;
+
+        self.vl.sign_and_send(&instructions, authority).await
+    }
+
+    fn send_to_instructions(
+        &self,
+        authority: &Keypair,
+        owner: &Pubkey,
+        token_account: &Pubkey,
+        amount: u64,
+    ) -> Result<Vec<Instruction>, VentureLaunchError> {
+        let mut instructions = Vec::new();
+        if *token_account == get_associated_token_address_with_program_id(&self.vl.mint, owner, &self.vl.token_program) {
+            instructions.push(create_associated_token_account_idempotent(
+                &authority.pubkey(),
+                token_account,
+                owner,
+                &self.vl.mint,
+                &self.vl.token_program
+            ));
+        }
+        instructions.push(instruction::withdraw(&self.vl, &authority.pubkey(), token_account, amount)?);
+
+        Ok(instructions)
+    }
+
+    fn ledger_entries(&self) -> Result<Vec<LedgerEntry>, VentureLaunchError> {
+        match self.vl.ledger.as_ref() {
+            Some(ledger) => ledger.entries(&self.vl.vault_account),
+            None => Err(VentureLaunchError::LedgerNotConfigured)
+        }
+    }
+
+    fn deposits(&self) -> Result<Vec<LedgerEntry>, VentureLaunchError> {
+        let mut entries = self.ledger_entries()?;
+        entries.retain(|entry| entry.kind == LedgerEntryKind::Deposit);
+
+        Ok(entries)
+    }
+}
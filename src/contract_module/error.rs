@@ -0,0 +1,103 @@
+use solana_sdk::signature::Signature;
+use thiserror::Error;
+
+use crate::{multisig_utils::error::BaseMultisigError, rpc_utils::rpc_backend::SendFailure};
+
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VentureLaunchError {
+    #[error("Failed to get minimum balance for rent exemption")]
+    FailedToGetMinimumBalanceForRentExemption,
+    #[error("Error on getting latest block hash")]
+    ErrorOnGettingLatestBlockHash,
+    #[error("Failed to build token instruction")]
+    FailedToBuildTokenInstruction,
+    #[error("Failed to sign transaction")]
+    FailedToSignTransaction,
+    /// Nothing landed: the node did not take the transaction or it failed
+    #[error("Failed to send transaction")]
+    FailedToSendTransaction,
+    #[error("Failed to fetch vault data account")]
+    FailedToFetchDataAccount,
+    #[error("Failed to deserialize vault data account")]
+    FailedToDeserializeDataAccount,
+    #[error("Failed to derive vault account address")]
+    FailedToDeriveAccountAddress,
+    #[error("Vault data account is not initialized")]
+    VaultNotInitialized,
+    #[error("Vault data account tracks a different vault")]
+    VaultAccountMismatch,
+    #[error("Invalid instruction data")]
+    InvalidInstructionData,
+    #[error("Invalid instruction accounts")]
+    InvalidInstructionAccounts,
+    #[error("Instruction is addressed to another program")]
+    InvalidProgramId,
+    #[error("Failed to fetch mint account")]
+    FailedToFetchMintAccount,
+    #[error("Failed to deserialize mint account")]
+    FailedToDeserializeMintAccount,
+    #[error("Mint is not owned by SPL Token or Token-2022")]
+    UnsupportedTokenProgram,
+    #[error("Invalid token amount")]
+    InvalidAmount,
+    #[error("Vault mint is not the native mint")]
+    MintIsNotNative,
+    #[error("Failed to fetch token account")]
+    FailedToFetchTokenAccount,
+    #[error("Failed to deserialize token account")]
+    FailedToDeserializeTokenAccount,
+    #[error("No ledger is configured for this vault")]
+    LedgerNotConfigured,
+    #[error("Failed to record ledger entry")]
+    FailedToRecordLedgerEntry,
+    #[error("Transaction {0} landed but its ledger entries were not recorded")]
+    LedgerEntriesNotRecorded(Signature),
+    #[error("Failed to read ledger")]
+    FailedToReadLedger,
+    #[error("Failed to record refund status")]
+    FailedToRecordRefund,
+    #[error("Failed to read refund plan")]
+    FailedToReadRefunds,
+    #[error("Invalid campaign configuration")]
+    InvalidCampaignConfig,
+    #[error("Failed to fetch cluster clock")]
+    FailedToFetchClock,
+    #[error("Campaign is not open for contributions")]
+    CampaignNotOpen,
+    #[error("Campaign has not ended yet")]
+    CampaignNotEnded,
+    #[error("Contribution is below the minimum ticket")]
+    TicketBelowMinimum,
+    #[error("Contribution exceeds the maximum ticket")]
+    TicketAboveMaximum,
+    #[error("Milestone does not exist")]
+    UnknownMilestone,
+    #[error("Milestone plan belongs to another multisig")]
+    MilestoneMultisigMismatch,
+    #[error("Milestone is already proposed")]
+    MilestoneAlreadyProposed,
+    #[error("Milestone is not approved")]
+    MilestoneNotApproved,
+    #[error("Proposal of the milestone has been closed")]
+    MilestoneProposalClosed,
+    #[error("Vault data account is not owned by the program")]
+    DataAccountOwnerMismatch,
+    #[error("Vault data account has an unexpected size")]
+    InvalidDataAccountSize,
+    #[error("Vault token account has an unexpected size")]
+    InvalidTokenAccountSize,
+    /// Sent, but neither a confirmation nor a failure was seen; it may still land
+    #[error("Transaction {0} was sent but its confirmation is unknown")]
+    TransactionNotConfirmed(Signature),
+    #[error("Multisig error: {0}")]
+    Multisig(#[from] BaseMultisigError)
+}
+
+impl From<SendFailure> for VentureLaunchError {
+    fn from(failure: SendFailure) -> Self {
+        match failure {
+            SendFailure::NotSent | SendFailure::Failed => VentureLaunchError::FailedToSendTransaction,
+            SendFailure::NotConfirmed(signature) => VentureLaunchError::TransactionNotConfirmed(signature)
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::{Keypair, Signature, Signer}
+};
+
+use crate::multisig_utils::{
+    base_multisig::BaseMultisig,
+    base_multisig_trait::BaseMultisigTrait,
+    business_analyst_multisig_trait::{BusinessAnalystMultisigTrait, TransactionCreateAction},
+    investor_multisig_trait::InvestorMultisigTrait,
+};
+use super::{error::VentureLaunchError, instruction, venture_launch::VentureLaunch};
+
+/// Vaults governed by a DAO multisig: the Squads vault PDA is the initializer of the crypto_tracker vault,
+/// so every withdrawal has to go through a multisig vault transaction voted on by the investors.
+impl VentureLaunch {
+    /// Proposes creating the vault of `startup_id` with the multisig vault as its authority.
+    /// The multisig vault pays the rent, so it has to be funded before the proposal is executed.
+    pub async fn propose_create_vault(
+        &mut self,
+        multisig: &BaseMultisig,
+        proposer: &Keypair,
+        startup_id: &str,
+    ) -> Result<u64, VentureLaunchError> {
+        let instructions = self.create_vault_instructions(&multisig.get_vault_pda(), startup_id).await?;
+
+        self.propose(multisig, proposer, instructions, format!("Create vault for {}", startup_id)).await
+    }
+
+    /// Proposes withdrawing `amount` from the vault to `receive_account`, signed by the multisig vault.
+    pub async fn propose_withdraw(
+        &self,
+        multisig: &BaseMultisig,
+        proposer: &Keypair,
+        receive_account: &Pubkey,
+        amount: u64,
+    ) -> Result<u64, VentureLaunchError> {
+        let withdraw_instruction = instruction::withdraw(
+            self,
+            &multisig.get_vault_pda(),
+            receive_account,
+            amount
+        )?;
+
+        self.propose(
+            multisig,
+            proposer,
+            vec![withdraw_instruction],
+            format!("Withdraw {} from vault {} to {}", amount, self.vault_account, receive_account)
+        ).await
+    }
+
+    /// Executes an approved proposal created by `propose_create_vault` or `propose_withdraw`.
+    /// Withdrawals it contains are recorded in the ledger like direct ones.
+    pub async fn execute_proposal(
+        &self,
+        multisig: &BaseMultisig,
+        executor: &Keypair,
+        transaction_index: u64,
+    ) -> Result<Signature, VentureLaunchError> {
+        let instructions = multisig.get_vault_transaction_instructions(transaction_index).await?;
+        let mut tx = multisig.transaction_vault_transaction_execute_at(executor.pubkey(), transaction_index).await?;
+
+        let recent_blockhash = tx.message.recent_blockhash;
+        if tx.try_sign(&[executor], recent_blockhash).is_err() {
+            return Err(VentureLaunchError::FailedToSignTransaction);
+        }
+
+        let signature = multisig.send_and_confirm_transaction(&tx).await?;
+        self.record_ledger_entries(&instructions, &signature).await?;
+
+        Ok(signature)
+    }
+
+    /// Runs the whole governed withdrawal: proposal, one vote per investor and execution by `executor`.
+    /// `investors` must be enough to reach the multisig threshold and `executor` needs the Execute
+    /// permission, which the proposer of a governed multisig does not have. The keys are the investors'
+    /// own, e.g. held by their wallets in a test or a script, and are never stored.
+    pub async fn governed_withdraw(
+        &self,
+        multisig: &BaseMultisig,
+        proposer: &Keypair,
+        investors: &[&Keypair],
+        executor: &Keypair,
+        receive_account: &Pubkey,
+        amount: u64,
+    ) -> Result<Signature, VentureLaunchError> {
+        let transaction_index = self.propose_withdraw(multisig, proposer, receive_account, amount).await?;
+
+        for investor in investors {
+            multisig.vote_approve(investor, transaction_index).await?;
+        }
+
+        self.execute_proposal(multisig, executor, transaction_index).await
+    }
+
+    async fn propose(
+        &self,
+        multisig: &BaseMultisig,
+        proposer: &Keypair,
+        instructions: Vec<Instruction>,
+        memo: String,
+    ) -> Result<u64, VentureLaunchError> {
+        let (transaction_index, _) = multisig.submit_transaction_create(
+            proposer,
+            TransactionCreateAction::VaultInstructions { instructions, memo: Some(memo) }
+        ).await?;
+
+        Ok(transaction_index)
+    }
+}
\ No newline at end of file
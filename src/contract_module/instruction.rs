@@ -0,0 +1,197 @@
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction}, message::Message, pubkey::Pubkey
+};
+
+use super::{error::VentureLaunchError, venture_launch::VentureLaunch};
+
+/// Instructions of the crypto_tracker program, in the on-chain wire format:
+/// a one byte tag followed by the little endian `u64` amount for `Deposit` and `Withdraw`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VentureLaunchInstruction {
+    /// Accounts: `[signer] initializer`, `[writable] vault token account`, `[writable] data account`,
+    /// `[] rent sysvar`, `[] token program`
+    CreateVault,
+    /// Accounts: `[signer] depositor`, `[writable] source token account`, `[writable] vault token account`,
+    /// `[writable] data account`, `[] token program`
+    Deposit { amount: u64 },
+    /// Accounts: `[signer] withdrawer`, `[writable] receiving token account`, `[writable] vault token account`,
+    /// `[writable] data account`, `[] token program`, `[writable] program PDA`
+    Withdraw { amount: u64 },
+}
+
+/// (is_signer, is_writable) of every account an instruction expects, in order
+const CREATE_VAULT_ACCOUNTS: &[(bool, bool)] = &[(true, false), (false, true), (false, true), (false, false), (false, false)];
+const DEPOSIT_ACCOUNTS: &[(bool, bool)] = &[(true, false), (false, true), (false, true), (false, true), (false, false)];
+const WITHDRAW_ACCOUNTS: &[(bool, bool)] = &[(true, false), (false, true), (false, true), (false, true), (false, false), (false, true)];
+
+impl VentureLaunchInstruction {
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(9);
+        match self {
+            VentureLaunchInstruction::CreateVault => data.push(0),
+            VentureLaunchInstruction::Deposit { amount } => {
+                data.push(1);
+                data.extend_from_slice(&amount.to_le_bytes());
+            }
+            VentureLaunchInstruction::Withdraw { amount } => {
+                data.push(2);
+                data.extend_from_slice(&amount.to_le_bytes());
+            }
+        }
+        data
+    }
+
+    pub fn unpack(input: &[u8]) -> Result<Self, VentureLaunchError> {
+        let (tag, rest) = match input.split_first() {
+            Some(split) => split,
+            None => return Err(VentureLaunchError::InvalidInstructionData)
+        };
+
+        match tag {
+            0 if rest.is_empty() => Ok(VentureLaunchInstruction::CreateVault),
+            1 => Ok(VentureLaunchInstruction::Deposit { amount: Self::unpack_amount(rest)? }),
+            2 => Ok(VentureLaunchInstruction::Withdraw { amount: Self::unpack_amount(rest)? }),
+            _ => Err(VentureLaunchError::InvalidInstructionData)
+        }
+    }
+
+    fn unpack_amount(input: &[u8]) -> Result<u64, VentureLaunchError> {
+        match <[u8; 8]>::try_from(input) {
+            Ok(bytes) => Ok(u64::from_le_bytes(bytes)),
+            Err(_) => Err(VentureLaunchError::InvalidInstructionData)
+        }
+    }
+
+    fn expected_accounts(&self) -> &'static [(bool, bool)] {
+        match self {
+            VentureLaunchInstruction::CreateVault => CREATE_VAULT_ACCOUNTS,
+            VentureLaunchInstruction::Deposit { .. } => DEPOSIT_ACCOUNTS,
+            VentureLaunchInstruction::Withdraw { .. } => WITHDRAW_ACCOUNTS,
+        }
+    }
+
+    /// Checks the number of accounts and that every account is at least as privileged as the program requires.
+    pub fn validate_accounts(&self, accounts: &[AccountMeta]) -> Result<(), VentureLaunchError> {
+        let expected = self.expected_accounts();
+        if accounts.len() != expected.len() {
+            return Err(VentureLaunchError::InvalidInstructionAccounts);
+        }
+
+        let privileged = accounts
+            .iter()
+            .zip(expected)
+            .all(|(account, (is_signer, is_writable))| {
+                (account.is_signer || !is_signer) && (account.is_writable || !is_writable)
+            });
+        if !privileged {
+            return Err(VentureLaunchError::InvalidInstructionAccounts);
+        }
+
+        Ok(())
+    }
+
+    /// Builds the instruction after validating `accounts`, so a builder can not drift from the on-chain format.
+    pub fn to_instruction(&self, program_id: &Pubkey, accounts: Vec<AccountMeta>) -> Result<Instruction, VentureLaunchError> {
+        self.validate_accounts(&accounts)?;
+
+        Ok(Instruction::new_with_bytes(*program_id, &self.pack(), accounts))
+    }
+
+    /// Decodes an instruction addressed to `program_id`, validating its accounts.
+    pub fn decode(program_id: &Pubkey, instruction: &Instruction) -> Result<Self, VentureLaunchError> {
+        if instruction.program_id != *program_id {
+            return Err(VentureLaunchError::InvalidProgramId);
+        }
+
+        let decoded = Self::unpack(&instruction.data)?;
+        decoded.validate_accounts(&instruction.accounts)?;
+
+        Ok(decoded)
+    }
+
+    /// Decodes every top level instruction of a (historical) transaction message sent to `program_id`.
+    pub fn decode_message(program_id: &Pubkey, message: &Message) -> Result<Vec<(Self, Vec<Pubkey>)>, VentureLaunchError> {
+        let mut decoded = Vec::new();
+
+        for compiled in &message.instructions {
+            if message.account_keys.get(compiled.program_id_index as usize) != Some(program_id) {
+                continue;
+            }
+
+            let mut accounts = Vec::with_capacity(compiled.accounts.len());
+            for index in &compiled.accounts {
+                let index = *index as usize;
+                let pubkey = match message.account_keys.get(index) {
+                    Some(pubkey) => *pubkey,
+                    None => return Err(VentureLaunchError::InvalidInstructionAccounts)
+                };
+                accounts.push(AccountMeta {
+                    pubkey,
+                    is_signer: message.is_signer(index),
+                    is_writable: message.is_writable(index),
+                });
+            }
+
+            let instruction = Self::unpack(&compiled.data)?;
+            instruction.validate_accounts(&accounts)?;
+            decoded.push((instruction, accounts.into_iter().map(|account| account.pubkey).collect()));
+        }
+
+        Ok(decoded)
+    }
+}
+
+pub fn create_vault(
+    vl: &VentureLaunch,
+    payer: &Pubkey,
+) -> Result<Instruction, VentureLaunchError> {
+    VentureLaunchInstruction::CreateVault.to_instruction(
+        &vl.program_id,
+        vec![
+            AccountMeta::new_readonly(*payer, true),
+            AccountMeta::new(vl.vault_account, false),
+            AccountMeta::new(vl.data_account, false),
+            AccountMeta::new_readonly(solana_sdk::sysvar::rent::id(), false),
+            AccountMeta::new_readonly(vl.token_program, false),
+        ],
+    )
+}
+
+pub fn deposit(
+    vl: &VentureLaunch,
+    payer: &Pubkey,
+    ata_account: &Pubkey,
+    amount: u64
+) -> Result<Instruction, VentureLaunchError> {
+    VentureLaunchInstruction::Deposit { amount }.to_instruction(
+        &vl.program_id,
+        vec![
+            AccountMeta::new_readonly(*payer, true),
+            AccountMeta::new(*ata_account, false),
+            AccountMeta::new(vl.vault_account, false),
+            AccountMeta::new(vl.data_account, false),
+            AccountMeta::new_readonly(vl.token_program, false),
+        ],
+    )
+}
+
+pub fn withdraw(
+    vl: &VentureLaunch,
+    payer: &Pubkey,
+    receive_account: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, VentureLaunchError> {
+    let (pda_account, _) = Pubkey::find_program_address(&[b"cryptotracker"], &vl.program_id);
+
+    VentureLaunchInstruction::Withdraw { amount }.to_instruction(
+        &vl.program_id,
+        vec![
+            AccountMeta::new_readonly(*payer, true),
+            AccountMeta::new(*receive_account, false),
+            AccountMeta::new(vl.vault_account, false),
+            AccountMeta::new(vl.data_account, false),
+            AccountMeta::new_readonly(vl.token_program, false),
+            AccountMeta::new(pda_account, false),
+        ],
+    )
+}
\ No newline at end of file
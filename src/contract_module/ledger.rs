@@ -0,0 +1,199 @@
+use std::{
+    fs::OpenOptions,
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::PathBuf,
+    str::FromStr,
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use super::error::VentureLaunchError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedgerEntryKind {
+    Deposit,
+    Withdraw,
+}
+
+/// One deposit into or withdrawal from a vault, as sent by this service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerEntry {
+    pub kind: LedgerEntryKind,
+    /// The signer of the instruction: the depositing investor, or the vault authority for withdrawals
+    pub investor: Pubkey,
+    /// Token account the funds came from (deposit) or went to (withdrawal)
+    pub token_account: Pubkey,
+    pub signature: Signature,
+    pub slot: u64,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub vault_account: Pubkey,
+}
+
+impl LedgerEntry {
+    /// The amount with the sign it has on the vault balance.
+    pub fn signed_amount(&self) -> i128 {
+        match self.kind {
+            LedgerEntryKind::Deposit => self.amount as i128,
+            LedgerEntryKind::Withdraw => -(self.amount as i128),
+        }
+    }
+}
+
+/// Where ledger entries are kept. Entries are only ever appended.
+pub trait LedgerStore: Send + Sync {
+    fn record(&self, entry: LedgerEntry) -> Result<(), VentureLaunchError>;
+    fn entries(&self, vault_account: &Pubkey) -> Result<Vec<LedgerEntry>, VentureLaunchError>;
+}
+
+/// Sum of the signed amounts of `entries`, i.e. what the vault should hold according to the ledger.
+pub fn ledger_balance(entries: &[LedgerEntry]) -> i128 {
+    entries.iter().map(LedgerEntry::signed_amount).sum()
+}
+
+#[derive(Default)]
+pub struct InMemoryLedger {
+    entries: Mutex<Vec<LedgerEntry>>,
+}
+
+impl LedgerStore for InMemoryLedger {
+    fn record(&self, entry: LedgerEntry) -> Result<(), VentureLaunchError> {
+        match self.entries.lock() {
+            Ok(mut entries) => entries.push(entry),
+            Err(poisoned) => poisoned.into_inner().push(entry)
+        };
+
+        Ok(())
+    }
+
+    fn entries(&self, vault_account: &Pubkey) -> Result<Vec<LedgerEntry>, VentureLaunchError> {
+        let entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        Ok(entries.iter().filter(|entry| entry.vault_account == *vault_account).cloned().collect())
+    }
+}
+
+/// Ledger persisted as one JSON object per line, so it survives restarts and can be inspected by hand.
+pub struct JsonLinesLedger {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+/// On-disk form of a `LedgerEntry`, with keys and signatures base58 encoded.
+#[derive(Serialize, Deserialize)]
+struct StoredLedgerEntry {
+    kind: LedgerEntryKind,
+    investor: String,
+    token_account: String,
+    signature: String,
+    slot: u64,
+    amount: u64,
+    mint: String,
+    vault_account: String,
+}
+
+impl StoredLedgerEntry {
+    fn from_entry(entry: &LedgerEntry) -> Self {
+        StoredLedgerEntry {
+            kind: entry.kind,
+            investor: entry.investor.to_string(),
+            token_account: entry.token_account.to_string(),
+            signature: entry.signature.to_string(),
+            slot: entry.slot,
+            amount: entry.amount,
+            mint: entry.mint.to_string(),
+            vault_account: entry.vault_account.to_string(),
+        }
+    }
+
+    fn into_entry(self) -> Result<LedgerEntry, VentureLaunchError> {
+        let pubkey = |value: &str| match Pubkey::from_str(value) {
+            Ok(pubkey) => Ok(pubkey),
+            Err(_) => Err(VentureLaunchError::FailedToReadLedger)
+        };
+        let signature = match Signature::from_str(&self.signature) {
+            Ok(signature) => signature,
+            Err(_) => return Err(VentureLaunchError::FailedToReadLedger)
+        };
+
+        Ok(LedgerEntry {
+            kind: self.kind,
+            investor: pubkey(&self.investor)?,
+            token_account: pubkey(&self.token_account)?,
+            signature,
+            slot: self.slot,
+            amount: self.amount,
+            mint: pubkey(&self.mint)?,
+            vault_account: pubkey(&self.vault_account)?,
+        })
+    }
+}
+
+impl JsonLinesLedger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JsonLinesLedger {
+            path: path.into(),
+            write_lock: Mutex::new(()),
+        }
+    }
+}
+
+impl LedgerStore for JsonLinesLedger {
+    fn record(&self, entry: LedgerEntry) -> Result<(), VentureLaunchError> {
+        let mut line = match serde_json::to_string(&StoredLedgerEntry::from_entry(&entry)) {
+            Ok(line) => line,
+            Err(_) => return Err(VentureLaunchError::FailedToRecordLedgerEntry)
+        };
+        line.push('\n');
+
+        let _guard = match self.write_lock.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner()
+        };
+        let mut file = match OpenOptions::new().create(true).append(true).open(&self.path) {
+            Ok(file) => file,
+            Err(_) => return Err(VentureLaunchError::FailedToRecordLedgerEntry)
+        };
+
+        match file.write_all(line.as_bytes()) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(VentureLaunchError::FailedToRecordLedgerEntry)
+        }
+    }
+
+    fn entries(&self, vault_account: &Pubkey) -> Result<Vec<LedgerEntry>, VentureLaunchError> {
+        let file = match OpenOptions::new().read(true).open(&self.path) {
+            Ok(file) => file,
+            // Nothing was recorded yet
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(_) => return Err(VentureLaunchError::FailedToReadLedger)
+        };
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => return Err(VentureLaunchError::FailedToReadLedger)
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let stored: StoredLedgerEntry = match serde_json::from_str(&line) {
+                Ok(stored) => stored,
+                Err(_) => return Err(VentureLaunchError::FailedToReadLedger)
+            };
+            let entry = stored.into_entry()?;
+            if entry.vault_account == *vault_account {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+}
@@ -0,0 +1,207 @@
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::{Keypair, Signature, Signer}
+};
+use squads_multisig::state::ProposalStatus;
+
+use super::{
+    associated_token::{instruction::create_associated_token_account_idempotent, token::{get_mint_info, MintInfo}},
+    error::VentureLaunchError,
+};
+use crate::multisig_utils::{
+    base_multisig::BaseMultisig,
+    base_multisig_trait::BaseMultisigTrait,
+    business_analyst_multisig_trait::{BusinessAnalystMultisigTrait, TransactionCreateAction},
+};
+
+/// Funds released to a startup from the DAO treasury (the Squads vault) once a milestone is approved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tranche {
+    /// Amount in base units of `mint`
+    pub amount: u64,
+    pub mint: Pubkey,
+    /// Wallet of the startup, the tokens go to its associated token account
+    pub recipient: Pubkey,
+    pub description: String,
+}
+
+impl Tranche {
+    /// Instructions moving the tranche out of the token account of `vault_pda`, to be run as a vault transaction.
+    /// The vault pays for the associated token account of the recipient if it does not exist yet.
+    pub fn instructions(&self, vault_pda: &Pubkey, mint_info: &MintInfo) -> Result<Vec<Instruction>, VentureLaunchError> {
+        let source = mint_info.get_associated_token_address(vault_pda);
+        let destination = mint_info.get_associated_token_address(&self.recipient);
+
+        let transfer_ix = match spl_token_2022::instruction::transfer_checked(
+            &mint_info.token_program,
+            &source,
+            &self.mint,
+            &destination,
+            vault_pda,
+            &[],
+            self.amount,
+            mint_info.decimals
+        ) {
+            Ok(ix) => ix,
+            Err(_) => return Err(VentureLaunchError::FailedToBuildTokenInstruction)
+        };
+
+        Ok(vec![
+            create_associated_token_account_idempotent(
+                vault_pda,
+                &destination,
+                &self.recipient,
+                &self.mint,
+                &mint_info.token_program
+            ),
+            transfer_ix,
+        ])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MilestoneStatus {
+    /// Not proposed to the DAO yet
+    Pending,
+    /// Proposed and being voted on
+    Proposed,
+    /// Approved by the investors, waiting to be released
+    Approved,
+    /// The tranche was transferred
+    Released,
+    /// Rejected or cancelled, or invalidated by a change of the multisig; it can be proposed again
+    Rejected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Milestone {
+    pub tranche: Tranche,
+    /// Multisig transaction the tranche was last proposed as
+    pub transaction_index: Option<u64>,
+}
+
+/// The tranches of one startup, released one milestone at a time through vault transactions of its DAO.
+/// The lifecycle of every milestone is read from the status of its on-chain proposal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MilestonePlan {
+    pub multisig_pda: Pubkey,
+    pub milestones: Vec<Milestone>,
+}
+
+impl MilestonePlan {
+    pub fn new(multisig_pda: Pubkey, tranches: Vec<Tranche>) -> Result<MilestonePlan, VentureLaunchError> {
+        if tranches.iter().any(|tranche| tranche.amount == 0) {
+            return Err(VentureLaunchError::InvalidAmount);
+        }
+
+        Ok(MilestonePlan {
+            multisig_pda,
+            milestones: tranches
+                .into_iter()
+                .map(|tranche| Milestone { tranche, transaction_index: None })
+                .collect(),
+        })
+    }
+
+    /// Creates the vault transaction and proposal of `milestone` and returns its transaction index.
+    /// A milestone can only be proposed while it is pending or after its previous proposal was rejected.
+    pub async fn propose(
+        &mut self,
+        multisig: &BaseMultisig,
+        proposer: &Keypair,
+        milestone: usize,
+    ) -> Result<u64, VentureLaunchError> {
+        match self.status(multisig, milestone).await? {
+            MilestoneStatus::Pending | MilestoneStatus::Rejected => {},
+            _ => return Err(VentureLaunchError::MilestoneAlreadyProposed)
+        };
+
+        let tranche = &self.milestones[milestone].tranche;
+        let mint_info = get_mint_info(multisig.get_rpc_client(), &tranche.mint).await?;
+        let instructions = tranche.instructions(&multisig.get_vault_pda(), &mint_info)?;
+
+        let (transaction_index, _) = multisig.submit_transaction_create(
+            proposer,
+            TransactionCreateAction::VaultInstructions {
+                instructions,
+                memo: Some(format!("Milestone {}: {}", milestone + 1, tranche.description))
+            }
+        ).await?;
+        self.milestones[milestone].transaction_index = Some(transaction_index);
+
+        Ok(transaction_index)
+    }
+
+    pub async fn status(&self, multisig: &BaseMultisig, milestone: usize) -> Result<MilestoneStatus, VentureLaunchError> {
+        let transaction_index = match self.get(multisig, milestone)?.transaction_index {
+            Some(transaction_index) => transaction_index,
+            None => return Ok(MilestoneStatus::Pending)
+        };
+
+        let proposal = match multisig.get_proposal(transaction_index).await? {
+            Some(proposal) => proposal,
+            // Closed after being executed or rejected, which of the two can not be told any more
+            None => return Err(VentureLaunchError::MilestoneProposalClosed)
+        };
+
+        #[allow(deprecated)]
+        let status = match proposal.status {
+            ProposalStatus::Executed { .. } => MilestoneStatus::Released,
+            ProposalStatus::Rejected { .. } | ProposalStatus::Cancelled { .. } => MilestoneStatus::Rejected,
+            ProposalStatus::Draft { .. } | ProposalStatus::Active { .. } => MilestoneStatus::Proposed,
+            ProposalStatus::Approved { .. } | ProposalStatus::Executing => MilestoneStatus::Approved,
+            // A status added in a later program version can neither be released nor proposed again
+            _ => MilestoneStatus::Proposed,
+        };
+
+        // Squads refuses votes on and execution of stale proposals
+        if matches!(status, MilestoneStatus::Proposed | MilestoneStatus::Approved) && multisig.is_stale(transaction_index).await? {
+            return Ok(MilestoneStatus::Rejected);
+        }
+
+        Ok(status)
+    }
+
+    pub async fn statuses(&self, multisig: &BaseMultisig) -> Result<Vec<MilestoneStatus>, VentureLaunchError> {
+        let mut statuses = Vec::with_capacity(self.milestones.len());
+        for milestone in 0..self.milestones.len() {
+            statuses.push(self.status(multisig, milestone).await?);
+        }
+
+        Ok(statuses)
+    }
+
+    /// Executes the approved vault transaction of `milestone`, transferring the tranche.
+    pub async fn release(
+        &self,
+        multisig: &BaseMultisig,
+        executor: &Keypair,
+        milestone: usize,
+    ) -> Result<Signature, VentureLaunchError> {
+        if self.status(multisig, milestone).await? != MilestoneStatus::Approved {
+            return Err(VentureLaunchError::MilestoneNotApproved);
+        }
+        let transaction_index = match self.milestones[milestone].transaction_index {
+            Some(transaction_index) => transaction_index,
+            None => return Err(VentureLaunchError::MilestoneNotApproved)
+        };
+
+        let mut tx = multisig.transaction_vault_transaction_execute_at(executor.pubkey(), transaction_index).await?;
+        let recent_blockhash = tx.message.recent_blockhash;
+        if tx.try_sign(&[executor], recent_blockhash).is_err() {
+            return Err(VentureLaunchError::FailedToSignTransaction);
+        }
+
+        Ok(multisig.send_and_confirm_transaction(&tx).await?)
+    }
+
+    fn get(&self, multisig: &BaseMultisig, milestone: usize) -> Result<&Milestone, VentureLaunchError> {
+        if multisig.get_multisig_pda() != self.multisig_pda {
+            return Err(VentureLaunchError::MilestoneMultisigMismatch);
+        }
+
+        match self.milestones.get(milestone) {
+            Some(milestone) => Ok(milestone),
+            None => Err(VentureLaunchError::UnknownMilestone)
+        }
+    }
+}
@@ -0,0 +1,13 @@
+pub mod venture_launch;
+pub mod instruction;
+pub mod associated_token;
+pub mod state;
+pub mod error;
+pub mod governance;
+pub mod ledger;
+pub mod reconciliation;
+pub mod campaign;
+pub mod refunds;
+pub mod milestone;
+pub mod vault_state;
+mod test;
\ No newline at end of file
@@ -0,0 +1,187 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use solana_sdk::{instruction::Instruction, signature::Signature};
+use tokio::task::JoinHandle;
+
+use super::{
+    error::VentureLaunchError,
+    instruction::VentureLaunchInstruction,
+    ledger::{ledger_balance, LedgerEntry, LedgerEntryKind},
+    venture_launch::VentureLaunch,
+};
+
+/// A mismatch between two of the three views of a vault's balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Discrepancy {
+    LedgerDiffersFromTracker { ledger_balance: i128, tracked_amount: u64 },
+    LedgerDiffersFromVault { ledger_balance: i128, vault_token_balance: u64 },
+    TrackerDiffersFromVault { tracked_amount: u64, vault_token_balance: u64 },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::LedgerDiffersFromTracker { ledger_balance, tracked_amount } =>
+                write!(f, "ledger balance {} differs from tracked amount {}", ledger_balance, tracked_amount),
+            Discrepancy::LedgerDiffersFromVault { ledger_balance, vault_token_balance } =>
+                write!(f, "ledger balance {} differs from vault token balance {}", ledger_balance, vault_token_balance),
+            Discrepancy::TrackerDiffersFromVault { tracked_amount, vault_token_balance } =>
+                write!(f, "tracked amount {} differs from vault token balance {}", tracked_amount, vault_token_balance),
+        }
+    }
+}
+
+/// The ledger sum, `CryptoTracker.amount` and the vault token account balance at one point in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconciliationReport {
+    pub entry_count: usize,
+    pub ledger_balance: i128,
+    pub tracked_amount: u64,
+    pub vault_token_balance: u64,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl ReconciliationReport {
+    pub fn new(entries: &[LedgerEntry], tracked_amount: u64, vault_token_balance: u64) -> Self {
+        let ledger_balance = ledger_balance(entries);
+        let mut discrepancies = Vec::new();
+
+        if ledger_balance != tracked_amount as i128 {
+            discrepancies.push(Discrepancy::LedgerDiffersFromTracker { ledger_balance, tracked_amount });
+        }
+        if ledger_balance != vault_token_balance as i128 {
+            discrepancies.push(Discrepancy::LedgerDiffersFromVault { ledger_balance, vault_token_balance });
+        }
+        if tracked_amount != vault_token_balance {
+            discrepancies.push(Discrepancy::TrackerDiffersFromVault { tracked_amount, vault_token_balance });
+        }
+
+        ReconciliationReport {
+            entry_count: entries.len(),
+            ledger_balance,
+            tracked_amount,
+            vault_token_balance,
+            discrepancies,
+        }
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+impl VentureLaunch {
+    /// Compares the ledger of this vault with the on-chain `CryptoTracker` and the vault token account.
+    pub async fn reconcile(&self) -> Result<ReconciliationReport, VentureLaunchError> {
+        let ledger = match self.ledger.as_ref() {
+            Some(ledger) => ledger,
+            None => return Err(VentureLaunchError::LedgerNotConfigured)
+        };
+
+        let entries = ledger.entries(&self.vault_account)?;
+        let state = self.get_vault_state().await?;
+
+        Ok(ReconciliationReport::new(&entries, state.tracked_amount, state.token_balance))
+    }
+
+    /// Records the deposits and withdrawals on this vault among `instructions`, which landed in `signature`.
+    /// Every entry is attempted; if any could not be recorded the error carries `signature`, so the caller
+    /// knows the transaction went through and must not be sent again. The missing entry then shows up as
+    /// a discrepancy on the next reconciliation.
+    pub(crate) async fn record_ledger_entries(
+        &self,
+        instructions: &[Instruction],
+        signature: &Signature,
+    ) -> Result<(), VentureLaunchError> {
+        let ledger = match self.ledger.as_ref() {
+            Some(ledger) => ledger,
+            None => return Ok(())
+        };
+
+        let mut entries = Vec::new();
+        for instruction in instructions {
+            // Instructions of other programs (token wrapping, ATA creation) are not part of the ledger
+            let (kind, amount) = match VentureLaunchInstruction::decode(&self.program_id, instruction) {
+                Ok(VentureLaunchInstruction::Deposit { amount }) => (LedgerEntryKind::Deposit, amount),
+                Ok(VentureLaunchInstruction::Withdraw { amount }) => (LedgerEntryKind::Withdraw, amount),
+                _ => continue
+            };
+            if instruction.accounts[2].pubkey != self.vault_account {
+                continue;
+            }
+
+            entries.push((kind, instruction.accounts[0].pubkey, instruction.accounts[1].pubkey, amount));
+        }
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let slot = match self.rpc_client.get_signature_slot(signature).await {
+            Ok(Some(slot)) => slot,
+            _ => return Err(VentureLaunchError::LedgerEntriesNotRecorded(*signature))
+        };
+
+        let mut recorded = true;
+        for (kind, investor, token_account, amount) in entries {
+            let entry = LedgerEntry {
+                kind,
+                investor,
+                token_account,
+                signature: *signature,
+                slot,
+                amount,
+                mint: self.mint,
+                vault_account: self.vault_account,
+            };
+
+            recorded &= ledger.record(entry).is_ok();
+        }
+
+        if !recorded {
+            return Err(VentureLaunchError::LedgerEntriesNotRecorded(*signature));
+        }
+
+        Ok(())
+    }
+}
+
+/// Reconciles `vl` every `period` and logs the outcome, until the returned handle is aborted.
+pub fn spawn_reconciliation_job(vl: Arc<VentureLaunch>, period: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            match vl.reconcile().await {
+                Ok(report) if report.is_consistent() => {
+                    println!(
+                        "[{:?} RECONCILIATION INFO] Vault {} matches its ledger ({} entries, balance {})",
+                        chrono::Utc::now(),
+                        vl.vault_account,
+                        report.entry_count,
+                        report.vault_token_balance
+                    );
+                }
+                Ok(report) => {
+                    for discrepancy in &report.discrepancies {
+                        eprintln!(
+                            "[{:?} RECONCILIATION ERROR] Vault {}: {}",
+                            chrono::Utc::now(),
+                            vl.vault_account,
+                            discrepancy
+                        );
+                    }
+                }
+                Err(error) => {
+                    eprintln!(
+                        "[{:?} RECONCILIATION ERROR] Failed to reconcile vault {}: {}",
+                        chrono::Utc::now(),
+                        vl.vault_account,
+                        error
+                    );
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,330 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    path::PathBuf,
+    str::FromStr,
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature};
+
+use super::{campaign::Refund, error::VentureLaunchError};
+
+/// A withdrawal sent for a refund, saved before sending so a later settlement can tell whether it
+/// landed before sending another one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefundAttempt {
+    pub signature: Signature,
+    /// The attempt can no longer land once this blockhash expired
+    pub recent_blockhash: Hash,
+}
+
+/// One refund of a plan and whether it was sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefundRecord {
+    pub refund: Refund,
+    /// Signature of the withdrawal once the refund went through
+    pub signature: Option<Signature>,
+    /// The last withdrawal sent while the refund was not known to have gone through
+    pub attempt: Option<RefundAttempt>,
+}
+
+/// The refunds of a failed campaign, split once when the campaign is first settled so later
+/// settlements pay out the same shares whatever happened to the vault balance in between.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefundPlan {
+    pub vault_account: Pubkey,
+    /// Number of ledger entries of the vault when the plan was made, withdrawals recorded after it
+    /// can only be refunds of the plan
+    pub ledger_len: usize,
+    pub refunds: Vec<RefundRecord>,
+}
+
+impl RefundPlan {
+    pub fn pending(&self) -> Vec<Refund> {
+        self.refunds.iter().filter(|record| record.signature.is_none()).map(|record| record.refund).collect()
+    }
+}
+
+/// Where refund plans are kept, one per vault.
+pub trait RefundStore: Send + Sync {
+    fn plan(&self, vault_account: &Pubkey) -> Result<Option<RefundPlan>, VentureLaunchError>;
+    /// Keeps `plan` unless the vault already has one, and returns the plan in effect.
+    fn create_plan(&self, plan: RefundPlan) -> Result<RefundPlan, VentureLaunchError>;
+    /// Saves `attempt` as the withdrawal about to be sent for the refund to `token_account`.
+    fn record_attempt(
+        &self,
+        vault_account: &Pubkey,
+        token_account: &Pubkey,
+        attempt: RefundAttempt,
+    ) -> Result<(), VentureLaunchError>;
+    fn mark_refunded(
+        &self,
+        vault_account: &Pubkey,
+        token_account: &Pubkey,
+        signature: Signature,
+    ) -> Result<(), VentureLaunchError>;
+}
+
+fn update(
+    plan: &mut RefundPlan,
+    token_account: &Pubkey,
+    change: impl FnOnce(&mut RefundRecord),
+) -> Result<(), VentureLaunchError> {
+    match plan.refunds.iter_mut().find(|record| record.refund.token_account == *token_account) {
+        Some(record) => {
+            change(record);
+            Ok(())
+        }
+        None => Err(VentureLaunchError::FailedToRecordRefund)
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryRefundStore {
+    plans: Mutex<HashMap<Pubkey, RefundPlan>>,
+}
+
+impl RefundStore for InMemoryRefundStore {
+    fn plan(&self, vault_account: &Pubkey) -> Result<Option<RefundPlan>, VentureLaunchError> {
+        let plans = match self.plans.lock() {
+            Ok(plans) => plans,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        Ok(plans.get(vault_account).cloned())
+    }
+
+    fn create_plan(&self, plan: RefundPlan) -> Result<RefundPlan, VentureLaunchError> {
+        let mut plans = match self.plans.lock() {
+            Ok(plans) => plans,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        Ok(plans.entry(plan.vault_account).or_insert(plan).clone())
+    }
+
+    fn record_attempt(
+        &self,
+        vault_account: &Pubkey,
+        token_account: &Pubkey,
+        attempt: RefundAttempt,
+    ) -> Result<(), VentureLaunchError> {
+        let mut plans = match self.plans.lock() {
+            Ok(plans) => plans,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        match plans.get_mut(vault_account) {
+            Some(plan) => update(plan, token_account, |record| record.attempt = Some(attempt)),
+            None => Err(VentureLaunchError::FailedToRecordRefund)
+        }
+    }
+
+    fn mark_refunded(
+        &self,
+        vault_account: &Pubkey,
+        token_account: &Pubkey,
+        signature: Signature,
+    ) -> Result<(), VentureLaunchError> {
+        let mut plans = match self.plans.lock() {
+            Ok(plans) => plans,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        match plans.get_mut(vault_account) {
+            Some(plan) => update(plan, token_account, |record| record.signature = Some(signature)),
+            None => Err(VentureLaunchError::FailedToRecordRefund)
+        }
+    }
+}
+
+/// Refund plans persisted as one JSON file per vault in `dir`, rewritten whole on every change.
+pub struct JsonRefundStore {
+    dir: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+/// On-disk form of a `RefundRecord`, with keys and signatures base58 encoded.
+#[derive(Serialize, Deserialize)]
+struct StoredRefund {
+    investor: String,
+    token_account: String,
+    amount: u64,
+    signature: Option<String>,
+    #[serde(default)]
+    attempt: Option<StoredRefundAttempt>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredRefundAttempt {
+    signature: String,
+    recent_blockhash: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredRefundPlan {
+    ledger_len: usize,
+    refunds: Vec<StoredRefund>,
+}
+
+impl StoredRefundPlan {
+    fn from_plan(plan: &RefundPlan) -> Self {
+        StoredRefundPlan {
+            ledger_len: plan.ledger_len,
+            refunds: plan.refunds.iter().map(|record| StoredRefund {
+                investor: record.refund.investor.to_string(),
+                token_account: record.refund.token_account.to_string(),
+                amount: record.refund.amount,
+                signature: record.signature.map(|signature| signature.to_string()),
+                attempt: record.attempt.map(|attempt| StoredRefundAttempt {
+                    signature: attempt.signature.to_string(),
+                    recent_blockhash: attempt.recent_blockhash.to_string(),
+                }),
+            }).collect(),
+        }
+    }
+
+    fn into_plan(self, vault_account: &Pubkey) -> Result<RefundPlan, VentureLaunchError> {
+        let pubkey = |value: &str| match Pubkey::from_str(value) {
+            Ok(pubkey) => Ok(pubkey),
+            Err(_) => Err(VentureLaunchError::FailedToReadRefunds)
+        };
+
+        let mut refunds = Vec::new();
+        for stored in self.refunds {
+            let signature = match stored.signature.as_deref().map(Signature::from_str) {
+                None => None,
+                Some(Ok(signature)) => Some(signature),
+                Some(Err(_)) => return Err(VentureLaunchError::FailedToReadRefunds)
+            };
+            let attempt = match stored.attempt {
+                None => None,
+                Some(attempt) => match (Signature::from_str(&attempt.signature), Hash::from_str(&attempt.recent_blockhash)) {
+                    (Ok(signature), Ok(recent_blockhash)) => Some(RefundAttempt { signature, recent_blockhash }),
+                    _ => return Err(VentureLaunchError::FailedToReadRefunds)
+                }
+            };
+            refunds.push(RefundRecord {
+                refund: Refund {
+                    investor: pubkey(&stored.investor)?,
+                    token_account: pubkey(&stored.token_account)?,
+                    amount: stored.amount,
+                },
+                signature,
+                attempt,
+            });
+        }
+
+        Ok(RefundPlan { vault_account: *vault_account, ledger_len: self.ledger_len, refunds })
+    }
+}
+
+impl JsonRefundStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        JsonRefundStore {
+            dir: dir.into(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn path(&self, vault_account: &Pubkey) -> PathBuf {
+        self.dir.join(format!("{}.json", vault_account))
+    }
+
+    fn read(&self, vault_account: &Pubkey) -> Result<Option<RefundPlan>, VentureLaunchError> {
+        let content = match fs::read_to_string(self.path(vault_account)) {
+            Ok(content) => content,
+            // The campaign was not settled yet
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(_) => return Err(VentureLaunchError::FailedToReadRefunds)
+        };
+
+        match serde_json::from_str::<StoredRefundPlan>(&content) {
+            Ok(stored) => Ok(Some(stored.into_plan(vault_account)?)),
+            Err(_) => Err(VentureLaunchError::FailedToReadRefunds)
+        }
+    }
+
+    fn update(
+        &self,
+        vault_account: &Pubkey,
+        token_account: &Pubkey,
+        change: impl FnOnce(&mut RefundRecord),
+    ) -> Result<(), VentureLaunchError> {
+        let _guard = match self.write_lock.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        let mut plan = match self.read(vault_account)? {
+            Some(plan) => plan,
+            None => return Err(VentureLaunchError::FailedToRecordRefund)
+        };
+        update(&mut plan, token_account, change)?;
+
+        self.write(&plan)
+    }
+
+    /// Writes through a temporary file, so a crash never leaves a half written plan behind.
+    fn write(&self, plan: &RefundPlan) -> Result<(), VentureLaunchError> {
+        let content = match serde_json::to_string_pretty(&StoredRefundPlan::from_plan(plan)) {
+            Ok(content) => content,
+            Err(_) => return Err(VentureLaunchError::FailedToRecordRefund)
+        };
+        if fs::create_dir_all(&self.dir).is_err() {
+            return Err(VentureLaunchError::FailedToRecordRefund);
+        }
+
+        let path = self.path(&plan.vault_account);
+        let temporary = path.with_extension("json.tmp");
+        if fs::write(&temporary, content).is_err() {
+            return Err(VentureLaunchError::FailedToRecordRefund);
+        }
+
+        match fs::rename(&temporary, &path) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(VentureLaunchError::FailedToRecordRefund)
+        }
+    }
+}
+
+impl RefundStore for JsonRefundStore {
+    fn plan(&self, vault_account: &Pubkey) -> Result<Option<RefundPlan>, VentureLaunchError> {
+        self.read(vault_account)
+    }
+
+    fn create_plan(&self, plan: RefundPlan) -> Result<RefundPlan, VentureLaunchError> {
+        let _guard = match self.write_lock.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        if let Some(existing) = self.read(&plan.vault_account)? {
+            return Ok(existing);
+        }
+        self.write(&plan)?;
+
+        Ok(plan)
+    }
+
+    fn record_attempt(
+        &self,
+        vault_account: &Pubkey,
+        token_account: &Pubkey,
+        attempt: RefundAttempt,
+    ) -> Result<(), VentureLaunchError> {
+        self.update(vault_account, token_account, |record| record.attempt = Some(attempt))
+    }
+
+    fn mark_refunded(
+        &self,
+        vault_account: &Pubkey,
+        token_account: &Pubkey,
+        signature: Signature,
+    ) -> Result<(), VentureLaunchError> {
+        self.update(vault_account, token_account, |record| record.signature = Some(signature))
+    }
+}
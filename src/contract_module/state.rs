@@ -0,0 +1,55 @@
+use solana_sdk::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+/// Content of the data account of one vault, laid out the way the crypto_tracker program writes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CryptoTracker {
+    pub is_initialized: bool,
+    pub initializer_pubkey: Pubkey,
+    pub vault_account_pubkey: Pubkey,
+    /// Deposits minus withdrawals, in base units of the vault mint
+    pub amount: u64,
+}
+
+impl Sealed for CryptoTracker {}
+
+impl IsInitialized for CryptoTracker {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for CryptoTracker {
+    const LEN: usize = 73;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let is_initialized = match src[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let mut amount = [0; 8];
+        amount.copy_from_slice(&src[65..73]);
+
+        Ok(CryptoTracker {
+            is_initialized,
+            initializer_pubkey: Pubkey::try_from(&src[1..33]).map_err(|_| ProgramError::InvalidAccountData)?,
+            vault_account_pubkey: Pubkey::try_from(&src[33..65]).map_err(|_| ProgramError::InvalidAccountData)?,
+            amount: u64::from_le_bytes(amount),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[0] = self.is_initialized as u8;
+        dst[1..33].copy_from_slice(self.initializer_pubkey.as_ref());
+        dst[33..65].copy_from_slice(self.vault_account_pubkey.as_ref());
+        dst[65..73].copy_from_slice(&self.amount.to_le_bytes());
+    }
+}
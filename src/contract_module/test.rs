@@ -0,0 +1,897 @@
+#![cfg(test)]
+
+use solana_sdk::{
+    account::Account, instruction::AccountMeta, message::Message, program_option::COption, program_pack::Pack,
+    pubkey::Pubkey, signature::{Keypair, Signature, Signer}
+};
+use std::{str::FromStr, sync::Arc};
+
+use crate::contract_module::{
+    venture_launch::{VaultAddresses, VentureLaunch},
+    associated_token::{self, token::{self as token_utils, MintInfo}, utils::TOKEN_2022_PROGRAM_ID},
+    error::VentureLaunchError,
+    instruction::{self, VentureLaunchInstruction},
+    ledger::{InMemoryLedger, JsonLinesLedger, LedgerEntry, LedgerEntryKind, LedgerStore},
+    reconciliation::Discrepancy,
+    campaign::{pro_rata_refunds, Campaign, CampaignConfig, CampaignSettlement, CampaignStatus, Refund},
+    refunds::{InMemoryRefundStore, JsonRefundStore, RefundAttempt, RefundPlan, RefundRecord, RefundStore},
+    milestone::{MilestonePlan, MilestoneStatus, Tranche},
+    vault_state::VaultStateIssue,
+};
+use crate::cluster_utils::cluster_profile::VENTURE_LAUNCH_PROGRAM_ID;
+use crate::rpc_utils::{in_process_bank::InProcessBank, rpc_backend::{RpcBackend, SendFailure}};
+use crate::multisig_utils::{
+    base_multisig::{BaseMultisig, BaseMultisigCreateArgs, DEFAULT_MULTISIG_CACHE_MAX_AGE},
+    base_multisig_trait::BaseMultisigTrait,
+    business_analyst_multisig_trait::BusinessAnalystMultisigTrait,
+    investor_multisig_trait::InvestorMultisigTrait,
+};
+use squads_multisig::{squads_multisig_program, state::{Member, Permission, Permissions}};
+
+fn seed_native_mint(bank: &InProcessBank) {
+    let mint = spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply: 0,
+        decimals: 9,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint::pack(mint, &mut data).unwrap();
+
+    bank.set_account(spl_token::native_mint::id(), Account {
+        lamports: 1_461_600,
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    });
+}
+
+// Stands in for `create_native_sol_ata` + `deposit_to_wrapped_sol_ata`, so tests need not go through the ATA program
+fn seed_wrapped_sol_ata(bank: &InProcessBank, owner: &Pubkey, amount: u64) -> Pubkey {
+    let rent_exempt_reserve = 2_039_280;
+    let address = associated_token::utils::get_associated_token_address(&spl_token::native_mint::id(), owner);
+    let token_account = spl_token::state::Account {
+        mint: spl_token::native_mint::id(),
+        owner: *owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::Some(rent_exempt_reserve),
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account::pack(token_account, &mut data).unwrap();
+
+    bank.set_account(address, Account {
+        lamports: rent_exempt_reserve + amount,
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    });
+
+    address
+}
+
+#[tokio::test]
+#[ignore = "needs crypto_tracker.so, built from the venture_launch_contract submodule"]
+async fn contract_module_test() {
+    let native_mint = spl_token::native_mint::id();
+    let program_id = Pubkey::from_str(VENTURE_LAUNCH_PROGRAM_ID).unwrap();
+
+    let bank = InProcessBank::new();
+    bank.add_program("crypto_tracker", program_id);
+    seed_native_mint(&bank);
+
+    // Initialize a keypair for the payer
+    let payer = Keypair::new();
+    bank.request_airdrop(&payer.pubkey(), 10 * 10_u64.pow(9)).await.unwrap();
+    let payer_ata = seed_wrapped_sol_ata(&bank, &payer.pubkey(), 5 * 10_u64.pow(9));
+
+    let rpc_client: Arc<dyn RpcBackend> = Arc::new(bank);
+    let mut vl = VentureLaunch::new(
+        rpc_client.clone(),
+        program_id,
+        Pubkey::default(),
+        Pubkey::default(),
+        native_mint
+    );
+
+    // Contract initialization transaction
+    let signature = vl.invoke_create_vault(&payer, "startup-1").await.unwrap();
+    println!("[create_vault] Signature: {:?}", signature);
+
+    let addresses = VaultAddresses::derive(&payer.pubkey(), &program_id, "startup-1", &native_mint, &spl_token::id()).unwrap();
+    assert_eq!(addresses.vault_account, vl.vault_account);
+    assert_eq!(addresses.data_account, vl.data_account);
+
+    // Deposit to the vault
+    let signature = vl.invoke_deposit(&payer, &payer_ata, 2 * 10_u64.pow(9)).await.unwrap();
+    println!("[deposit] Signature: {:?}", signature);
+
+    // Withdraw from the vault
+    let signature = vl.invoke_withdraw(&payer, &payer_ata, 10_u64.pow(9)).await.unwrap();
+    println!("[withdraw] Signature: {:?}", signature);
+
+    // Check account data
+    let data = vl.get_vault_data().await.unwrap();
+    assert!(data.is_initialized);
+    assert_eq!(data.initializer_pubkey, payer.pubkey());
+    assert_eq!(data.vault_account_pubkey, vl.vault_account);
+    assert_eq!(data.amount, 10_u64.pow(9));
+    assert_eq!(vl.get_vault_balance().await.unwrap(), 10_u64.pow(9));
+
+    // Reconstruct the client from chain
+    let loaded = VentureLaunch::load(rpc_client.clone(), program_id, &payer.pubkey(), "startup-1", native_mint).await.unwrap();
+    assert_eq!(loaded.vault_account, vl.vault_account);
+    assert_eq!(loaded.data_account, vl.data_account);
+    assert_eq!(loaded.get_vault_balance().await.unwrap(), 10_u64.pow(9));
+
+    assert_eq!(
+        VentureLaunch::load(rpc_client, program_id, &payer.pubkey(), "startup-2", native_mint).await.err(),
+        Some(VentureLaunchError::FailedToFetchDataAccount)
+    );
+}
+
+#[tokio::test]
+async fn missing_vault_returns_error() {
+    let bank = InProcessBank::new();
+    let payer = Keypair::new();
+    bank.request_airdrop(&payer.pubkey(), 10_u64.pow(9)).await.unwrap();
+
+    let rpc_client: Arc<dyn RpcBackend> = Arc::new(bank);
+    let vl = VentureLaunch::new(
+        rpc_client,
+        Pubkey::from_str(VENTURE_LAUNCH_PROGRAM_ID).unwrap(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        spl_token::native_mint::id()
+    );
+
+    assert_eq!(vl.get_vault_balance().await, Err(VentureLaunchError::FailedToFetchDataAccount));
+    assert_eq!(
+        vl.invoke_deposit(&payer, &Pubkey::new_unique(), 10).await,
+        Err(VentureLaunchError::FailedToSendTransaction)
+    );
+}
+
+#[test]
+fn unconfirmed_send_is_not_reported_as_failed() {
+    let signature = Signature::new_unique();
+
+    assert_eq!(
+        VentureLaunchError::TransactionNotConfirmed(signature),
+        VentureLaunchError::from(SendFailure::NotConfirmed(signature))
+    );
+    assert_eq!(VentureLaunchError::FailedToSendTransaction, VentureLaunchError::from(SendFailure::Failed));
+    assert_eq!(VentureLaunchError::FailedToSendTransaction, VentureLaunchError::from(SendFailure::NotSent));
+}
+
+#[test]
+fn instruction_pack_unpack() {
+    for ix in [
+        VentureLaunchInstruction::CreateVault,
+        VentureLaunchInstruction::Deposit { amount: 2 * 10_u64.pow(9) },
+        VentureLaunchInstruction::Withdraw { amount: u64::MAX },
+    ] {
+        assert_eq!(VentureLaunchInstruction::unpack(&ix.pack()), Ok(ix));
+    }
+
+    // Matches the bytes the program was always sent
+    assert_eq!(VentureLaunchInstruction::Deposit { amount: 1 }.pack(), vec![1, 1, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(VentureLaunchInstruction::unpack(&[]), Err(VentureLaunchError::InvalidInstructionData));
+    assert_eq!(VentureLaunchInstruction::unpack(&[0, 1]), Err(VentureLaunchError::InvalidInstructionData));
+    assert_eq!(VentureLaunchInstruction::unpack(&[2, 1, 0]), Err(VentureLaunchError::InvalidInstructionData));
+    assert_eq!(VentureLaunchInstruction::unpack(&[3]), Err(VentureLaunchError::InvalidInstructionData));
+}
+
+#[test]
+fn instruction_decode_message() {
+    let program_id = Pubkey::from_str(VENTURE_LAUNCH_PROGRAM_ID).unwrap();
+    let payer = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let rpc_client: Arc<dyn RpcBackend> = Arc::new(InProcessBank::new());
+    let vl = VentureLaunch::new(rpc_client, program_id, Pubkey::new_unique(), Pubkey::new_unique(), spl_token::native_mint::id());
+
+    let deposit = instruction::deposit(&vl, &payer, &source, 42).unwrap();
+    let message = Message::new(
+        &[solana_sdk::system_instruction::transfer(&payer, &source, 1), deposit.clone()],
+        Some(&payer)
+    );
+
+    let decoded = VentureLaunchInstruction::decode_message(&program_id, &message).unwrap();
+    assert_eq!(decoded.len(), 1);
+    assert_eq!(decoded[0].0, VentureLaunchInstruction::Deposit { amount: 42 });
+    assert_eq!(decoded[0].1, deposit.accounts.iter().map(|account| account.pubkey).collect::<Vec<_>>());
+    assert_eq!(VentureLaunchInstruction::decode(&program_id, &deposit), Ok(VentureLaunchInstruction::Deposit { amount: 42 }));
+
+    let mut unsigned = deposit.accounts.clone();
+    unsigned[0] = AccountMeta::new_readonly(payer, false);
+    assert_eq!(
+        VentureLaunchInstruction::Deposit { amount: 42 }.validate_accounts(&unsigned),
+        Err(VentureLaunchError::InvalidInstructionAccounts)
+    );
+    assert_eq!(
+        VentureLaunchInstruction::Deposit { amount: 42 }.validate_accounts(&deposit.accounts[..4]),
+        Err(VentureLaunchError::InvalidInstructionAccounts)
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs crypto_tracker.so, built from the venture_launch_contract submodule"]
+async fn governed_withdraw() {
+    let native_mint = spl_token::native_mint::id();
+    let program_id = Pubkey::from_str(VENTURE_LAUNCH_PROGRAM_ID).unwrap();
+
+    let bank = InProcessBank::with_squads(squads_multisig_program::ID);
+    bank.add_program("crypto_tracker", program_id);
+    seed_native_mint(&bank);
+
+    let ba = Keypair::new();
+    let investor = Keypair::new();
+    bank.request_airdrop(&ba.pubkey(), 10 * 10_u64.pow(9)).await.unwrap();
+    bank.request_airdrop(&investor.pubkey(), 10_u64.pow(9)).await.unwrap();
+    let ba_ata = seed_wrapped_sol_ata(&bank, &ba.pubkey(), 5 * 10_u64.pow(9));
+    let investor_ata = seed_wrapped_sol_ata(&bank, &investor.pubkey(), 0);
+
+    let rpc_client: Arc<dyn RpcBackend> = Arc::new(bank);
+    let create_key = Keypair::new();
+    let multisig = BaseMultisig::new(BaseMultisigCreateArgs {
+        rpc_client: rpc_client.clone(),
+        program_id: squads_multisig_program::ID,
+        multisig_create_keypair: create_key.insecure_clone(),
+        creator: ba.pubkey(),
+        cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE,
+    }).await.unwrap();
+    // The business analyst only proposes, the investor votes and executes
+    let members = [Member { key: investor.pubkey(), permissions: Permissions::from_vec(&[Permission::Vote, Permission::Execute]) }];
+    let mut tx = multisig.transaction_create_governed_multisig(&members, 1, 0).await.unwrap();
+    tx.sign(&[&ba, &create_key], tx.message.recent_blockhash);
+    multisig.send_and_confirm_transaction(&tx).await.unwrap();
+    rpc_client.request_airdrop(&multisig.get_vault_pda(), 10_u64.pow(9)).await.unwrap();
+
+    let mut vl = VentureLaunch::new(rpc_client.clone(), program_id, Pubkey::default(), Pubkey::default(), native_mint);
+
+    // The vault is created by the multisig, so the multisig vault is its authority
+    let transaction_index = vl.propose_create_vault(&multisig, &ba, "startup-1").await.unwrap();
+    multisig.vote_approve(&investor, transaction_index).await.unwrap();
+    assert!(vl.execute_proposal(&multisig, &ba, transaction_index).await.is_err());
+    vl.execute_proposal(&multisig, &investor, transaction_index).await.unwrap();
+    assert_eq!(vl.get_vault_data().await.unwrap().initializer_pubkey, multisig.get_vault_pda());
+
+    vl.invoke_deposit(&ba, &ba_ata, 2 * 10_u64.pow(9)).await.unwrap();
+
+    // A single key can not withdraw any more
+    assert!(vl.invoke_withdraw(&ba, &ba_ata, 10_u64.pow(9)).await.is_err());
+
+    vl.governed_withdraw(&multisig, &ba, &[&investor], &investor, &investor_ata, 10_u64.pow(9)).await.unwrap();
+    assert_eq!(vl.get_vault_balance().await.unwrap(), 10_u64.pow(9));
+
+    let loaded = VentureLaunch::load(rpc_client, program_id, &multisig.get_vault_pda(), "startup-1", native_mint).await.unwrap();
+    assert_eq!(loaded.vault_account, vl.vault_account);
+}
+
+#[test]
+fn ui_amount_conversion() {
+    assert_eq!(token_utils::ui_amount_to_base_units("12.5", 6), Ok(12_500_000));
+    assert_eq!(token_utils::ui_amount_to_base_units("0.000001", 6), Ok(1));
+    assert_eq!(token_utils::ui_amount_to_base_units(".5", 9), Ok(500_000_000));
+    assert_eq!(token_utils::ui_amount_to_base_units("7", 0), Ok(7));
+    assert_eq!(token_utils::ui_amount_to_base_units("1.10", 1), Ok(11));
+    assert_eq!(token_utils::ui_amount_to_base_units("0.0000001", 6), Err(VentureLaunchError::InvalidAmount));
+    assert_eq!(token_utils::ui_amount_to_base_units("-1", 6), Err(VentureLaunchError::InvalidAmount));
+    assert_eq!(token_utils::ui_amount_to_base_units("1e3", 6), Err(VentureLaunchError::InvalidAmount));
+    assert_eq!(token_utils::ui_amount_to_base_units("18446744073709551616", 0), Err(VentureLaunchError::InvalidAmount));
+
+    assert_eq!(token_utils::base_units_to_ui_amount(12_500_000, 6), "12.5");
+    assert_eq!(token_utils::base_units_to_ui_amount(1, 6), "0.000001");
+    assert_eq!(token_utils::base_units_to_ui_amount(3 * 10_u64.pow(9), 9), "3");
+    assert_eq!(token_utils::base_units_to_ui_amount(7, 0), "7");
+}
+
+#[tokio::test]
+async fn token_2022_mint_and_idempotent_ata() {
+    let bank = InProcessBank::new();
+
+    // Token-2022 mint without extensions shares the SPL Token layout
+    let mint = Pubkey::new_unique();
+    let mut data = vec![0; spl_token_2022::state::Mint::LEN];
+    spl_token_2022::state::Mint::pack(spl_token_2022::state::Mint {
+        mint_authority: COption::None,
+        supply: 0,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }, &mut data).unwrap();
+    bank.set_account(mint, Account { lamports: 1_461_600, data, owner: TOKEN_2022_PROGRAM_ID, executable: false, rent_epoch: 0 });
+
+    let payer = Keypair::new();
+    bank.request_airdrop(&payer.pubkey(), 10_u64.pow(9)).await.unwrap();
+
+    let mint_info = token_utils::get_mint_info(&bank, &mint).await.unwrap();
+    assert_eq!(mint_info, MintInfo { mint, token_program: TOKEN_2022_PROGRAM_ID, decimals: 6, account_size: 165 });
+    assert_eq!(mint_info.to_base_units("2.5"), Ok(2_500_000));
+
+    let (ata, _) = token_utils::create_associated_token_account_idempotent(&bank, &payer, &payer.pubkey(), &mint_info).await.unwrap();
+    let (again, _) = token_utils::create_associated_token_account_idempotent(&bank, &payer, &payer.pubkey(), &mint_info).await.unwrap();
+    assert_eq!(ata, again);
+    assert_eq!(bank.get_account(&ata).await.unwrap().owner, TOKEN_2022_PROGRAM_ID);
+
+    assert_eq!(
+        token_utils::get_mint_info(&bank, &Pubkey::new_unique()).await,
+        Err(VentureLaunchError::FailedToFetchMintAccount)
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs crypto_tracker.so, built from the venture_launch_contract submodule"]
+async fn deposit_native_sol_and_unwrap() {
+    let native_mint = spl_token::native_mint::id();
+    let program_id = Pubkey::from_str(VENTURE_LAUNCH_PROGRAM_ID).unwrap();
+
+    let bank = InProcessBank::new();
+    bank.add_program("crypto_tracker", program_id);
+    seed_native_mint(&bank);
+
+    let payer = Keypair::new();
+    let investor = Keypair::new();
+    bank.request_airdrop(&payer.pubkey(), 10 * 10_u64.pow(9)).await.unwrap();
+    bank.request_airdrop(&investor.pubkey(), 2 * 10_u64.pow(9)).await.unwrap();
+    let investor_ata = seed_wrapped_sol_ata(&bank, &investor.pubkey(), 3 * 10_u64.pow(9));
+
+    let rpc_client: Arc<dyn RpcBackend> = Arc::new(bank);
+    let mut vl = VentureLaunch::new(rpc_client.clone(), program_id, Pubkey::default(), Pubkey::default(), native_mint);
+    vl.invoke_create_vault(&payer, "startup-1").await.unwrap();
+
+    // No wSOL ATA beforehand, so the one used for the deposit is closed in the same transaction
+    let payer_ata = associated_token::utils::get_associated_token_address(&native_mint, &payer.pubkey());
+    vl.invoke_deposit_native(&payer, 2 * 10_u64.pow(9)).await.unwrap();
+    assert_eq!(vl.get_vault_balance().await.unwrap(), 2 * 10_u64.pow(9));
+    assert!(rpc_client.get_account(&payer_ata).await.is_err());
+
+    // An existing wSOL ATA is left open with its own balance
+    vl.invoke_deposit_native(&investor, 10_u64.pow(9)).await.unwrap();
+    assert_eq!(vl.get_vault_balance().await.unwrap(), 3 * 10_u64.pow(9));
+    let investor_ata_lamports = rpc_client.get_balance(&investor_ata).await.unwrap();
+
+    // Unwrapping returns everything the ATA held
+    let lamports_before = rpc_client.get_balance(&investor.pubkey()).await.unwrap();
+    associated_token::native::unwrap_wrapped_sol_ata(rpc_client.as_ref(), &investor).await.unwrap();
+    assert!(rpc_client.get_account(&investor_ata).await.is_err());
+    assert_eq!(
+        rpc_client.get_balance(&investor.pubkey()).await.unwrap(),
+        lamports_before + investor_ata_lamports - 5000
+    );
+
+    let vl_token = VentureLaunch::new(rpc_client, program_id, Pubkey::default(), Pubkey::default(), Pubkey::new_unique());
+    assert_eq!(vl_token.invoke_deposit_native(&payer, 1).await, Err(VentureLaunchError::MintIsNotNative));
+}
+
+#[tokio::test]
+#[ignore = "needs crypto_tracker.so, built from the venture_launch_contract submodule"]
+async fn ledger_reconciles_with_vault() {
+    let native_mint = spl_token::native_mint::id();
+    let program_id = Pubkey::from_str(VENTURE_LAUNCH_PROGRAM_ID).unwrap();
+
+    let bank = InProcessBank::new();
+    bank.add_program("crypto_tracker", program_id);
+    seed_native_mint(&bank);
+
+    let payer = Keypair::new();
+    bank.request_airdrop(&payer.pubkey(), 10 * 10_u64.pow(9)).await.unwrap();
+    let payer_ata = seed_wrapped_sol_ata(&bank, &payer.pubkey(), 5 * 10_u64.pow(9));
+
+    let rpc_client: Arc<dyn RpcBackend> = Arc::new(bank);
+    let ledger = Arc::new(InMemoryLedger::default());
+    let mut vl = VentureLaunch::new(
+        rpc_client.clone(),
+        program_id,
+        Pubkey::default(),
+        Pubkey::default(),
+        native_mint
+    ).with_ledger(ledger.clone());
+
+    vl.invoke_create_vault(&payer, "startup-1").await.unwrap();
+    let deposit_signature = vl.invoke_deposit(&payer, &payer_ata, 2 * 10_u64.pow(9)).await.unwrap();
+    vl.invoke_withdraw(&payer, &payer_ata, 10_u64.pow(9)).await.unwrap();
+
+    // Creating the vault is not a ledger entry
+    let entries = ledger.entries(&vl.vault_account).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].kind, LedgerEntryKind::Deposit);
+    assert_eq!(entries[0].investor, payer.pubkey());
+    assert_eq!(entries[0].token_account, payer_ata);
+    assert_eq!(entries[0].signature, deposit_signature);
+    assert_eq!(entries[0].slot, rpc_client.get_signature_slot(&deposit_signature).await.unwrap().unwrap());
+    assert_eq!(entries[0].mint, native_mint);
+    assert_eq!(entries[1].kind, LedgerEntryKind::Withdraw);
+    assert!(entries[1].slot >= entries[0].slot);
+
+    let report = vl.reconcile().await.unwrap();
+    assert!(report.is_consistent());
+    assert_eq!(report.ledger_balance, 10_i128.pow(9));
+    assert_eq!(report.tracked_amount, 10_u64.pow(9));
+    assert_eq!(report.vault_token_balance, 10_u64.pow(9));
+
+    // A deposit the chain never saw
+    ledger.record(LedgerEntry {
+        amount: 5,
+        signature: Signature::new_unique(),
+        ..entries[0].clone()
+    }).unwrap();
+    let report = vl.reconcile().await.unwrap();
+    assert_eq!(report.discrepancies, vec![
+        Discrepancy::LedgerDiffersFromTracker { ledger_balance: 10_i128.pow(9) + 5, tracked_amount: 10_u64.pow(9) },
+        Discrepancy::LedgerDiffersFromVault { ledger_balance: 10_i128.pow(9) + 5, vault_token_balance: 10_u64.pow(9) },
+    ]);
+
+    let without_ledger = VentureLaunch::load(rpc_client.clone(), program_id, &payer.pubkey(), "startup-1", native_mint).await.unwrap();
+    assert_eq!(without_ledger.reconcile().await.err(), Some(VentureLaunchError::LedgerNotConfigured));
+
+    // A ledger that can not be written reports the deposit that landed anyway
+    let unwritable = without_ledger.with_ledger(Arc::new(JsonLinesLedger::new("/nonexistent/ledger.jsonl")));
+    let signature = match unwritable.invoke_deposit(&payer, &payer_ata, 10_u64.pow(8)).await {
+        Err(VentureLaunchError::LedgerEntriesNotRecorded(signature)) => signature,
+        result => panic!("unexpected result {:?}", result),
+    };
+    assert!(rpc_client.get_signature_slot(&signature).await.unwrap().is_some());
+    assert_eq!(unwritable.get_vault_balance().await, Ok(11 * 10_u64.pow(8)));
+}
+
+#[test]
+fn json_lines_ledger_round_trip() {
+    let path = std::env::temp_dir().join(format!("venture_launch_ledger_{}.jsonl", Pubkey::new_unique()));
+    let vault_account = Pubkey::new_unique();
+    let entry = LedgerEntry {
+        kind: LedgerEntryKind::Deposit,
+        investor: Pubkey::new_unique(),
+        token_account: Pubkey::new_unique(),
+        signature: Signature::new_unique(),
+        slot: 7,
+        amount: 42,
+        mint: Pubkey::new_unique(),
+        vault_account,
+    };
+
+    let ledger = JsonLinesLedger::new(&path);
+    assert!(ledger.entries(&vault_account).unwrap().is_empty());
+    ledger.record(entry.clone()).unwrap();
+    ledger.record(LedgerEntry { vault_account: Pubkey::new_unique(), ..entry.clone() }).unwrap();
+
+    // A fresh instance reads what the previous one wrote
+    assert_eq!(JsonLinesLedger::new(&path).entries(&vault_account).unwrap(), vec![entry]);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+async fn token_balance(rpc_client: &dyn RpcBackend, token_account: &Pubkey) -> u64 {
+    let data = rpc_client.get_account_data(token_account).await.unwrap();
+    spl_token::state::Account::unpack(&data).unwrap().amount
+}
+
+#[test]
+fn pro_rata_refund_split() {
+    let vault_account = Pubkey::new_unique();
+    let entry = |kind, token_account, amount| LedgerEntry {
+        kind,
+        investor: Pubkey::new_unique(),
+        token_account,
+        signature: Signature::new_unique(),
+        slot: 1,
+        amount,
+        mint: spl_token::native_mint::id(),
+        vault_account,
+    };
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let entries = vec![
+        entry(LedgerEntryKind::Deposit, first, 300),
+        entry(LedgerEntryKind::Deposit, second, 100),
+        entry(LedgerEntryKind::Deposit, first, 100),
+    ];
+
+    let refunds = pro_rata_refunds(&entries, 500);
+    assert_eq!(refunds.iter().map(|refund| (refund.token_account, refund.amount)).collect::<Vec<_>>(), vec![(first, 400), (second, 100)]);
+    assert_eq!(refunds[0].investor, entries[0].investor);
+
+    // A shortfall is shared, the rounding dust stays in the vault
+    let refunds = pro_rata_refunds(&entries, 99);
+    assert_eq!(refunds.iter().map(|refund| refund.amount).collect::<Vec<_>>(), vec![79, 19]);
+
+    // Refunded accounts drop out
+    let mut entries = entries;
+    entries.push(entry(LedgerEntryKind::Withdraw, first, 400));
+    assert_eq!(pro_rata_refunds(&entries, 100), vec![Refund { investor: entries[1].investor, token_account: second, amount: 100 }]);
+    assert!(pro_rata_refunds(&[], 100).is_empty());
+}
+
+async fn campaign_setup(target: u64) -> (Arc<InProcessBank>, BaseMultisig, Keypair, Campaign) {
+    let program_id = Pubkey::from_str(VENTURE_LAUNCH_PROGRAM_ID).unwrap();
+
+    let bank = Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+    bank.add_program("crypto_tracker", program_id);
+    seed_native_mint(&bank);
+
+    let platform = Keypair::new();
+    let create_key = Keypair::new();
+    bank.request_airdrop(&platform.pubkey(), 10 * 10_u64.pow(9)).await.unwrap();
+
+    let rpc_client: Arc<dyn RpcBackend> = bank.clone();
+    let multisig = BaseMultisig::new(BaseMultisigCreateArgs {
+        rpc_client: rpc_client.clone(),
+        program_id: squads_multisig_program::ID,
+        multisig_create_keypair: create_key.insecure_clone(),
+        creator: platform.pubkey(),
+        cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE,
+    }).await.unwrap();
+    // The platform is the creator and as such already the only member
+    let mut tx = multisig.transaction_create_multisig(&[], 1, 0).await.unwrap();
+    tx.sign(&[&platform, &create_key], tx.message.recent_blockhash);
+    multisig.send_and_confirm_transaction(&tx).await.unwrap();
+
+    let clock: solana_sdk::clock::Clock = solana_sdk::account::from_account(
+        &bank.get_account(&solana_sdk::sysvar::clock::id()).await.unwrap()
+    ).unwrap();
+    let deadline = clock.unix_timestamp + 50;
+    let campaign = Campaign::open(
+        rpc_client,
+        program_id,
+        &platform,
+        spl_token::native_mint::id(),
+        Arc::new(InMemoryLedger::default()),
+        CampaignConfig {
+            startup_id: "startup-1".to_string(),
+            target,
+            min_ticket: 10_u64.pow(8),
+            max_ticket: 2 * 10_u64.pow(9),
+            deadline,
+        }
+    ).await.unwrap();
+
+    (bank, multisig, platform, campaign)
+}
+
+#[tokio::test]
+#[ignore = "needs crypto_tracker.so, built from the venture_launch_contract submodule"]
+async fn failed_campaign_refunds_investors() {
+    let (bank, multisig, platform, campaign) = campaign_setup(5 * 10_u64.pow(9)).await;
+
+    let first = Keypair::new();
+    let second = Keypair::new();
+    bank.request_airdrop(&first.pubkey(), 10_u64.pow(9)).await.unwrap();
+    bank.request_airdrop(&second.pubkey(), 10_u64.pow(9)).await.unwrap();
+    let first_ata = seed_wrapped_sol_ata(&bank, &first.pubkey(), 3 * 10_u64.pow(9));
+    let second_ata = seed_wrapped_sol_ata(&bank, &second.pubkey(), 3 * 10_u64.pow(9));
+
+    assert_eq!(campaign.status().await, Ok(CampaignStatus::Open));
+    assert_eq!(campaign.contribute(&first, &first_ata, 10_u64.pow(7)).await, Err(VentureLaunchError::TicketBelowMinimum));
+    campaign.contribute(&first, &first_ata, 15 * 10_u64.pow(8)).await.unwrap();
+    assert_eq!(campaign.contribute(&first, &first_ata, 10_u64.pow(9)).await, Err(VentureLaunchError::TicketAboveMaximum));
+    campaign.contribute(&second, &second_ata, 5 * 10_u64.pow(8)).await.unwrap();
+    assert_eq!(campaign.raised(), Ok(2 * 10_u64.pow(9)));
+
+    assert_eq!(campaign.settle(&platform, &multisig).await, Err(VentureLaunchError::CampaignNotEnded));
+
+    bank.warp_to_slot(bank.get_slot() + 100);
+    assert_eq!(campaign.status().await, Ok(CampaignStatus::Failed));
+    assert_eq!(campaign.contribute(&second, &second_ata, 10_u64.pow(8)).await, Err(VentureLaunchError::CampaignNotOpen));
+
+    match campaign.settle(&platform, &multisig).await.unwrap() {
+        CampaignSettlement::Refunded(refunds) => assert_eq!(refunds.len(), 2),
+        settlement => panic!("unexpected settlement {:?}", settlement),
+    }
+    assert_eq!(token_balance(bank.as_ref(), &first_ata).await, 3 * 10_u64.pow(9));
+    assert_eq!(token_balance(bank.as_ref(), &second_ata).await, 3 * 10_u64.pow(9));
+    assert_eq!(campaign.vl.get_vault_balance().await, Ok(0));
+
+    // Settling again has nothing left to refund, and the campaign stays failed
+    assert_eq!(campaign.settle(&platform, &multisig).await, Ok(CampaignSettlement::Refunded(Vec::new())));
+    assert_eq!(campaign.status().await, Ok(CampaignStatus::Failed));
+    assert!(campaign.vl.reconcile().await.unwrap().is_consistent());
+}
+
+/// Refund store that fails to save the first refund status, like a process stopped right after sending it.
+struct StatusLostOnce {
+    inner: JsonRefundStore,
+    lost: std::sync::atomic::AtomicBool,
+}
+
+impl RefundStore for StatusLostOnce {
+    fn plan(&self, vault_account: &Pubkey) -> Result<Option<RefundPlan>, VentureLaunchError> {
+        self.inner.plan(vault_account)
+    }
+
+    fn create_plan(&self, plan: RefundPlan) -> Result<RefundPlan, VentureLaunchError> {
+        self.inner.create_plan(plan)
+    }
+
+    fn record_attempt(&self, vault_account: &Pubkey, token_account: &Pubkey, attempt: RefundAttempt) -> Result<(), VentureLaunchError> {
+        self.inner.record_attempt(vault_account, token_account, attempt)
+    }
+
+    fn mark_refunded(&self, vault_account: &Pubkey, token_account: &Pubkey, signature: Signature) -> Result<(), VentureLaunchError> {
+        if !self.lost.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            return Err(VentureLaunchError::FailedToRecordRefund);
+        }
+        self.inner.mark_refunded(vault_account, token_account, signature)
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs crypto_tracker.so, built from the venture_launch_contract submodule"]
+async fn interrupted_refunds_resume_with_original_shares() {
+    let (bank, multisig, platform, campaign) = campaign_setup(5 * 10_u64.pow(9)).await;
+    let refunds_dir = std::env::temp_dir().join(format!("refunds-{}", Keypair::new().pubkey()));
+    let campaign = campaign.with_refund_store(Arc::new(StatusLostOnce {
+        inner: JsonRefundStore::new(&refunds_dir),
+        lost: std::sync::atomic::AtomicBool::new(false),
+    }));
+
+    let first = Keypair::new();
+    let second = Keypair::new();
+    bank.request_airdrop(&first.pubkey(), 10_u64.pow(9)).await.unwrap();
+    bank.request_airdrop(&second.pubkey(), 10_u64.pow(9)).await.unwrap();
+    let first_ata = seed_wrapped_sol_ata(&bank, &first.pubkey(), 3 * 10_u64.pow(9));
+    let second_ata = seed_wrapped_sol_ata(&bank, &second.pubkey(), 3 * 10_u64.pow(9));
+    campaign.contribute(&first, &first_ata, 15 * 10_u64.pow(8)).await.unwrap();
+    campaign.contribute(&second, &second_ata, 5 * 10_u64.pow(8)).await.unwrap();
+    bank.warp_to_slot(bank.get_slot() + 100);
+
+    // The first refund lands but its status is lost
+    assert_eq!(campaign.settle(&platform, &multisig).await, Err(VentureLaunchError::FailedToRecordRefund));
+    assert_eq!(token_balance(bank.as_ref(), &first_ata).await, 3 * 10_u64.pow(9));
+    let plan = JsonRefundStore::new(&refunds_dir).plan(&campaign.vl.vault_account).unwrap().unwrap();
+    assert_eq!(plan.pending().len(), 2);
+
+    // The ledger shows it was sent, so only the second one is still owed
+    let second_refund = Refund { investor: second.pubkey(), token_account: second_ata, amount: 5 * 10_u64.pow(8) };
+    assert_eq!(campaign.refunds().await, Ok(vec![second_refund]));
+
+    // Funds reaching the vault after the split do not change the shares
+    let stray = Keypair::new();
+    bank.request_airdrop(&stray.pubkey(), 10_u64.pow(9)).await.unwrap();
+    let stray_ata = seed_wrapped_sol_ata(&bank, &stray.pubkey(), 10_u64.pow(9));
+    campaign.vl.invoke_deposit(&stray, &stray_ata, 10_u64.pow(9)).await.unwrap();
+
+    match campaign.settle(&platform, &multisig).await.unwrap() {
+        CampaignSettlement::Refunded(refunds) => {
+            assert_eq!(refunds.iter().map(|(refund, _)| *refund).collect::<Vec<_>>(), vec![second_refund]);
+        }
+        settlement => panic!("unexpected settlement {:?}", settlement),
+    }
+    assert_eq!(token_balance(bank.as_ref(), &second_ata).await, 3 * 10_u64.pow(9));
+    assert_eq!(campaign.vl.get_vault_balance().await, Ok(10_u64.pow(9)));
+    assert_eq!(campaign.settle(&platform, &multisig).await, Ok(CampaignSettlement::Refunded(Vec::new())));
+
+    // The statuses survive a restart, and a new split never replaces the stored one
+    let store = JsonRefundStore::new(&refunds_dir);
+    let plan = store.plan(&campaign.vl.vault_account).unwrap().unwrap();
+    assert!(plan.pending().is_empty());
+    assert_eq!(plan.refunds[0].refund.amount, 15 * 10_u64.pow(8));
+    let replacement = RefundPlan { vault_account: plan.vault_account, ledger_len: 0, refunds: Vec::new() };
+    assert_eq!(store.create_plan(replacement), Ok(plan));
+    std::fs::remove_dir_all(&refunds_dir).unwrap();
+}
+
+#[test]
+fn refund_attempts_survive_a_restart() {
+    let refunds_dir = std::env::temp_dir().join(format!("refunds-{}", Keypair::new().pubkey()));
+    let refund = Refund { investor: Pubkey::new_unique(), token_account: Pubkey::new_unique(), amount: 10 };
+    let plan = RefundPlan {
+        vault_account: Pubkey::new_unique(),
+        ledger_len: 1,
+        refunds: vec![RefundRecord { refund, signature: None, attempt: None }],
+    };
+    let attempt = RefundAttempt { signature: Signature::new_unique(), recent_blockhash: solana_sdk::hash::Hash::new_unique() };
+
+    let store = JsonRefundStore::new(&refunds_dir);
+    store.create_plan(plan.clone()).unwrap();
+    store.record_attempt(&plan.vault_account, &refund.token_account, attempt).unwrap();
+
+    // Sent without a known outcome, so it is still owed
+    let stored = JsonRefundStore::new(&refunds_dir).plan(&plan.vault_account).unwrap().unwrap();
+    assert_eq!(stored.refunds[0].attempt, Some(attempt));
+    assert_eq!(stored.pending(), vec![refund]);
+    assert_eq!(
+        store.record_attempt(&plan.vault_account, &Pubkey::new_unique(), attempt),
+        Err(VentureLaunchError::FailedToRecordRefund)
+    );
+    std::fs::remove_dir_all(&refunds_dir).unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs crypto_tracker.so, built from the venture_launch_contract submodule"]
+async fn unconfirmed_refund_is_not_sent_again_while_it_may_land() {
+    let (bank, multisig, platform, campaign) = campaign_setup(5 * 10_u64.pow(9)).await;
+    let refund_store = Arc::new(InMemoryRefundStore::default());
+    let campaign = campaign.with_refund_store(refund_store.clone());
+
+    let investor = Keypair::new();
+    bank.request_airdrop(&investor.pubkey(), 10_u64.pow(9)).await.unwrap();
+    let investor_ata = seed_wrapped_sol_ata(&bank, &investor.pubkey(), 3 * 10_u64.pow(9));
+    campaign.contribute(&investor, &investor_ata, 10_u64.pow(9)).await.unwrap();
+    bank.warp_to_slot(bank.get_slot() + 100);
+
+    // An earlier settlement sent the refund and stopped before learning its outcome
+    let refund = campaign.refunds().await.unwrap()[0];
+    refund_store.create_plan(RefundPlan {
+        vault_account: campaign.vl.vault_account,
+        ledger_len: 1,
+        refunds: vec![RefundRecord { refund, signature: None, attempt: None }],
+    }).unwrap();
+    let lost = RefundAttempt { signature: Signature::new_unique(), recent_blockhash: bank.get_latest_blockhash().await.unwrap() };
+    refund_store.record_attempt(&campaign.vl.vault_account, &investor_ata, lost).unwrap();
+
+    assert_eq!(campaign.settle(&platform, &multisig).await, Err(VentureLaunchError::TransactionNotConfirmed(lost.signature)));
+    assert_eq!(token_balance(bank.as_ref(), &investor_ata).await, 2 * 10_u64.pow(9));
+
+    // Once its blockhash expired it can no longer land, so the refund is sent again
+    let expired = RefundAttempt { signature: lost.signature, recent_blockhash: solana_sdk::hash::Hash::new_unique() };
+    refund_store.record_attempt(&campaign.vl.vault_account, &investor_ata, expired).unwrap();
+    match campaign.settle(&platform, &multisig).await.unwrap() {
+        CampaignSettlement::Refunded(refunds) => assert_eq!(refunds.iter().map(|(refund, _)| *refund).collect::<Vec<_>>(), vec![refund]),
+        settlement => panic!("unexpected settlement {:?}", settlement),
+    }
+    assert_eq!(token_balance(bank.as_ref(), &investor_ata).await, 3 * 10_u64.pow(9));
+}
+
+#[tokio::test]
+#[ignore = "needs crypto_tracker.so, built from the venture_launch_contract submodule"]
+async fn concurrent_contributions_respect_the_maximum_ticket() {
+    let (bank, _multisig, _platform, campaign) = campaign_setup(5 * 10_u64.pow(9)).await;
+
+    let investor = Keypair::new();
+    bank.request_airdrop(&investor.pubkey(), 10_u64.pow(9)).await.unwrap();
+    let investor_ata = seed_wrapped_sol_ata(&bank, &investor.pubkey(), 3 * 10_u64.pow(9));
+
+    // Each fits the maximum ticket on its own, both together do not
+    let (first, second) = tokio::join!(
+        campaign.contribute(&investor, &investor_ata, 12 * 10_u64.pow(8)),
+        campaign.contribute(&investor, &investor_ata, 12 * 10_u64.pow(8))
+    );
+
+    let mut results = vec![first.map(|_| ()), second.map(|_| ())];
+    results.sort_by_key(|result| result.is_err());
+    assert_eq!(results, vec![Ok(()), Err(VentureLaunchError::TicketAboveMaximum)]);
+    assert_eq!(campaign.contribution_of(&investor.pubkey()), Ok(12 * 10_u64.pow(8)));
+}
+
+#[tokio::test]
+#[ignore = "needs crypto_tracker.so, built from the venture_launch_contract submodule"]
+async fn succeeded_campaign_moves_funds_to_multisig_vault() {
+    let (bank, multisig, platform, campaign) = campaign_setup(10_u64.pow(9)).await;
+
+    let investor = Keypair::new();
+    bank.request_airdrop(&investor.pubkey(), 2 * 10_u64.pow(9)).await.unwrap();
+    let investor_ata = seed_wrapped_sol_ata(&bank, &investor.pubkey(), 3 * 10_u64.pow(9));
+    campaign.contribute(&investor, &investor_ata, 2 * 10_u64.pow(9)).await.unwrap();
+
+    bank.warp_to_slot(bank.get_slot() + 100);
+    assert_eq!(campaign.status().await, Ok(CampaignStatus::Succeeded));
+
+    let multisig_ata = associated_token::utils::get_associated_token_address(&spl_token::native_mint::id(), &multisig.get_vault_pda());
+    match campaign.settle(&platform, &multisig).await.unwrap() {
+        CampaignSettlement::Transferred { token_account, amount, .. } => {
+            assert_eq!(token_account, multisig_ata);
+            assert_eq!(amount, 2 * 10_u64.pow(9));
+        }
+        settlement => panic!("unexpected settlement {:?}", settlement),
+    }
+    assert_eq!(token_balance(bank.as_ref(), &multisig_ata).await, 2 * 10_u64.pow(9));
+    assert_eq!(campaign.vl.get_vault_balance().await, Ok(0));
+    assert_eq!(campaign.status().await, Ok(CampaignStatus::Succeeded));
+}
+
+#[tokio::test]
+async fn milestone_tranches_follow_proposals() {
+    let native_mint = spl_token::native_mint::id();
+
+    let bank = InProcessBank::with_squads(squads_multisig_program::ID);
+    seed_native_mint(&bank);
+
+    let ba = Keypair::new();
+    let investor = Keypair::new();
+    bank.request_airdrop(&ba.pubkey(), 10 * 10_u64.pow(9)).await.unwrap();
+    bank.request_airdrop(&investor.pubkey(), 10_u64.pow(9)).await.unwrap();
+
+    let bank = Arc::new(bank);
+    let rpc_client: Arc<dyn RpcBackend> = bank.clone();
+    let create_key = Keypair::new();
+    let multisig = BaseMultisig::new(BaseMultisigCreateArgs {
+        rpc_client: rpc_client.clone(),
+        program_id: squads_multisig_program::ID,
+        multisig_create_keypair: create_key.insecure_clone(),
+        creator: ba.pubkey(),
+        cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE,
+    }).await.unwrap();
+    let members = [Member { key: investor.pubkey(), permissions: Permissions::from_vec(&[Permission::Vote]) }];
+    let mut tx = multisig.transaction_create_multisig(&members, 1, 0).await.unwrap();
+    tx.sign(&[&ba, &create_key], tx.message.recent_blockhash);
+    multisig.send_and_confirm_transaction(&tx).await.unwrap();
+
+    // The treasury holds the raised wSOL and some SOL to pay for the startup's token account
+    rpc_client.request_airdrop(&multisig.get_vault_pda(), 10_u64.pow(9)).await.unwrap();
+    seed_wrapped_sol_ata(&bank, &multisig.get_vault_pda(), 3 * 10_u64.pow(9));
+
+    let startup = Pubkey::new_unique();
+    let tranche = |amount, description: &str| Tranche { amount, mint: native_mint, recipient: startup, description: description.to_string() };
+    let mut plan = MilestonePlan::new(multisig.get_multisig_pda(), vec![
+        tranche(10_u64.pow(9), "MVP"),
+        tranche(2 * 10_u64.pow(9), "Launch"),
+    ]).unwrap();
+    assert_eq!(plan.statuses(&multisig).await.unwrap(), vec![MilestoneStatus::Pending, MilestoneStatus::Pending]);
+
+    plan.propose(&multisig, &ba, 0).await.unwrap();
+    assert_eq!(plan.status(&multisig, 0).await, Ok(MilestoneStatus::Proposed));
+    assert_eq!(plan.propose(&multisig, &ba, 0).await, Err(VentureLaunchError::MilestoneAlreadyProposed));
+    assert_eq!(plan.release(&multisig, &ba, 0).await, Err(VentureLaunchError::MilestoneNotApproved));
+
+    multisig.vote_approve(&investor, plan.milestones[0].transaction_index.unwrap()).await.unwrap();
+    assert_eq!(plan.status(&multisig, 0).await, Ok(MilestoneStatus::Approved));
+    plan.release(&multisig, &ba, 0).await.unwrap();
+    assert_eq!(plan.status(&multisig, 0).await, Ok(MilestoneStatus::Released));
+    let startup_ata = associated_token::utils::get_associated_token_address(&native_mint, &startup);
+    assert_eq!(token_balance(rpc_client.as_ref(), &startup_ata).await, 10_u64.pow(9));
+
+    // Both voters reject the second milestone, after which it can be proposed again
+    let rejected_index = plan.propose(&multisig, &ba, 1).await.unwrap();
+    multisig.vote_reject(&investor, rejected_index).await.unwrap();
+    multisig.vote_reject(&ba, rejected_index).await.unwrap();
+    assert_eq!(plan.status(&multisig, 1).await, Ok(MilestoneStatus::Rejected));
+    assert!(plan.propose(&multisig, &ba, 1).await.unwrap() > rejected_index);
+    assert_eq!(plan.status(&multisig, 1).await, Ok(MilestoneStatus::Proposed));
+
+    assert_eq!(plan.status(&multisig, 2).await, Err(VentureLaunchError::UnknownMilestone));
+}
+
+#[tokio::test]
+#[ignore = "needs crypto_tracker.so, built from the venture_launch_contract submodule"]
+async fn vault_state_is_validated() {
+    let native_mint = spl_token::native_mint::id();
+    let program_id = Pubkey::from_str(VENTURE_LAUNCH_PROGRAM_ID).unwrap();
+
+    let bank = Arc::new(InProcessBank::new());
+    bank.add_program("crypto_tracker", program_id);
+    seed_native_mint(&bank);
+
+    let payer = Keypair::new();
+    bank.request_airdrop(&payer.pubkey(), 10 * 10_u64.pow(9)).await.unwrap();
+    let payer_ata = seed_wrapped_sol_ata(&bank, &payer.pubkey(), 5 * 10_u64.pow(9));
+
+    let rpc_client: Arc<dyn RpcBackend> = bank.clone();
+    let mut vl = VentureLaunch::new(rpc_client.clone(), program_id, Pubkey::default(), Pubkey::default(), native_mint);
+    vl.invoke_create_vault(&payer, "startup-1").await.unwrap();
+    vl.invoke_deposit(&payer, &payer_ata, 2 * 10_u64.pow(9)).await.unwrap();
+
+    let state = vl.get_vault_state().await.unwrap();
+    assert!(state.is_healthy());
+    assert_eq!(state.initializer, payer.pubkey());
+    assert_eq!(state.vault_account, vl.vault_account);
+    assert_eq!(state.token_mint, native_mint);
+    assert_eq!(state.tracked_amount, 2 * 10_u64.pow(9));
+    assert_eq!(state.token_balance, 2 * 10_u64.pow(9));
+
+    // A client expecting another mint gets the vault reported, not rejected
+    let other_mint = Pubkey::new_unique();
+    let mut wrong_mint = VentureLaunch::new(rpc_client.clone(), program_id, vl.vault_account, vl.data_account, other_mint);
+    assert_eq!(
+        wrong_mint.get_vault_state().await.unwrap().issues,
+        vec![VaultStateIssue::MintMismatch { expected: other_mint, actual: native_mint }]
+    );
+
+    // Another vault's token account is not this vault
+    wrong_mint.vault_account = payer_ata;
+    assert_eq!(wrong_mint.get_vault_state().await.err(), Some(VentureLaunchError::VaultAccountMismatch));
+
+    let data_account = rpc_client.get_account(&vl.data_account).await.unwrap();
+    bank.set_account(vl.data_account, Account { owner: Pubkey::new_unique(), ..data_account.clone() });
+    assert_eq!(vl.get_vault_state().await.err(), Some(VentureLaunchError::DataAccountOwnerMismatch));
+
+    bank.set_account(vl.data_account, Account { data: vec![0; data_account.data.len() + 1], ..data_account.clone() });
+    assert_eq!(vl.get_vault_state().await.err(), Some(VentureLaunchError::InvalidDataAccountSize));
+
+    bank.set_account(vl.data_account, Account { data: vec![0; data_account.data.len()], ..data_account });
+    assert_eq!(vl.get_vault_balance().await.err(), Some(VentureLaunchError::VaultNotInitialized));
+}
@@ -0,0 +1,105 @@
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+use spl_token_2022::{extension::StateWithExtensions, state::Account};
+use super::state::CryptoTracker;
+
+use super::{
+    error::VentureLaunchError,
+    venture_launch::{VentureLaunch, CRYPTO_TRACKER_DATA_SIZE},
+};
+
+/// Something about a valid vault that does not add up, reported rather than failed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultStateIssue {
+    /// The vault token account holds another mint than the client expects
+    MintMismatch { expected: Pubkey, actual: Pubkey },
+    /// The vault token account is not controlled by the program PDA
+    TokenOwnerMismatch { expected: Pubkey, actual: Pubkey },
+    /// `CryptoTracker.amount` differs from the balance of the vault token account
+    BalanceMismatch { tracked_amount: u64, token_balance: u64 },
+}
+
+/// Validated view of a vault: its `CryptoTracker` data account cross-checked with its token account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultState {
+    pub data_account: Pubkey,
+    pub vault_account: Pubkey,
+    pub initializer: Pubkey,
+    pub tracked_amount: u64,
+    pub token_mint: Pubkey,
+    pub token_owner: Pubkey,
+    pub token_balance: u64,
+    pub issues: Vec<VaultStateIssue>,
+}
+
+impl VaultState {
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl VentureLaunch {
+    /// Reads and validates the vault. A data or token account that is missing, owned by the wrong program,
+    /// of the wrong size, uninitialized or tracking another vault is an error; mismatches between two
+    /// valid accounts are listed in `VaultState::issues`.
+    pub async fn get_vault_state(&self) -> Result<VaultState, VentureLaunchError> {
+        let data_account = match self.rpc_client.get_account(&self.data_account).await {
+            Ok(account) => account,
+            Err(_) => return Err(VentureLaunchError::FailedToFetchDataAccount)
+        };
+        if data_account.owner != self.program_id {
+            return Err(VentureLaunchError::DataAccountOwnerMismatch);
+        }
+        if data_account.data.len() as u64 != CRYPTO_TRACKER_DATA_SIZE {
+            return Err(VentureLaunchError::InvalidDataAccountSize);
+        }
+
+        let data = match CryptoTracker::unpack_unchecked(&data_account.data) {
+            Ok(data) => data,
+            Err(_) => return Err(VentureLaunchError::FailedToDeserializeDataAccount)
+        };
+        if !data.is_initialized {
+            return Err(VentureLaunchError::VaultNotInitialized);
+        }
+        if data.vault_account_pubkey != self.vault_account {
+            return Err(VentureLaunchError::VaultAccountMismatch);
+        }
+
+        let token_account = match self.rpc_client.get_account(&self.vault_account).await {
+            Ok(account) => account,
+            Err(_) => return Err(VentureLaunchError::FailedToFetchTokenAccount)
+        };
+        if token_account.owner != self.token_program {
+            return Err(VentureLaunchError::UnsupportedTokenProgram);
+        }
+        if token_account.data.len() as u64 != self.vault_account_size {
+            return Err(VentureLaunchError::InvalidTokenAccountSize);
+        }
+        let token_state = match StateWithExtensions::<Account>::unpack(&token_account.data) {
+            Ok(state) => state.base,
+            Err(_) => return Err(VentureLaunchError::FailedToDeserializeTokenAccount)
+        };
+
+        let mut issues = Vec::new();
+        if token_state.mint != self.mint {
+            issues.push(VaultStateIssue::MintMismatch { expected: self.mint, actual: token_state.mint });
+        }
+        let (program_pda, _) = Pubkey::find_program_address(&[b"cryptotracker"], &self.program_id);
+        if token_state.owner != program_pda {
+            issues.push(VaultStateIssue::TokenOwnerMismatch { expected: program_pda, actual: token_state.owner });
+        }
+        if token_state.amount != data.amount {
+            issues.push(VaultStateIssue::BalanceMismatch { tracked_amount: data.amount, token_balance: token_state.amount });
+        }
+
+        Ok(VaultState {
+            data_account: self.data_account,
+            vault_account: self.vault_account,
+            initializer: data.initializer_pubkey,
+            tracked_amount: data.amount,
+            token_mint: token_state.mint,
+            token_owner: token_state.owner,
+            token_balance: token_state.amount,
+            issues,
+        })
+    }
+}
@@ -0,0 +1,362 @@
+use solana_sdk::{
+    hash::{hashv, Hash}, instruction::Instruction, message::Message, pubkey::Pubkey, signature::{Keypair, Signer, Signature},
+    transaction::Transaction, program_pack::Pack
+};
+use std::sync::Arc;
+use super::state::CryptoTracker;
+use super::{
+    associated_token::{self, native, token::{get_mint_info, MintInfo}, utils::get_associated_token_address},
+    error::VentureLaunchError,
+    instruction,
+    ledger::LedgerStore,
+};
+use crate::{cluster_utils::cluster_profile::ClusterProfile, rpc_utils::rpc_backend::{send_and_confirm, RpcBackend}};
+
+pub const ACCOUNT_SIZE: u64 = 165;
+pub const CRYPTO_TRACKER_DATA_SIZE: u64 = 73;
+const VAULT_SEED_PREFIX: &[u8] = b"venture_launch_vault";
+const DATA_SEED_PREFIX: &[u8] = b"venture_launch_data";
+
+/// Seeds for `create_account_with_seed` are limited to 32 bytes, so the project parts are hashed
+/// and the first 16 bytes are used hex encoded.
+fn account_seed(prefix: &[u8], startup_id: &str, mint: &Pubkey) -> String {
+    hashv(&[prefix, startup_id.as_bytes(), mint.as_ref()])
+        .to_bytes()
+        .iter()
+        .take(16)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Addresses of the vault token account and the data account of one project.
+/// Both are derived from `authority` (the payer of `invoke_create_vault`), the startup id and the mint,
+/// the vault token account is owned by the token program of the mint.
+pub struct VaultAddresses {
+    pub vault_account: Pubkey,
+    pub vault_seed: String,
+    pub data_account: Pubkey,
+    pub data_seed: String,
+}
+
+impl VaultAddresses {
+    pub fn derive(
+        authority: &Pubkey,
+        program_id: &Pubkey,
+        startup_id: &str,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Result<VaultAddresses, VentureLaunchError> {
+        let vault_seed = account_seed(VAULT_SEED_PREFIX, startup_id, mint);
+        let data_seed = account_seed(DATA_SEED_PREFIX, startup_id, mint);
+
+        let vault_account = match Pubkey::create_with_seed(authority, &vault_seed, token_program) {
+            Ok(address) => address,
+            Err(_) => return Err(VentureLaunchError::FailedToDeriveAccountAddress)
+        };
+        let data_account = match Pubkey::create_with_seed(authority, &data_seed, program_id) {
+            Ok(address) => address,
+            Err(_) => return Err(VentureLaunchError::FailedToDeriveAccountAddress)
+        };
+
+        Ok(VaultAddresses {
+            vault_account,
+            vault_seed,
+            data_account,
+            data_seed,
+        })
+    }
+}
+
+pub struct VentureLaunch {
+    pub rpc_client: Arc<dyn RpcBackend>,
+    pub program_id: Pubkey,
+    pub vault_account: Pubkey,
+    pub data_account: Pubkey,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub vault_account_size: u64,
+    /// Where successful deposits and withdrawals are recorded, see `with_ledger`
+    pub ledger: Option<Arc<dyn LedgerStore>>,
+}
+
+impl VentureLaunch {
+    pub fn new(
+        rpc_client: Arc<dyn RpcBackend>,
+        program_id: Pubkey,
+        vault_account: Pubkey,
+        data_account: Pubkey,
+        mint: Pubkey,
+    ) -> VentureLaunch {
+        VentureLaunch {
+            rpc_client,
+            program_id,
+            vault_account,
+            data_account,
+            mint,
+            token_program: spl_token::id(),
+            vault_account_size: ACCOUNT_SIZE,
+            ledger: None,
+        }
+    }
+
+    /// Uses the token program and token account size of `mint_info`, e.g. for a Token-2022 mint.
+    pub fn with_mint_info(mut self, mint_info: &MintInfo) -> VentureLaunch {
+        self.mint = mint_info.mint;
+        self.token_program = mint_info.token_program;
+        self.vault_account_size = mint_info.account_size;
+        self
+    }
+
+    /// Records every deposit and withdrawal sent through this client in `ledger`.
+    pub fn with_ledger(mut self, ledger: Arc<dyn LedgerStore>) -> VentureLaunch {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    pub fn from_profile(
+        profile: &ClusterProfile,
+        vault_account: Pubkey,
+        data_account: Pubkey,
+        mint: Pubkey,
+    ) -> VentureLaunch {
+        VentureLaunch::new(
+            Arc::new(profile.rpc_client()),
+            profile.venture_launch_program_id,
+            vault_account,
+            data_account,
+            mint,
+        )
+    }
+
+    /// Reconstructs the client of an existing project vault from chain.
+    /// Fails if the data account was not initialized or tracks another vault than the derived one.
+    pub async fn load(
+        rpc_client: Arc<dyn RpcBackend>,
+        program_id: Pubkey,
+        authority: &Pubkey,
+        startup_id: &str,
+        mint: Pubkey,
+    ) -> Result<VentureLaunch, VentureLaunchError> {
+        let mint_info = get_mint_info(rpc_client.as_ref(), &mint).await?;
+        let addresses = VaultAddresses::derive(authority, &program_id, startup_id, &mint, &mint_info.token_program)?;
+        let vl = VentureLaunch::new(
+            rpc_client,
+            program_id,
+            addresses.vault_account,
+            addresses.data_account,
+            mint,
+        ).with_mint_info(&mint_info);
+
+        let data = vl.get_vault_data().await?;
+        if !data.is_initialized {
+            return Err(VentureLaunchError::VaultNotInitialized);
+        }
+        if data.vault_account_pubkey != vl.vault_account || data.initializer_pubkey != *authority {
+            return Err(VentureLaunchError::VaultAccountMismatch);
+        }
+
+        Ok(vl)
+    }
+
+    pub async fn invoke_create_vault(
+        &mut self,
+        payer: &Keypair,
+        startup_id: &str,
+    ) -> Result<Signature, VentureLaunchError> {
+        let instructions = self.create_vault_instructions(&payer.pubkey(), startup_id).await?;
+
+        self.sign_and_send(&instructions, payer).await
+    }
+
+    /// Instructions creating the vault of `startup_id` with `authority` as funder, seed base and initializer.
+    /// `authority` signs them, either directly or as a multisig vault executing them.
+    pub(crate) async fn create_vault_instructions(
+        &mut self,
+        authority: &Pubkey,
+        startup_id: &str,
+    ) -> Result<Vec<Instruction>, VentureLaunchError> {
+        let mut instructions = Vec::new();
+        let addresses = VaultAddresses::derive(authority, &self.program_id, startup_id, &self.mint, &self.token_program)?;
+
+        // Create token account that will be transfered to program
+        let vault_rent = self.get_minimum_balance_for_rent_exemption(self.vault_account_size).await?;
+        let data_rent = self.get_minimum_balance_for_rent_exemption(CRYPTO_TRACKER_DATA_SIZE).await?;
+
+        instructions.push(solana_sdk::system_instruction::create_account_with_seed(
+            authority,
+            &addresses.vault_account,
+            authority,
+            &addresses.vault_seed,
+            vault_rent,
+            self.vault_account_size,
+            &self.token_program
+        ));
+        match spl_token_2022::instruction::initialize_account(
+            &self.token_program,
+            &addresses.vault_account,
+            &self.mint,
+            authority
+        ) {
+            Ok(ix) => instructions.push(ix),
+            Err(_) => return Err(VentureLaunchError::FailedToBuildTokenInstruction)
+        };
+
+        // Create data account (to store state) that will be transfered to program
+        instructions.push(solana_sdk::system_instruction::create_account_with_seed(
+            authority,
+            &addresses.data_account,
+            authority,
+            &addresses.data_seed,
+            data_rent,
+            CRYPTO_TRACKER_DATA_SIZE,
+            &self.program_id
+        ));
+
+        self.vault_account = addresses.vault_account;
+        self.data_account = addresses.data_account;
+
+        // Add call to the smart contract
+        instructions.push(instruction::create_vault(
+            self,
+            authority
+        )?);
+
+        Ok(instructions)
+    }
+
+    pub async fn invoke_deposit(
+        &self,
+        payer: &Keypair,
+        deposit_account: &Pubkey,
+        amount: u64
+    ) -> Result<Signature, VentureLaunchError> {
+        let deposit_instruction = instruction::deposit(
+            self,
+            &payer.pubkey(),
+            deposit_account,
+            amount
+        )?;
+
+        self.sign_and_send(&[deposit_instruction], payer).await
+    }
+
+    /// Deposits `amount` lamports of native SOL in one transaction: the payer's wSOL ATA is created if needed,
+    /// exactly `amount` is wrapped and deposited, and an ATA created for this deposit is closed again.
+    pub async fn invoke_deposit_native(
+        &self,
+        payer: &Keypair,
+        amount: u64,
+    ) -> Result<Signature, VentureLaunchError> {
+        let native_mint = spl_token::native_mint::id();
+        if self.mint != native_mint {
+            return Err(VentureLaunchError::MintIsNotNative);
+        }
+
+        let wrapped_sol_ata = get_associated_token_address(&native_mint, &payer.pubkey());
+        // An ATA the payer already had may hold wSOL of its own, so it is left open
+        let ata_exists = match self.rpc_client.get_account_with_commitment(&wrapped_sol_ata, self.rpc_client.commitment()).await {
+            Ok(response) => response.value.is_some(),
+            Err(_) => return Err(VentureLaunchError::FailedToFetchTokenAccount)
+        };
+
+        let mut instructions = Vec::new();
+        if !ata_exists {
+            instructions.push(associated_token::instruction::create_associated_token_account_idempotent(
+                &payer.pubkey(),
+                &wrapped_sol_ata,
+                &payer.pubkey(),
+                &native_mint,
+                &spl_token::id()
+            ));
+        }
+        instructions.extend(native::wrap_instructions(&payer.pubkey(), &wrapped_sol_ata, amount)?);
+        instructions.push(instruction::deposit(
+            self,
+            &payer.pubkey(),
+            &wrapped_sol_ata,
+            amount
+        )?);
+        if !ata_exists {
+            instructions.push(native::unwrap_instruction(&payer.pubkey(), &wrapped_sol_ata, &payer.pubkey())?);
+        }
+
+        self.sign_and_send(&instructions, payer).await
+    }
+
+    pub async fn invoke_withdraw(
+        &self,
+        payer: &Keypair,
+        withdraw_account: &Pubkey,
+        amount: u64,
+    ) -> Result<Signature, VentureLaunchError> {
+        let withdraw_instruction = instruction::withdraw(
+            self,
+            &payer.pubkey(),
+            withdraw_account,
+            amount
+        )?;
+
+        self.sign_and_send(&[withdraw_instruction], payer).await
+    }
+
+    pub async fn get_vault_data(&self) -> Result<CryptoTracker, VentureLaunchError> {
+        let raw_data = match self.rpc_client.get_account_data(&self.data_account).await {
+            Ok(data) => data,
+            Err(_) => return Err(VentureLaunchError::FailedToFetchDataAccount)
+        };
+
+        match CryptoTracker::unpack_unchecked(&raw_data) {
+            Ok(data) => Ok(data),
+            Err(_) => Err(VentureLaunchError::FailedToDeserializeDataAccount)
+        }
+    }
+
+    /// The amount tracked by the program, read through `get_vault_state` so an invalid vault is an error.
+    pub async fn get_vault_balance(&self) -> Result<u64, VentureLaunchError> {
+        Ok(self.get_vault_state().await?.tracked_amount)
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, size: u64) -> Result<u64, VentureLaunchError> {
+        match self.rpc_client.get_minimum_balance_for_rent_exemption(size as usize).await {
+            Ok(lamports) => Ok(lamports),
+            Err(_) => Err(VentureLaunchError::FailedToGetMinimumBalanceForRentExemption)
+        }
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash, VentureLaunchError> {
+        match self.rpc_client.get_latest_blockhash().await {
+            Ok(hash) => Ok(hash),
+            Err(_) => Err(VentureLaunchError::ErrorOnGettingLatestBlockHash)
+        }
+    }
+
+    pub(crate) async fn sign_and_send(
+        &self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+    ) -> Result<Signature, VentureLaunchError> {
+        let tx = self.sign(instructions, payer).await?;
+
+        self.send(&tx, instructions).await
+    }
+
+    pub(crate) async fn sign(&self, instructions: &[Instruction], payer: &Keypair) -> Result<Transaction, VentureLaunchError> {
+        let msg = Message::new(instructions, Some(&payer.pubkey()));
+        let mut tx = Transaction::new_unsigned(msg);
+        let recent_blockhash = self.get_latest_blockhash().await?;
+
+        if tx.try_sign(&[payer], recent_blockhash).is_err() {
+            return Err(VentureLaunchError::FailedToSignTransaction);
+        }
+
+        Ok(tx)
+    }
+
+    /// Sends `tx`, made of `instructions`, and records it in the ledger once it landed.
+    pub(crate) async fn send(&self, tx: &Transaction, instructions: &[Instruction]) -> Result<Signature, VentureLaunchError> {
+        let signature = send_and_confirm(self.rpc_client.as_ref(), tx).await?;
+        self.record_ledger_entries(instructions, &signature).await?;
+
+        Ok(signature)
+    }
+}
\ No newline at end of file
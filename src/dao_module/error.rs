@@ -0,0 +1,59 @@
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use thiserror::Error;
+
+use crate::cluster_utils::error::ClusterProfileError;
+use crate::multisig_utils::error::BaseMultisigError;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DaoServiceError {
+    #[error("Failed to load cluster profile: {0}")]
+    ClusterProfile(String),
+    #[error("Failed to read keypair {0}")]
+    FailedToReadKeypair(String),
+    #[error("Failed to write keypair {0}")]
+    FailedToWriteKeypair(String),
+    #[error("Multisig {0} is not managed by this service")]
+    UnknownDao(Pubkey),
+    #[error("Transaction of {member} is rejected: {reason}")]
+    InvalidMemberTransaction { member: Pubkey, reason: String },
+    #[error("{0} is not a member of the multisig")]
+    NotAMember(Pubkey),
+    #[error("Proposal #{0} does not exist")]
+    ProposalNotFound(u64),
+    #[error(
+        "Proposal #{transaction_index} is stale: the multisig config changed at transaction #{stale_transaction_index} \
+         and it can no longer be approved. Send recreate_proposal to get the same action proposed as transaction #{next_transaction_index}"
+    )]
+    StaleProposal { transaction_index: u64, stale_transaction_index: u64, next_transaction_index: u64 },
+    #[error("Failed to fetch vault balance")]
+    FailedToFetchVaultBalance,
+    #[error("Transaction {signature} landed, but reading its outcome failed: {error}")]
+    OutcomeUnknown { signature: Signature, error: BaseMultisigError },
+    #[error(transparent)]
+    Multisig(#[from] BaseMultisigError)
+}
+
+impl DaoServiceError {
+    /// Whether the same request may succeed later without changes, e.g. once the RPC node answers again.
+    /// Transactions that failed, landed or may still land are final, so a retry never sends them twice.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            DaoServiceError::FailedToFetchVaultBalance
+                | DaoServiceError::Multisig(
+                    BaseMultisigError::FailedToFetchProgramConfigAccount
+                        | BaseMultisigError::FailedToFetchMultisigConfigAccount
+                        | BaseMultisigError::FailedToFetchProposalConfigAccount
+                        | BaseMultisigError::FailedToFetchTransactionAccount
+                        | BaseMultisigError::ErrorOnGettingLatestBlockHash
+                        | BaseMultisigError::FailedToSendTransaction
+                )
+        )
+    }
+}
+
+impl From<ClusterProfileError> for DaoServiceError {
+    fn from(e: ClusterProfileError) -> Self {
+        DaoServiceError::ClusterProfile(e.to_string())
+    }
+}
@@ -1,7 +1,61 @@
-pub fn create_dao() {
-    println!("huy");
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{read_keypair_file, write_keypair_file, Keypair},
+};
+
+use crate::dao_module::error::DaoServiceError;
+
+/// Keypairs held by the service, as Solana CLI keypair files below `dir`: the create key of every DAO
+/// it created under `daos/<multisig_pda>.json`. Member keys are never held, members sign for themselves.
+pub struct DaoRepository {
+    dir: PathBuf,
 }
 
-pub fn update_dao() {
-    println!("huy");
+impl DaoRepository {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        DaoRepository { dir: dir.into() }
+    }
+
+    fn dao_path(&self, multisig_pda: &Pubkey) -> PathBuf {
+        self.dir.join("daos").join(format!("{}.json", multisig_pda))
+    }
+
+    /// The create key derives the multisig address, it is needed to open the DAO again.
+    pub fn save_create_key(&self, multisig_pda: &Pubkey, create_key: &Keypair) -> Result<(), DaoServiceError> {
+        write_key(&self.dao_path(multisig_pda), create_key)
+    }
+
+    pub fn create_key(&self, multisig_pda: &Pubkey) -> Result<Keypair, DaoServiceError> {
+        let path = self.dao_path(multisig_pda);
+        if !path.exists() {
+            return Err(DaoServiceError::UnknownDao(*multisig_pda));
+        }
+
+        read_key(&path)
+    }
+}
+
+pub fn read_key(path: &Path) -> Result<Keypair, DaoServiceError> {
+    return match read_keypair_file(path) {
+        Ok(keypair) => Ok(keypair),
+        Err(_) => Err(DaoServiceError::FailedToReadKeypair(path.display().to_string())),
+    };
+}
+
+fn write_key(path: &Path, keypair: &Keypair) -> Result<(), DaoServiceError> {
+    let written = match path.parent() {
+        Some(parent) => fs::create_dir_all(parent).is_ok(),
+        None => true,
+    };
+
+    if !written || write_keypair_file(keypair, path).is_err() {
+        return Err(DaoServiceError::FailedToWriteKeypair(path.display().to_string()));
+    }
+
+    return Ok(());
 }
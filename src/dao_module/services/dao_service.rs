@@ -1,8 +1,631 @@
-use crate::dao_module::repositories::dao_repository;
-pub fn create_dao() {
-    dao_repository::create_dao();
+use std::{
+    collections::HashMap,
+    env,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    sanitize::Sanitize,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+use squads_multisig::{
+    pda::get_proposal_pda,
+    state::{Member, ProposalStatus},
+};
+
+use crate::cluster_utils::cluster_profile::ClusterProfile;
+use crate::dao_module::error::DaoServiceError;
+use crate::dao_module::repositories::dao_repository::{read_key, DaoRepository};
+use crate::multisig_utils::{
+    base_multisig::{BaseMultisig, BaseMultisigCreateArgs, ProposalSummary, DEFAULT_MULTISIG_CACHE_MAX_AGE, MAX_PROPOSALS_PER_PAGE},
+    base_multisig_trait::BaseMultisigTrait,
+    business_analyst_multisig_trait::{BusinessAnalystMultisigTrait, TransactionCreateAction},
+    error::BaseMultisigError,
+};
+use crate::rpc_utils::rpc_backend::RpcBackend;
+
+pub const DEFAULT_KEYSTORE_DIR: &str = "keystore";
+
+/// A DAO created by the service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedDao {
+    pub multisig_pda: Pubkey,
+    pub vault_pda: Pubkey,
+    pub create_key: Pubkey,
+    pub signature: Signature,
+}
+
+/// A transaction created together with its proposal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedProposal {
+    pub multisig_pda: Pubkey,
+    pub transaction_index: u64,
+    pub proposal_pda: Pubkey,
+    pub signature: Signature,
+}
+
+/// The outcome of a vote or cancellation on one proposal.
+#[derive(Clone)]
+pub struct ProposalUpdate {
+    pub multisig_pda: Pubkey,
+    pub proposal: ProposalSummary,
+    pub signature: Signature,
+}
+
+/// Everything `get_dao` reports about one DAO.
+#[derive(Clone)]
+pub struct DaoInfo {
+    pub multisig_pda: Pubkey,
+    pub vault_pda: Pubkey,
+    pub create_key: Pubkey,
+    pub threshold: u16,
+    pub time_lock: u32,
+    pub members: Vec<Member>,
+    pub transaction_index: u64,
+    pub stale_transaction_index: u64,
+    pub vault_balance: u64,
+    pub proposals: Vec<ProposalSummary>,
+}
+
+/// Runs the DAO commands against Squads. The service `payer` creates every DAO and joins it with the
+/// `Initiate` permission only: it proposes transactions and pays for them. Votes, cancellations and
+/// executions are signed by the members themselves, the service checks and relays their transactions.
+pub struct DaoService {
+    rpc_client: Arc<dyn RpcBackend>,
+    program_id: Pubkey,
+    payer: Keypair,
+    repository: DaoRepository,
+    /// One `BaseMultisig` per opened DAO, so every request on it goes through the same multisig cache
+    multisigs: Mutex<HashMap<Pubkey, Arc<BaseMultisig>>>,
+}
+
+impl DaoService {
+    pub fn new(rpc_client: Arc<dyn RpcBackend>, program_id: Pubkey, payer: Keypair, repository: DaoRepository) -> Self {
+        DaoService {
+            rpc_client,
+            program_id,
+            payer,
+            repository,
+            multisigs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Builds the service from the cluster profile, `DAO_KEYSTORE_DIR` (`keystore` by default) and
+    /// `DAO_PAYER_KEYPAIR`, a keypair file that defaults to `payer.json` in the keystore directory.
+    pub fn from_env() -> Result<Self, DaoServiceError> {
+        let profile = ClusterProfile::from_env()?;
+
+        let keystore_dir = env::var("DAO_KEYSTORE_DIR").unwrap_or_else(|_| DEFAULT_KEYSTORE_DIR.into());
+        let payer_path = match env::var("DAO_PAYER_KEYPAIR") {
+            Ok(path) => path.into(),
+            Err(_) => Path::new(&keystore_dir).join("payer.json"),
+        };
+        let payer = read_key(&payer_path)?;
+
+        Ok(DaoService::new(
+            Arc::new(profile.rpc_client()),
+            profile.squads_program_id,
+            payer,
+            DaoRepository::new(keystore_dir),
+        ))
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    pub fn repository(&self) -> &DaoRepository {
+        &self.repository
+    }
+
+    async fn multisig(&self, create_key: Keypair) -> Result<BaseMultisig, DaoServiceError> {
+        let multisig = BaseMultisig::new(BaseMultisigCreateArgs {
+            rpc_client: self.rpc_client.clone(),
+            program_id: self.program_id,
+            multisig_create_keypair: create_key,
+            creator: self.payer.pubkey(),
+            cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE,
+        })
+        .await?;
+
+        Ok(multisig)
+    }
+
+    fn multisigs(&self) -> MutexGuard<'_, HashMap<Pubkey, Arc<BaseMultisig>>> {
+        match self.multisigs.lock() {
+            Ok(multisigs) => multisigs,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Opens a DAO this service created, through the create key it kept. Later requests get the same instance.
+    async fn open(&self, multisig_pda: &Pubkey) -> Result<Arc<BaseMultisig>, DaoServiceError> {
+        if let Some(multisig) = self.multisigs().get(multisig_pda) {
+            return Ok(multisig.clone());
+        }
+
+        let create_key = self.repository.create_key(multisig_pda)?;
+        let multisig = self.multisig(create_key).await?;
+
+        if multisig.multisig_pda != *multisig_pda {
+            return Err(DaoServiceError::UnknownDao(*multisig_pda));
+        }
+
+        // Requests that opened the DAO at the same time all keep the first instance stored
+        Ok(self.multisigs().entry(*multisig_pda).or_insert_with(|| Arc::new(multisig)).clone())
+    }
+
+    /// Sends `transaction` of `member` after checking it holds nothing but `expected`, signed by `member`.
+    /// Only the discriminator of the instruction data is compared, so a member may add a memo.
+    async fn relay(
+        &self,
+        multisig: &BaseMultisig,
+        transaction: &Transaction,
+        expected: &Instruction,
+        member: &Pubkey,
+    ) -> Result<Signature, DaoServiceError> {
+        let invalid = |reason: &str| DaoServiceError::InvalidMemberTransaction {
+            member: *member,
+            reason: reason.to_string(),
+        };
+
+        // Every account index of a sanitized message is in range
+        if transaction.sanitize().is_err() {
+            return Err(invalid("it is malformed"));
+        }
+        if transaction.verify().is_err() {
+            return Err(invalid("its signatures do not verify"));
+        }
+        let message = &transaction.message;
+        let signed_by_member = message
+            .account_keys
+            .iter()
+            .position(|key| key == member)
+            .is_some_and(|index| message.is_signer(index));
+        if !signed_by_member {
+            return Err(invalid("it is not signed by the member"));
+        }
+
+        let [instruction] = message.instructions.as_slice() else {
+            return Err(invalid("it has to hold exactly one instruction"));
+        };
+        let accounts: Vec<Pubkey> = instruction
+            .accounts
+            .iter()
+            .map(|index| message.account_keys[*index as usize])
+            .collect();
+        let expected_accounts: Vec<Pubkey> = expected.accounts.iter().map(|account| account.pubkey).collect();
+        if message.account_keys[instruction.program_id_index as usize] != expected.program_id
+            || accounts != expected_accounts
+            || instruction.data.get(..8) != expected.data.get(..8)
+        {
+            return Err(invalid("it does not hold the expected Squads instruction"));
+        }
+
+        Ok(multisig.send_and_confirm_transaction(transaction).await?)
+    }
+
+    async fn proposal_summary(&self, multisig: &BaseMultisig, transaction_index: u64) -> Result<ProposalSummary, DaoServiceError> {
+        let proposal = match multisig.get_proposal(transaction_index).await? {
+            Some(proposal) => proposal,
+            None => return Err(DaoServiceError::ProposalNotFound(transaction_index)),
+        };
+        let stale_transaction_index = multisig.get_stale_transaction_index().await?;
+        let (proposal_pda, _) = get_proposal_pda(&multisig.multisig_pda, transaction_index, Some(&self.program_id));
+
+        Ok(ProposalSummary::new(transaction_index, proposal_pda, proposal.status, stale_transaction_index))
+    }
+
+    /// The proposal after `signature` landed. The transaction went through, so failing to read the
+    /// proposal is final: retrying the request would send it again.
+    async fn summary_after(
+        &self,
+        multisig: &BaseMultisig,
+        transaction_index: u64,
+        signature: Signature,
+    ) -> Result<ProposalSummary, DaoServiceError> {
+        match self.proposal_summary(multisig, transaction_index).await {
+            Ok(summary) => Ok(summary),
+            Err(DaoServiceError::Multisig(error)) => Err(DaoServiceError::OutcomeUnknown { signature, error }),
+            Err(error) => Err(error)
+        }
+    }
+
+    /// Creates a multisig with `members` and the payer as proposer. The create key is stored before the
+    /// transaction is sent, so a DAO that lands is always one the service can open again.
+    pub async fn create_dao(&self, members: &[Member], threshold: u16, time_lock: u32) -> Result<CreatedDao, DaoServiceError> {
+        let create_key = Keypair::new();
+        let multisig = self.multisig(create_key.insecure_clone()).await?;
+
+        self.repository.save_create_key(&multisig.multisig_pda, &create_key)?;
+
+        let mut tx = multisig.transaction_create_governed_multisig(members, threshold, time_lock).await?;
+        let recent_blockhash = tx.message.recent_blockhash;
+        if tx.try_sign(&[&self.payer, &create_key], recent_blockhash).is_err() {
+            return Err(DaoServiceError::Multisig(BaseMultisigError::FailedToSignTransaction));
+        }
+        let signature = multisig.send_and_confirm_transaction(&tx).await?;
+
+        let created = CreatedDao {
+            multisig_pda: multisig.multisig_pda,
+            vault_pda: multisig.vault_pda,
+            create_key: create_key.pubkey(),
+            signature,
+        };
+        self.multisigs().insert(multisig.multisig_pda, Arc::new(multisig));
+
+        Ok(created)
+    }
+
+    /// Creates the transaction for `action` with its proposal, initiated by the payer.
+    pub async fn propose(&self, multisig_pda: &Pubkey, action: TransactionCreateAction) -> Result<CreatedProposal, DaoServiceError> {
+        let multisig = self.open(multisig_pda).await?;
+
+        let (transaction_index, signature) = multisig.submit_transaction_create(&self.payer, action).await?;
+        let (proposal_pda, _) = get_proposal_pda(multisig_pda, transaction_index, Some(&self.program_id));
+
+        Ok(CreatedProposal {
+            multisig_pda: *multisig_pda,
+            transaction_index,
+            proposal_pda,
+            signature,
+        })
+    }
+
+    /// Proposes the action of the stale proposal at `transaction_index` again under a new index, as
+    /// suggested by `StaleProposal`. Only proposals still open for voting are re-created.
+    pub async fn recreate_proposal(&self, multisig_pda: &Pubkey, transaction_index: u64) -> Result<CreatedProposal, DaoServiceError> {
+        let multisig = self.open(multisig_pda).await?;
+        if !self.proposal_summary(&multisig, transaction_index).await?.is_stale {
+            return Err(DaoServiceError::Multisig(BaseMultisigError::ProposalIsNotStale));
+        }
+
+        let (transaction_index, signature) = multisig.recreate_stale_transaction(&self.payer, transaction_index).await?;
+        let (proposal_pda, _) = get_proposal_pda(multisig_pda, transaction_index, Some(&self.program_id));
+
+        Ok(CreatedProposal {
+            multisig_pda: *multisig_pda,
+            transaction_index,
+            proposal_pda,
+            signature,
+        })
+    }
+
+    /// Relays `approver`'s approval, `transaction` being the Squads `proposal_approve` signed by `approver`.
+    pub async fn approve_proposal(
+        &self,
+        multisig_pda: &Pubkey,
+        transaction_index: u64,
+        approver: &Pubkey,
+        transaction: &Transaction,
+    ) -> Result<ProposalUpdate, DaoServiceError> {
+        let multisig = self.open(multisig_pda).await?;
+        if !multisig.is_member(*approver).await? {
+            return Err(DaoServiceError::NotAMember(*approver));
+        }
+
+        let summary = self.proposal_summary(&multisig, transaction_index).await?;
+        if summary.is_stale {
+            let account = multisig.get_multisig().await?;
+            return Err(DaoServiceError::StaleProposal {
+                transaction_index,
+                stale_transaction_index: account.stale_transaction_index,
+                next_transaction_index: account.transaction_index + 1,
+            });
+        }
+
+        let ix = multisig.instruction_proposal_approve_at(*approver, transaction_index).await?;
+        let signature = self.relay(&multisig, transaction, &ix, approver).await?;
+
+        Ok(ProposalUpdate {
+            multisig_pda: *multisig_pda,
+            proposal: self.summary_after(&multisig, transaction_index, signature).await?,
+            signature,
+        })
+    }
+
+    /// Relays `canceler`'s cancellation vote on an approved proposal, signed by `canceler` like approvals.
+    pub async fn cancel_proposal(
+        &self,
+        multisig_pda: &Pubkey,
+        transaction_index: u64,
+        canceler: &Pubkey,
+        transaction: &Transaction,
+    ) -> Result<ProposalUpdate, DaoServiceError> {
+        let multisig = self.open(multisig_pda).await?;
+        if !multisig.is_member(*canceler).await? {
+            return Err(DaoServiceError::NotAMember(*canceler));
+        }
+
+        let ix = multisig.instruction_proposal_cancel_at(*canceler, transaction_index).await?;
+        let signature = self.relay(&multisig, transaction, &ix, canceler).await?;
+
+        Ok(ProposalUpdate {
+            multisig_pda: *multisig_pda,
+            proposal: self.summary_after(&multisig, transaction_index, signature).await?,
+            signature,
+        })
+    }
+
+    /// Relays the execution of the approved config or vault transaction at `transaction_index`,
+    /// signed by `executor`, a member with the `Execute` permission.
+    pub async fn execute_proposal(
+        &self,
+        multisig_pda: &Pubkey,
+        transaction_index: u64,
+        executor: &Pubkey,
+        transaction: &Transaction,
+    ) -> Result<ProposalUpdate, DaoServiceError> {
+        let multisig = self.open(multisig_pda).await?;
+        if !multisig.is_member(*executor).await? {
+            return Err(DaoServiceError::NotAMember(*executor));
+        }
+
+        let summary = self.proposal_summary(&multisig, transaction_index).await?;
+        if !matches!(summary.status, ProposalStatus::Approved { .. }) {
+            return Err(DaoServiceError::Multisig(BaseMultisigError::ProposalStatusIsNotApproved));
+        }
+
+        let ix = multisig.instruction_transaction_execute_at(*executor, transaction_index).await?;
+        let signature = self.relay(&multisig, transaction, &ix, executor).await?;
+
+        Ok(ProposalUpdate {
+            multisig_pda: *multisig_pda,
+            proposal: self.summary_after(&multisig, transaction_index, signature).await?,
+            signature,
+        })
+    }
+
+    /// The DAO with one page of its proposals, the newest ones unless `proposals_before` says otherwise.
+    pub async fn get_dao(&self, multisig_pda: &Pubkey, proposals_before: Option<u64>) -> Result<DaoInfo, DaoServiceError> {
+        let multisig = self.open(multisig_pda).await?;
+        let account = multisig.get_multisig().await?;
+
+        let vault_balance = match self.rpc_client.get_balance(&multisig.vault_pda).await {
+            Ok(balance) => balance,
+            Err(_) => return Err(DaoServiceError::FailedToFetchVaultBalance),
+        };
+
+        Ok(DaoInfo {
+            multisig_pda: *multisig_pda,
+            vault_pda: multisig.vault_pda,
+            create_key: account.create_key,
+            threshold: account.threshold,
+            time_lock: account.time_lock,
+            members: account.members.clone(),
+            transaction_index: account.transaction_index,
+            stale_transaction_index: account.stale_transaction_index,
+            vault_balance,
+            proposals: multisig.list_proposals(proposals_before, MAX_PROPOSALS_PER_PAGE).await?,
+        })
+    }
 }
 
-pub fn update_dao() {
-    dao_repository::update_dao();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_utils::in_process_bank::InProcessBank;
+    use solana_sdk::native_token::LAMPORTS_PER_SOL;
+    use squads_multisig::{squads_multisig_program, state::{Permission, Permissions}};
+
+    fn test_service(rpc_client: Arc<dyn RpcBackend>, payer: Keypair) -> DaoService {
+        let keystore_dir = env::temp_dir().join(format!("dao-keystore-{}", Keypair::new().pubkey()));
+
+        DaoService::new(rpc_client, squads_multisig_program::ID, payer, DaoRepository::new(keystore_dir))
+    }
+
+    async fn airdrop(rpc_client: &dyn RpcBackend, address: &Pubkey, lamports: u64) {
+        let signature = rpc_client.request_airdrop(address, lamports).await.unwrap();
+        assert!(rpc_client.confirm_transaction(&signature).await.unwrap());
+    }
+
+    /// What the wallet of `member` sends: `instruction` in a transaction signed with the member's own key.
+    async fn signed_by(service: &DaoService, multisig_pda: &Pubkey, member: &Keypair, instruction: Instruction) -> Transaction {
+        let multisig = service.open(multisig_pda).await.unwrap();
+        let mut tx = multisig.get_transaction_from_instructions(member.pubkey(), &[instruction]).await.unwrap();
+        tx.sign(&[member], tx.message.recent_blockhash);
+        tx
+    }
+
+    #[test]
+    fn only_requests_that_sent_nothing_are_transient() {
+        let multisig = |error| DaoServiceError::Multisig(error);
+        assert!(multisig(BaseMultisigError::FailedToSendTransaction).is_transient());
+        assert!(multisig(BaseMultisigError::FailedToFetchProposalConfigAccount).is_transient());
+        assert!(!multisig(BaseMultisigError::TransactionNotConfirmed).is_transient());
+        assert!(!multisig(BaseMultisigError::TransactionFailed).is_transient());
+        assert!(!DaoServiceError::OutcomeUnknown {
+            signature: Signature::new_unique(),
+            error: BaseMultisigError::FailedToFetchProposalConfigAccount,
+        }
+        .is_transient());
+    }
+
+    #[tokio::test]
+    async fn dao_lifecycle() {
+        let rpc_client: Arc<dyn RpcBackend> = Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let payer = Keypair::new();
+        airdrop(rpc_client.as_ref(), &payer.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+        let service = test_service(rpc_client.clone(), payer);
+
+        let investor = Keypair::new();
+        airdrop(rpc_client.as_ref(), &investor.pubkey(), LAMPORTS_PER_SOL).await;
+        let voter = Member {
+            key: investor.pubkey(),
+            permissions: Permissions::from_vec(&[Permission::Vote, Permission::Execute]),
+        };
+
+        let dao = service.create_dao(&[voter], 1, 0).await.unwrap();
+        let info = service.get_dao(&dao.multisig_pda, None).await.unwrap();
+        assert_eq!(2, info.members.len());
+        assert_eq!(1, info.threshold);
+        assert_eq!(dao.vault_pda, info.vault_pda);
+        // The payer proposes but has no say in what passes
+        let payer_member = info.members.iter().find(|member| member.key == service.payer()).unwrap();
+        assert_eq!(Permissions::from_vec(&[Permission::Initiate]), payer_member.permissions);
+
+        // Config transaction approved and executed by the member, the service only relays
+        let newcomer = Keypair::new().pubkey();
+        let proposal = service
+            .propose(
+                &dao.multisig_pda,
+                TransactionCreateAction::AddMember {
+                    new_member: Member { key: newcomer, permissions: Permissions::from_vec(&[Permission::Vote]) },
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, proposal.transaction_index);
+
+        let multisig = service.open(&dao.multisig_pda).await.unwrap();
+        let approve = multisig.instruction_proposal_approve_at(investor.pubkey(), 1).await.unwrap();
+        let approval = signed_by(&service, &dao.multisig_pda, &investor, approve).await;
+        let approved = service.approve_proposal(&dao.multisig_pda, 1, &investor.pubkey(), &approval).await.unwrap();
+        assert!(matches!(approved.proposal.status, ProposalStatus::Approved { .. }));
+        let execute = multisig.instruction_transaction_execute_at(investor.pubkey(), 1).await.unwrap();
+        let execution = signed_by(&service, &dao.multisig_pda, &investor, execute).await;
+        let executed = service.execute_proposal(&dao.multisig_pda, 1, &investor.pubkey(), &execution).await.unwrap();
+        assert!(matches!(executed.proposal.status, ProposalStatus::Executed { .. }));
+        assert_eq!(3, service.get_dao(&dao.multisig_pda, None).await.unwrap().members.len());
+
+        // Vault transfer approved, then cancelled instead of executed
+        airdrop(rpc_client.as_ref(), &dao.vault_pda, 2 * LAMPORTS_PER_SOL).await;
+        let receiver = Keypair::new().pubkey();
+        let proposal = service
+            .propose(
+                &dao.multisig_pda,
+                TransactionCreateAction::TransferFromVault { receiver, lamports: LAMPORTS_PER_SOL },
+            )
+            .await
+            .unwrap();
+        assert_eq!(2, proposal.transaction_index);
+
+        // A transaction for another proposal, or not signed by the member, is not relayed
+        assert!(matches!(
+            service.approve_proposal(&dao.multisig_pda, 2, &investor.pubkey(), &approval).await,
+            Err(DaoServiceError::InvalidMemberTransaction { member, .. }) if member == investor.pubkey()
+        ));
+        let approve = multisig.instruction_proposal_approve_at(investor.pubkey(), 2).await.unwrap();
+        let mut unsigned = signed_by(&service, &dao.multisig_pda, &investor, approve.clone()).await;
+        unsigned.signatures[0] = Signature::default();
+        assert!(matches!(
+            service.approve_proposal(&dao.multisig_pda, 2, &investor.pubkey(), &unsigned).await,
+            Err(DaoServiceError::InvalidMemberTransaction { .. })
+        ));
+
+        let mut malformed = signed_by(&service, &dao.multisig_pda, &investor, approve.clone()).await;
+        malformed.message.instructions[0].accounts.push(u8::MAX);
+        malformed.sign(&[&investor], malformed.message.recent_blockhash);
+        assert!(matches!(
+            service.approve_proposal(&dao.multisig_pda, 2, &investor.pubkey(), &malformed).await,
+            Err(DaoServiceError::InvalidMemberTransaction { .. })
+        ));
+
+        let approval = signed_by(&service, &dao.multisig_pda, &investor, approve).await;
+        service.approve_proposal(&dao.multisig_pda, 2, &investor.pubkey(), &approval).await.unwrap();
+        let cancel = multisig.instruction_proposal_cancel_at(investor.pubkey(), 2).await.unwrap();
+        let cancellation = signed_by(&service, &dao.multisig_pda, &investor, cancel).await;
+        let cancelled = service.cancel_proposal(&dao.multisig_pda, 2, &investor.pubkey(), &cancellation).await.unwrap();
+        assert!(matches!(cancelled.proposal.status, ProposalStatus::Cancelled { .. }));
+        let execute = multisig.instruction_transaction_execute_at(investor.pubkey(), 2).await.unwrap();
+        let execution = signed_by(&service, &dao.multisig_pda, &investor, execute).await;
+        assert_eq!(
+            Err(DaoServiceError::Multisig(BaseMultisigError::ProposalStatusIsNotApproved)),
+            service.execute_proposal(&dao.multisig_pda, 2, &investor.pubkey(), &execution).await.map(|update| update.signature)
+        );
+
+        let info = service.get_dao(&dao.multisig_pda, None).await.unwrap();
+        assert_eq!(2, info.proposals.len());
+        assert_eq!(2 * LAMPORTS_PER_SOL, info.vault_balance);
+
+        assert_eq!(
+            Err(DaoServiceError::NotAMember(receiver)),
+            service.approve_proposal(&dao.multisig_pda, 2, &receiver, &approval).await.map(|update| update.signature)
+        );
+        let unknown = Keypair::new().pubkey();
+        assert!(matches!(service.get_dao(&unknown, None).await, Err(DaoServiceError::UnknownDao(pda)) if pda == unknown));
+    }
+
+    #[tokio::test]
+    async fn requests_share_one_multisig_per_dao() {
+        let rpc_client: Arc<dyn RpcBackend> = Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let payer = Keypair::new();
+        airdrop(rpc_client.as_ref(), &payer.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+        let service = test_service(rpc_client.clone(), payer);
+
+        let voter = Member {
+            key: Keypair::new().pubkey(),
+            permissions: Permissions::from_vec(&[Permission::Vote, Permission::Execute]),
+        };
+        let dao = service.create_dao(&[voter], 1, 0).await.unwrap();
+        let first = service.open(&dao.multisig_pda).await.unwrap();
+        assert!(Arc::ptr_eq(&first, &service.open(&dao.multisig_pda).await.unwrap()));
+
+        // The proposal invalidates the shared cache, so the next request sees it at once
+        assert_eq!(0, service.get_dao(&dao.multisig_pda, None).await.unwrap().transaction_index);
+        let new_member = Member { key: Keypair::new().pubkey(), permissions: Permissions::from_vec(&[Permission::Vote]) };
+        service.propose(&dao.multisig_pda, TransactionCreateAction::AddMember { new_member }).await.unwrap();
+        assert_eq!(1, service.get_dao(&dao.multisig_pda, None).await.unwrap().transaction_index);
+    }
+
+    #[tokio::test]
+    async fn stale_proposals_are_recreated() {
+        let rpc_client: Arc<dyn RpcBackend> = Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let payer = Keypair::new();
+        airdrop(rpc_client.as_ref(), &payer.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+        let service = test_service(rpc_client.clone(), payer);
+        let investor = Keypair::new();
+        airdrop(rpc_client.as_ref(), &investor.pubkey(), LAMPORTS_PER_SOL).await;
+        let voter = Member {
+            key: investor.pubkey(),
+            permissions: Permissions::from_vec(&[Permission::Vote, Permission::Execute]),
+        };
+        let dao = service.create_dao(&[voter], 1, 0).await.unwrap();
+        airdrop(rpc_client.as_ref(), &dao.vault_pda, 2 * LAMPORTS_PER_SOL).await;
+        let multisig = service.open(&dao.multisig_pda).await.unwrap();
+
+        let transfer = || TransactionCreateAction::TransferFromVault { receiver: investor.pubkey(), lamports: LAMPORTS_PER_SOL };
+        let approved = service.propose(&dao.multisig_pda, transfer()).await.unwrap().transaction_index;
+        let approve = multisig.instruction_proposal_approve_at(investor.pubkey(), approved).await.unwrap();
+        let approval = signed_by(&service, &dao.multisig_pda, &investor, approve).await;
+        service.approve_proposal(&dao.multisig_pda, approved, &investor.pubkey(), &approval).await.unwrap();
+        let active = service.propose(&dao.multisig_pda, transfer()).await.unwrap().transaction_index;
+
+        // A config change makes both stale on chain
+        let config = service
+            .propose(&dao.multisig_pda, TransactionCreateAction::ChangeThreshold { new_threshold: 1 })
+            .await
+            .unwrap()
+            .transaction_index;
+        let approve = multisig.instruction_proposal_approve_at(investor.pubkey(), config).await.unwrap();
+        let approval = signed_by(&service, &dao.multisig_pda, &investor, approve).await;
+        service.approve_proposal(&dao.multisig_pda, config, &investor.pubkey(), &approval).await.unwrap();
+        let execute = multisig.instruction_transaction_execute_at(investor.pubkey(), config).await.unwrap();
+        let execution = signed_by(&service, &dao.multisig_pda, &investor, execute).await;
+        service.execute_proposal(&dao.multisig_pda, config, &investor.pubkey(), &execution).await.unwrap();
+
+        multisig.refresh().await.unwrap();
+        let approve = multisig.instruction_proposal_approve_at(investor.pubkey(), active).await;
+        assert_eq!(Err(BaseMultisigError::ProposalIsStale), approve.map(|_| ()));
+        let error = service.approve_proposal(&dao.multisig_pda, active, &investor.pubkey(), &approval).await.err().unwrap();
+        assert!(error.to_string().contains("Send recreate_proposal"), "{}", error);
+        let recreated = service.recreate_proposal(&dao.multisig_pda, active).await.unwrap();
+        assert_eq!(config + 1, recreated.transaction_index);
+
+        // The approved transfer is not stale for voting purposes and can still be executed
+        let proposals = service.get_dao(&dao.multisig_pda, None).await.unwrap().proposals;
+        assert!(!proposals.iter().find(|proposal| proposal.transaction_index == approved).unwrap().is_stale);
+        assert!(matches!(
+            service.recreate_proposal(&dao.multisig_pda, approved).await,
+            Err(DaoServiceError::Multisig(BaseMultisigError::ProposalIsNotStale))
+        ));
+        let execute = multisig.instruction_transaction_execute_at(investor.pubkey(), approved).await.unwrap();
+        let execution = signed_by(&service, &dao.multisig_pda, &investor, execute).await;
+        service.execute_proposal(&dao.multisig_pda, approved, &investor.pubkey(), &execution).await.unwrap();
+    }
 }
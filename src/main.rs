@@ -1,26 +1,23 @@
-use std::env;
+#![allow(clippy::needless_return)]
+
 use std::error::Error;
-use tokio;
+use std::sync::Arc;
+pub mod cluster_utils;
+pub mod contract_module;
 pub mod dao_module;
+pub mod multisig_utils;
 pub mod request_handler;
+pub mod rpc_utils;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let host = std::env::var("RABBIT_HOST").unwrap_or_else(|_| "localhost".into());
-    let queue = std::env::var("RESPONSE_QUEUE").unwrap_or_else(|_| "localhost".into());
-    let req_exchange: String =
-        std::env::var("REQUEST_EXCHANGE").unwrap_or_else(|_| "localhost".into());
-    // let port = std::env::var("RABBIT_PORT").unwrap_or_else(|_| "localhost".into());
+    let broker_config = request_handler::connection::BrokerConfig::from_env()?;
+    let dao_service = dao_module::services::dao_service::DaoService::from_env()?;
+    let registry = request_handler::consumers::dao_commands(Arc::new(dao_service));
+    let idempotency = request_handler::idempotency::store_from_env()?;
 
-    let rabbit_handle = request_handler::processor::start(
-        host.as_str(),
-        5672,
-        "guest",
-        "guest",
-        "request.rs".to_string(),
-        "request.consumer",
-    );
-    let (rabbit_result) = tokio::join!(rabbit_handle);
+    let rabbit_handle = request_handler::processor::start(broker_config, registry, idempotency);
+    let _rabbit_result = rabbit_handle.await;
 
     // env_logger::init();
 
@@ -1,21 +1,417 @@
+use std::{
+    collections::HashMap,
+    ops::RangeInclusive,
+    sync::{Arc, LazyLock, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
 use solana_sdk::{
     pubkey::Pubkey,
     signature::Keypair,
 };
-use squads_multisig::solana_client::nonblocking::rpc_client::RpcClient;
+use squads_multisig::{
+    anchor_lang::AccountDeserialize,
+    pda::get_proposal_pda,
+    squads_multisig_program::Multisig,
+    state::{Proposal, ProposalStatus},
+};
+use tokio::sync::Mutex as AsyncMutex;
+
+use super::error::BaseMultisigError;
+use crate::{cluster_utils::cluster_profile::ClusterProfile, rpc_utils::rpc_backend::{RpcBackend, MAX_MULTIPLE_ACCOUNTS}};
+
+pub const DEFAULT_MULTISIG_CACHE_MAX_AGE: Duration = Duration::from_secs(2);
 
 pub struct BaseMultisigCreateArgs {
-    pub rpc_client: RpcClient,
+    pub rpc_client: Arc<dyn RpcBackend>,
+    pub program_id: Pubkey,
     pub multisig_create_keypair: Keypair,
-    pub creator: Pubkey
+    pub creator: Pubkey,
+    /// How long a fetched `Multisig` account is served from memory before it is re-read.
+    /// `Duration::ZERO` disables caching.
+    pub cache_max_age: Duration
+}
+
+impl BaseMultisigCreateArgs {
+    pub fn from_profile(profile: &ClusterProfile, multisig_create_keypair: Keypair, creator: Pubkey) -> Self {
+        BaseMultisigCreateArgs {
+            rpc_client: Arc::new(profile.rpc_client()),
+            program_id: profile.squads_program_id,
+            multisig_create_keypair,
+            creator,
+            cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE
+        }
+    }
+}
+
+/// A copy of the on-chain `Multisig` account together with the slot it was read at.
+/// `version` is bumped every time a newer snapshot replaces the cached one.
+#[derive(Clone)]
+pub struct MultisigSnapshot {
+    pub multisig: Multisig,
+    pub context_slot: u64,
+    pub version: u64,
+    pub fetched_at: Instant
+}
+
+impl MultisigSnapshot {
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.fetched_at.elapsed() >= max_age
+    }
+}
+
+#[derive(Default)]
+struct MultisigCacheState {
+    snapshot: Option<MultisigSnapshot>,
+    invalidated: bool,
+    /// Bumped by every `invalidate`
+    generation: u64
+}
+
+#[derive(Default)]
+pub struct MultisigCache {
+    state: RwLock<MultisigCacheState>
+}
+
+impl MultisigCache {
+    pub fn get(&self, max_age: Duration) -> Option<MultisigSnapshot> {
+        let state = self.state.read().ok()?;
+
+        if state.invalidated {
+            return None;
+        }
+
+        state
+            .snapshot
+            .as_ref()
+            .filter(|snapshot| !snapshot.is_stale(max_age))
+            .cloned()
+    }
+
+    /// Stores `multisig` read at `context_slot` and returns the snapshot that is cached afterwards.
+    /// A response from a lagging RPC node (older slot than the cached one) does not replace a newer snapshot.
+    /// `generation` is the one taken before the read was sent: a read that was in flight when the cache
+    /// got invalidated may miss the transaction behind the invalidation, so it is dropped and `None` returned.
+    pub fn update(&self, multisig: Multisig, context_slot: u64, generation: u64) -> Option<MultisigSnapshot> {
+        let mut state = match self.state.write() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        if generation < state.generation {
+            return None;
+        }
+
+        let version = match state.snapshot.as_ref() {
+            Some(cached) if cached.context_slot > context_slot => return Some(cached.clone()),
+            Some(cached) => cached.version + 1,
+            None => 1
+        };
+
+        let snapshot = MultisigSnapshot {
+            multisig,
+            context_slot,
+            version,
+            fetched_at: Instant::now()
+        };
+        state.snapshot = Some(snapshot.clone());
+        state.invalidated = false;
+
+        Some(snapshot)
+    }
+
+    /// Forces the next read to go to the RPC node, e.g. after one of our own transactions landed.
+    pub fn invalidate(&self) {
+        let mut state = match self.state.write() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner()
+        };
+
+        state.invalidated = true;
+        state.generation += 1;
+    }
+
+    /// To be taken before a read is sent and handed to `update` with its result.
+    pub fn generation(&self) -> u64 {
+        match self.state.read() {
+            Ok(state) => state.generation,
+            Err(poisoned) => poisoned.into_inner().generation
+        }
+    }
+
+    pub fn version(&self) -> u64 {
+        match self.state.read() {
+            Ok(state) => state.snapshot.as_ref().map_or(0, |snapshot| snapshot.version),
+            Err(_) => 0
+        }
+    }
+}
+
+/// One entry of a proposal listing.
+/// `is_stale` is only set while the proposal can still change state: Squads refuses votes on it,
+/// and an approved config transaction can no longer be executed.
+#[derive(Clone)]
+pub struct ProposalSummary {
+    pub transaction_index: u64,
+    pub proposal_pda: Pubkey,
+    pub status: ProposalStatus,
+    pub is_stale: bool
+}
+
+impl ProposalSummary {
+    pub fn new(transaction_index: u64, proposal_pda: Pubkey, status: ProposalStatus, stale_transaction_index: u64) -> Self {
+        // Staleness only blocks voting; an approved vault transaction can still be executed
+        let is_open = matches!(status, ProposalStatus::Draft { .. } | ProposalStatus::Active { .. });
+
+        ProposalSummary {
+            transaction_index,
+            proposal_pda,
+            status,
+            is_stale: is_open && transaction_index <= stale_transaction_index
+        }
+    }
 }
 
 pub struct BaseMultisig {
-    pub rpc_client: RpcClient,
+    pub rpc_client: Arc<dyn RpcBackend>,
+    pub program_id: Pubkey,
     pub multisig_create_keypair: Keypair,
     pub creator: Pubkey,
     pub multisig_pda: Pubkey,
     pub vault_pda: Pubkey,
     pub program_config_pda: Pubkey,
-    pub treasury: Pubkey
-}
\ No newline at end of file
+    pub treasury: Pubkey,
+    pub cache_max_age: Duration,
+    pub multisig_cache: MultisigCache
+}
+
+pub const MAX_TRANSACTION_CREATE_ATTEMPTS: usize = 5;
+
+/// Reads of the `Multisig` account in a row that may be dropped because the cache got invalidated meanwhile.
+pub const MAX_REFRESH_ATTEMPTS: usize = 3;
+
+/// Most transaction indexes one proposal listing looks at.
+pub const MAX_PROPOSALS_PER_PAGE: u64 = 100;
+
+static TRANSACTION_CREATE_LOCKS: LazyLock<Mutex<HashMap<Pubkey, Arc<AsyncMutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the process-wide lock that serializes transaction creation for `multisig_pda`.
+/// Every `BaseMultisig` instance pointing at the same multisig shares it, different multisigs never contend.
+pub fn transaction_create_lock(multisig_pda: &Pubkey) -> Arc<AsyncMutex<()>> {
+    let mut locks = match TRANSACTION_CREATE_LOCKS.lock() {
+        Ok(locks) => locks,
+        Err(poisoned) => poisoned.into_inner()
+    };
+
+    locks.retain(|pda, lock| pda == multisig_pda || Arc::strong_count(lock) > 1);
+
+    locks
+        .entry(*multisig_pda)
+        .or_insert_with(|| Arc::new(AsyncMutex::new(())))
+        .clone()
+}
+
+/// Reads the `Multisig` account at `multisig_pda` and returns it with the slot it was read at.
+pub async fn fetch_multisig(rpc_client: &dyn RpcBackend, multisig_pda: &Pubkey) -> Result<(Multisig, u64), BaseMultisigError> {
+    let response =
+    match rpc_client.get_account_with_commitment(multisig_pda, rpc_client.commitment()).await {
+        Ok(response) => response,
+        Err(_) => return Err(BaseMultisigError::FailedToFetchMultisigConfigAccount)
+    };
+
+    let multisig_config =
+    match response.value {
+        Some(account) => account,
+        None => return Err(BaseMultisigError::FailedToFetchMultisigConfigAccount)
+    };
+
+    let mut multisig_config_data = multisig_config.data.as_slice();
+    match Multisig::try_deserialize(&mut multisig_config_data) {
+        Ok(multisig) => Ok((multisig, response.context.slot)),
+        Err(_) => Err(BaseMultisigError::FailedToDeserializeMultisigConfigData)
+    }
+}
+
+/// Reads the proposal of `transaction_index`, `None` if it was never created or has been closed.
+pub async fn fetch_proposal(
+    rpc_client: &dyn RpcBackend,
+    program_id: &Pubkey,
+    multisig_pda: &Pubkey,
+    transaction_index: u64
+) -> Result<Option<Proposal>, BaseMultisigError> {
+    let (proposal_pda, _) = get_proposal_pda(multisig_pda, transaction_index, Some(program_id));
+
+    let response =
+    match rpc_client.get_account_with_commitment(&proposal_pda, rpc_client.commitment()).await {
+        Ok(response) => response,
+        Err(_) => return Err(BaseMultisigError::FailedToFetchProposalConfigAccount)
+    };
+
+    let proposal_config =
+    match response.value {
+        Some(account) => account,
+        None => return Ok(None)
+    };
+
+    let mut proposal_config_data = proposal_config.data.as_slice();
+    match Proposal::try_deserialize(&mut proposal_config_data) {
+        Ok(proposal) => Ok(Some(proposal)),
+        Err(_) => Err(BaseMultisigError::FailedToDeserializeProposalConfigData)
+    }
+}
+
+/// Fetches the proposals of `transaction_indexes` in as few requests as the node allows.
+/// Transactions without a proposal, or with closed accounts, are skipped.
+pub async fn fetch_proposals(
+    rpc_client: &dyn RpcBackend,
+    program_id: &Pubkey,
+    multisig_pda: &Pubkey,
+    transaction_indexes: RangeInclusive<u64>
+) -> Result<Vec<(u64, Pubkey, Proposal)>, BaseMultisigError> {
+    let proposal_pdas: Vec<(u64, Pubkey)> = transaction_indexes
+        .map(|transaction_index| (transaction_index, get_proposal_pda(multisig_pda, transaction_index, Some(program_id)).0))
+        .collect();
+
+    let mut proposals = Vec::new();
+    for batch in proposal_pdas.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let pubkeys: Vec<Pubkey> = batch.iter().map(|(_, proposal_pda)| *proposal_pda).collect();
+        let accounts = match rpc_client.get_multiple_accounts(&pubkeys).await {
+            Ok(accounts) => accounts,
+            Err(_) => return Err(BaseMultisigError::FailedToFetchProposalConfigAccount)
+        };
+
+        for ((transaction_index, proposal_pda), account) in batch.iter().zip(accounts) {
+            let account = match account {
+                Some(account) => account,
+                None => continue
+            };
+
+            match Proposal::try_deserialize(&mut account.data.as_slice()) {
+                Ok(proposal) => proposals.push((*transaction_index, *proposal_pda, proposal)),
+                Err(_) => return Err(BaseMultisigError::FailedToDeserializeProposalConfigData)
+            }
+        }
+    }
+
+    Ok(proposals)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+    use crate::{
+        multisig_utils::base_multisig_trait::BaseMultisigTrait,
+        rpc_utils::in_process_bank::InProcessBank
+    };
+    use solana_sdk::{signature::Signer, system_instruction};
+    use squads_multisig::squads_multisig_program;
+
+    const LONG_MAX_AGE: Duration = Duration::from_secs(60);
+
+    fn multisig(transaction_index: u64) -> Multisig {
+        Multisig {
+            create_key: Pubkey::new_unique(),
+            config_authority: Pubkey::default(),
+            threshold: 1,
+            time_lock: 0,
+            transaction_index,
+            stale_transaction_index: 0,
+            rent_collector: None,
+            bump: 255,
+            members: Vec::new()
+        }
+    }
+
+    #[test]
+    fn updates_bump_the_version() {
+        let cache = MultisigCache::default();
+        assert_eq!(0, cache.version());
+        assert!(cache.get(LONG_MAX_AGE).is_none());
+
+        let first = cache.update(multisig(1), 10, cache.generation()).unwrap();
+        assert_eq!(1, first.version);
+
+        // A read at the same slot still replaces the snapshot, e.g. after an explicit refresh
+        let second = cache.update(multisig(2), 10, cache.generation()).unwrap();
+        assert_eq!(2, second.version);
+
+        let third = cache.update(multisig(3), 11, cache.generation()).unwrap();
+        assert_eq!(3, third.version);
+        assert_eq!(3, cache.version());
+
+        let cached = cache.get(LONG_MAX_AGE).unwrap();
+        assert_eq!(3, cached.multisig.transaction_index);
+        assert_eq!(11, cached.context_slot);
+        assert!(cache.get(Duration::ZERO).is_none());
+    }
+
+    #[test]
+    fn snapshots_from_older_slots_are_ignored() {
+        let cache = MultisigCache::default();
+        cache.update(multisig(5), 20, cache.generation()).unwrap();
+
+        let kept = cache.update(multisig(4), 19, cache.generation()).unwrap();
+        assert_eq!(1, kept.version);
+        assert_eq!(20, kept.context_slot);
+        assert_eq!(5, kept.multisig.transaction_index);
+        assert_eq!(5, cache.get(LONG_MAX_AGE).unwrap().multisig.transaction_index);
+    }
+
+    #[test]
+    fn invalidated_cache_serves_nothing_until_the_next_update() {
+        let cache = MultisigCache::default();
+        cache.update(multisig(1), 10, cache.generation()).unwrap();
+
+        cache.invalidate();
+        assert!(cache.get(LONG_MAX_AGE).is_none());
+        assert_eq!(1, cache.version());
+
+        cache.update(multisig(2), 11, cache.generation()).unwrap();
+        assert_eq!(2, cache.get(LONG_MAX_AGE).unwrap().version);
+    }
+
+    #[test]
+    fn reads_sent_before_an_invalidation_are_dropped() {
+        let cache = MultisigCache::default();
+        cache.update(multisig(1), 10, cache.generation()).unwrap();
+
+        // One of our transactions lands while a read is in flight
+        let generation = cache.generation();
+        cache.invalidate();
+        assert!(cache.update(multisig(1), 12, generation).is_none());
+        assert!(cache.get(LONG_MAX_AGE).is_none());
+        assert_eq!(1, cache.version());
+
+        let snapshot = cache.update(multisig(2), 13, cache.generation()).unwrap();
+        assert_eq!(2, snapshot.multisig.transaction_index);
+        assert_eq!(2, cache.get(LONG_MAX_AGE).unwrap().version);
+    }
+
+    #[tokio::test]
+    async fn sent_transactions_invalidate_the_cache() -> Result<(), Box<dyn Error>> {
+        let bank = Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let payer = Keypair::new();
+        bank.request_airdrop(&payer.pubkey(), 10_u64.pow(9)).await?;
+
+        let base_multisig = BaseMultisig::new(BaseMultisigCreateArgs {
+            rpc_client: bank.clone(),
+            program_id: squads_multisig_program::ID,
+            multisig_create_keypair: Keypair::new(),
+            creator: payer.pubkey(),
+            cache_max_age: LONG_MAX_AGE
+        }).await?;
+        base_multisig.multisig_cache.update(multisig(1), 10, base_multisig.multisig_cache.generation()).unwrap();
+
+        let transfer = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1_000_000);
+        let mut tx = base_multisig.get_transaction_from_instructions(payer.pubkey(), &[transfer]).await?;
+        tx.sign(&[&payer], tx.message.recent_blockhash);
+        base_multisig.send_and_confirm_transaction(&tx).await?;
+
+        assert!(base_multisig.multisig_cache.get(LONG_MAX_AGE).is_none());
+
+        Ok(())
+    }
+}
@@ -1,15 +1,15 @@
-use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
-    instruction::Instruction, message::Message, pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction
+    instruction::Instruction, message::Message, pubkey::Pubkey, signature::{Keypair, Signature}, signer::Signer, transaction::Transaction
 };
 use squads_multisig::{
-    anchor_lang::AccountDeserialize, client::{proposal_approve, proposal_cancel, ProposalVoteAccounts, ProposalVoteArgs}, pda::{get_multisig_pda, get_program_config_pda, get_proposal_pda, get_vault_pda}, squads_multisig_program::{self, state::ProgramConfig, Multisig}, state::{
+    anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas}, client::{proposal_approve, proposal_cancel, ProposalVoteAccounts, ProposalVoteArgs}, pda::{get_multisig_pda, get_program_config_pda, get_proposal_pda, get_vault_pda}, squads_multisig_program::{state::ProgramConfig, Multisig}, state::{
         Member, Proposal, ProposalStatus
     }
 };
 use async_trait::async_trait;
 
-use super::{base_multisig::{BaseMultisig, BaseMultisigCreateArgs}, error::BaseMultisigError};
+use crate::rpc_utils::rpc_backend::{send_and_confirm, RpcBackend, SendFailure};
+use super::{base_multisig::{fetch_multisig, fetch_proposal, fetch_proposals, BaseMultisig, BaseMultisigCreateArgs, MultisigCache, MultisigSnapshot, ProposalSummary, MAX_PROPOSALS_PER_PAGE, MAX_REFRESH_ATTEMPTS}, error::BaseMultisigError};
 
 #[async_trait]
 pub trait BaseMultisigTrait<Args>: Send + Sync {
@@ -20,6 +20,8 @@ pub trait BaseMultisigTrait<Args>: Send + Sync {
 
     fn get_multisig_create_args(&self) -> Args;
     async fn get_multisig(&self)                      -> Result<Multisig,        Self::Error>;
+    async fn refresh(&self)                           -> Result<MultisigSnapshot, Self::Error>;
+    fn invalidate_multisig_cache(&self);
     async fn get_multisig_members(&self)              -> Result<Vec<Member>,     Self::Error>{
         let multisig = self.get_multisig().await?;
         Ok(multisig.members)
@@ -36,11 +38,27 @@ pub trait BaseMultisigTrait<Args>: Send + Sync {
         let multisig = self.get_multisig().await?;
         Ok(multisig.is_member(member_pubkey).is_some())
     }
+    async fn get_stale_transaction_index(&self)       -> Result<u64,             Self::Error>{
+        let multisig = self.get_multisig().await?;
+        Ok(multisig.stale_transaction_index)
+    }
+    /// Every transaction up to `stale_transaction_index` was created before the last config change,
+    /// Squads no longer lets its proposal be voted on.
+    async fn is_stale(&self, transaction_index: u64)  -> Result<bool,            Self::Error>{
+        let stale_transaction_index = self.get_stale_transaction_index().await?;
+        Ok(transaction_index <= stale_transaction_index)
+    }
     async fn get_current_proposal_status(&self)       -> Result<ProposalStatus,  Self::Error>;
+    async fn get_proposal(&self, transaction_index: u64) -> Result<Option<Proposal>, Self::Error>;
+    /// Proposals of the `limit` transaction indexes right before `before`, up to `MAX_PROPOSALS_PER_PAGE`.
+    /// Without `before` the page ends with the newest transaction.
+    async fn list_proposals(&self, before: Option<u64>, limit: u64) -> Result<Vec<ProposalSummary>, Self::Error>;
 
     async fn get_transaction_from_instructions(&self, sender: Pubkey, instructions: &[Instruction]) -> Result<Transaction, Self::Error>;
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature, Self::Error>;
 
-    fn get_rpc_client(&self) -> &RpcClient;
+    fn get_rpc_client(&self) -> &dyn RpcBackend;
+    fn get_program_id(&self) -> Pubkey;
     fn get_creator_key(&self) -> Pubkey;
     fn get_multisig_pda(&self) -> Pubkey;
     fn get_vault_pda(&self) -> Pubkey;
@@ -48,8 +66,15 @@ pub trait BaseMultisigTrait<Args>: Send + Sync {
     fn get_treasury(&self) -> Pubkey;
     fn get_create_keypair(&self) -> &Keypair;
 
-    async fn instruction_proposal_approve(&self, approver: Pubkey)  -> Result<Instruction, Self::Error>;
+    async fn instruction_proposal_approve(&self, approver: Pubkey)  -> Result<Instruction, Self::Error> {
+        let transaction_index = self.get_multisig_transaction_index().await?;
+
+        self.instruction_proposal_approve_at(approver, transaction_index).await
+    }
+    async fn instruction_proposal_approve_at(&self, approver: Pubkey, transaction_index: u64) -> Result<Instruction, Self::Error>;
+    async fn instruction_proposal_reject_at(&self, rejecter: Pubkey, transaction_index: u64) -> Result<Instruction, Self::Error>;
     async fn instruction_proposal_cancel(&self, canceler: Pubkey) -> Result<Instruction, Self::Error>;
+    async fn instruction_proposal_cancel_at(&self, canceler: Pubkey, transaction_index: u64) -> Result<Instruction, Self::Error>;
     async fn transaction_proposal_approve(&self, approver: Pubkey)  -> Result<Transaction, Self::Error> {
         let ix = self.instruction_proposal_approve(approver).await?;
 
@@ -68,8 +93,11 @@ pub trait BaseMultisigTrait<Args>: Send + Sync {
 impl BaseMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
     type Error = BaseMultisigError;
 
-    fn get_rpc_client(&self) -> &RpcClient {
-        return &self.rpc_client;
+    fn get_rpc_client(&self) -> &dyn RpcBackend {
+        return self.rpc_client.as_ref();
+    }
+    fn get_program_id(&self) -> Pubkey {
+        return self.program_id;
     }
     fn get_creator_key(&self) -> Pubkey {
         return self.creator;
@@ -89,10 +117,13 @@ impl BaseMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
     fn get_create_keypair(&self) -> &Keypair{
         return &self.multisig_create_keypair;
     }
+    fn invalidate_multisig_cache(&self) {
+        self.multisig_cache.invalidate();
+    }
 
     async fn new(args: BaseMultisigCreateArgs) -> Result<Self, Self::Error>
     {
-        let program_id = squads_multisig_program::ID;
+        let program_id = args.program_id;
 
         let (multisig_pda, _)       = get_multisig_pda(&args.multisig_create_keypair.pubkey(), Some(&program_id));
         let (vault_pda, _)          = get_vault_pda(&multisig_pda, 0, Some(&program_id));
@@ -114,59 +145,83 @@ impl BaseMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
 
         Ok(BaseMultisig {
             rpc_client: args.rpc_client,
+            program_id,
             multisig_create_keypair: args.multisig_create_keypair,
             creator: args.creator,
             multisig_pda,
             vault_pda,
             program_config_pda,
-            treasury
+            treasury,
+            cache_max_age: args.cache_max_age,
+            multisig_cache: MultisigCache::default()
         })
     }
 
     fn get_multisig_create_args(&self) -> BaseMultisigCreateArgs {
         BaseMultisigCreateArgs {
-            rpc_client: RpcClient::new(self.rpc_client.url()),
+            rpc_client: self.rpc_client.clone(),
+            program_id: self.program_id,
             multisig_create_keypair: self.multisig_create_keypair.insecure_clone(),
-            creator: self.creator.clone()
+            creator: self.creator,
+            cache_max_age: self.cache_max_age
         }
     }
 
     async fn get_multisig(&self) -> Result<Multisig, Self::Error>{
-        let multisig_config =
-        match self.rpc_client.get_account(&self.multisig_pda).await{
-            Ok(account) => account,
-            Err(_) => return Err(Self::Error::FailedToFetchMultisigConfigAccount)
-        };
+        if let Some(snapshot) = self.multisig_cache.get(self.cache_max_age) {
+            return Ok(snapshot.multisig);
+        }
 
-        let mut multisig_config_data = multisig_config.data.as_slice();
-        let multisig =
-        match Multisig::try_deserialize(&mut multisig_config_data) {
-            Ok(a) => a,
-            Err(_) => return Err(Self::Error::FailedToDeserializeMultisigConfigData)
-        };
+        Ok(self.refresh().await?.multisig)
+    }
 
-        Ok(multisig)
+    async fn refresh(&self) -> Result<MultisigSnapshot, Self::Error>{
+        for _ in 0..MAX_REFRESH_ATTEMPTS {
+            let generation = self.multisig_cache.generation();
+            let (multisig, context_slot) = fetch_multisig(self.rpc_client.as_ref(), &self.multisig_pda).await?;
+
+            // Dropped when one of our transactions landed meanwhile, the read may predate it
+            if let Some(snapshot) = self.multisig_cache.update(multisig, context_slot, generation) {
+                return Ok(snapshot);
+            }
+        }
+
+        Err(Self::Error::FailedToFetchMultisigConfigAccount)
     }
 
     async fn get_current_proposal_status(&self) -> Result<ProposalStatus, Self::Error>{
-        let program_id = squads_multisig_program::ID;
         let transaction_index = self.get_multisig_transaction_index().await?;
-        let (proposal_pda, _) = get_proposal_pda(&self.multisig_pda, transaction_index, Some(&program_id));
 
-        let proposal_config =
-        match self.rpc_client.get_account(&proposal_pda).await{
-            Ok(account) => account,
-            Err(_) => return Err(Self::Error::FailedToFetchProposalConfigAccount)
-        };
+        match self.get_proposal(transaction_index).await? {
+            Some(proposal) => Ok(proposal.status),
+            None => Err(Self::Error::FailedToFetchProposalConfigAccount)
+        }
+    }
 
-        let mut proposal_config_data = proposal_config.data.as_slice();
-        let proposal =
-        match Proposal::try_deserialize(&mut proposal_config_data) {
-            Ok(a) => a,
-            Err(_) => return Err(Self::Error::FailedToDeserializeProposalConfigData)
-        };
+    async fn get_proposal(&self, transaction_index: u64) -> Result<Option<Proposal>, Self::Error>{
+        fetch_proposal(self.rpc_client.as_ref(), &self.program_id, &self.multisig_pda, transaction_index).await
+    }
+
+    async fn list_proposals(&self, before: Option<u64>, limit: u64) -> Result<Vec<ProposalSummary>, Self::Error>{
+        let multisig = self.get_multisig().await?;
 
-        Ok(proposal.status)
+        let last_index = match before {
+            Some(before) => before.saturating_sub(1).min(multisig.transaction_index),
+            None => multisig.transaction_index
+        };
+        let first_index = last_index.saturating_sub(limit.min(MAX_PROPOSALS_PER_PAGE)) + 1;
+
+        let proposals = fetch_proposals(self.rpc_client.as_ref(), &self.program_id, &self.multisig_pda, first_index..=last_index).await?;
+
+        Ok(proposals
+            .into_iter()
+            .map(|(transaction_index, proposal_pda, proposal)| ProposalSummary::new(
+                transaction_index,
+                proposal_pda,
+                proposal.status,
+                multisig.stale_transaction_index
+            ))
+            .collect())
     }
 
     async fn get_transaction_from_instructions(&self, sender: Pubkey, instructions: &[Instruction]) -> Result<Transaction, Self::Error> {
@@ -181,11 +236,28 @@ impl BaseMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
         Ok(Transaction::new_unsigned(message))
     }
 
-    async fn instruction_proposal_approve(&self, approver: Pubkey)  -> Result<Instruction, Self::Error> {
-        let program_id: Pubkey = squads_multisig_program::ID;
-        let transaction_index = self.get_multisig_transaction_index().await?;
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature, Self::Error> {
+        let result = send_and_confirm(self.rpc_client.as_ref(), transaction).await;
+
+        // Even a failed confirmation may have landed, so never trust the cached state afterwards.
+        self.invalidate_multisig_cache();
+
+        match result {
+            Ok(signature) => Ok(signature),
+            Err(SendFailure::NotSent) => Err(Self::Error::FailedToSendTransaction),
+            Err(SendFailure::Failed) => Err(Self::Error::TransactionFailed),
+            Err(SendFailure::NotConfirmed(_)) => Err(Self::Error::TransactionNotConfirmed)
+        }
+    }
+
+    async fn instruction_proposal_approve_at(&self, approver: Pubkey, transaction_index: u64) -> Result<Instruction, Self::Error> {
+        let program_id: Pubkey = self.program_id;
         let (proposal_pda, _) = get_proposal_pda(&self.multisig_pda, transaction_index, Some(&program_id));
 
+        if self.is_stale(transaction_index).await? {
+            return Err(Self::Error::ProposalIsStale);
+        }
+
         let proposal_approve_ix = proposal_approve(
             ProposalVoteAccounts {
                 multisig: self.multisig_pda,
@@ -199,16 +271,45 @@ impl BaseMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
         Ok(proposal_approve_ix)
     }
 
+    async fn instruction_proposal_reject_at(&self, rejecter: Pubkey, transaction_index: u64) -> Result<Instruction, Self::Error> {
+        let program_id: Pubkey = self.program_id;
+        let (proposal_pda, _) = get_proposal_pda(&self.multisig_pda, transaction_index, Some(&program_id));
+
+        if self.is_stale(transaction_index).await? {
+            return Err(Self::Error::ProposalIsStale);
+        }
+
+        // The client crate has no builder for reject, the accounts are the same as for approve
+        let proposal_reject_ix = Instruction {
+            program_id,
+            accounts: ProposalVoteAccounts {
+                multisig: self.multisig_pda,
+                member: rejecter,
+                proposal: proposal_pda
+            }.to_account_metas(Some(false)),
+            data: squads_multisig::squads_multisig_program::instruction::ProposalReject {
+                args: ProposalVoteArgs { memo: None }
+            }.data()
+        };
+
+        Ok(proposal_reject_ix)
+    }
+
     async fn instruction_proposal_cancel(&self, canceler: Pubkey) -> Result<Instruction, Self::Error> {
-        let program_id: Pubkey = squads_multisig_program::ID;
         let transaction_index = self.get_multisig_transaction_index().await?;
-        let (proposal_pda, _) = get_proposal_pda(&self.multisig_pda, transaction_index, Some(&program_id));
 
-        let proposal_status = self.get_current_proposal_status().await.unwrap();
+        self.instruction_proposal_cancel_at(canceler, transaction_index).await
+    }
+
+    async fn instruction_proposal_cancel_at(&self, canceler: Pubkey, transaction_index: u64) -> Result<Instruction, Self::Error> {
+        let program_id: Pubkey = self.program_id;
+        let (proposal_pda, _) = get_proposal_pda(&self.multisig_pda, transaction_index, Some(&program_id));
 
-        match proposal_status {
-            ProposalStatus::Approved { timestamp: _ } => {},
-            _ => return Err(Self::Error::ProposalStatusIsNotApproved)
+        // Squads only lets approved proposals be cancelled
+        match self.get_proposal(transaction_index).await? {
+            Some(Proposal { status: ProposalStatus::Approved { .. }, .. }) => {},
+            Some(_) => return Err(Self::Error::ProposalStatusIsNotApproved),
+            None => return Err(Self::Error::FailedToFetchProposalConfigAccount)
         }
 
         let proposal_cancel_ix = proposal_cancel(
@@ -227,24 +328,125 @@ impl BaseMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
 
 #[cfg(test)]
 mod tests {
-    use std::error::Error;
+    use std::{error::Error, sync::Arc};
 
     use super::*;
-    use solana_sdk::signature::Keypair;
+    use crate::{multisig_utils::base_multisig::DEFAULT_MULTISIG_CACHE_MAX_AGE, rpc_utils::in_process_bank::InProcessBank};
+    use solana_client::{client_error::{ClientError, Result as ClientResult}, rpc_request::RpcError, rpc_response::RpcResult};
+    use solana_sdk::{
+        account::Account, commitment_config::CommitmentConfig, hash::Hash, signature::Keypair, system_instruction,
+        transaction::Result as TransactionResult,
+    };
+    use squads_multisig::squads_multisig_program;
     use tokio;
 
     #[tokio::test]
     async fn get_base_multisig_instance() -> Result<(), Box<dyn Error>> {
-        let rpc_client = RpcClient::new("http://127.0.0.1:8899".to_string());
+        let program_id = squads_multisig_program::ID;
+        let rpc_client: Arc<dyn RpcBackend> = Arc::new(InProcessBank::with_squads(program_id));
         let creator: Keypair = Keypair::new();
         let create_key = Keypair::new();
 
-        let _ = BaseMultisig::new(BaseMultisigCreateArgs {
-            rpc_client: RpcClient::new(rpc_client.url()),
+        let base_multisig = BaseMultisig::new(BaseMultisigCreateArgs {
+            rpc_client,
+            program_id,
             multisig_create_keypair: create_key.insecure_clone(),
-            creator: creator.pubkey().clone()
+            creator: creator.pubkey(),
+            cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE
         }).await?;
 
+        assert_eq!(get_multisig_pda(&create_key.pubkey(), Some(&program_id)).0, base_multisig.get_multisig_pda());
+
+        Ok(())
+    }
+
+    /// Delegates to the bank, except that sending fails with `failure()`, after processing the
+    /// transaction when it `lands` and without otherwise.
+    struct FailingSend {
+        bank: Arc<InProcessBank>,
+        failure: fn() -> ClientError,
+        lands: bool,
+    }
+
+    #[async_trait]
+    impl RpcBackend for FailingSend {
+        fn url(&self) -> String { self.bank.url() }
+        fn commitment(&self) -> CommitmentConfig { self.bank.commitment() }
+        async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> { self.bank.get_account(pubkey).await }
+        async fn get_account_with_commitment(&self, pubkey: &Pubkey, commitment: CommitmentConfig) -> RpcResult<Option<Account>> {
+            self.bank.get_account_with_commitment(pubkey, commitment).await
+        }
+        async fn get_account_data(&self, pubkey: &Pubkey) -> ClientResult<Vec<u8>> { self.bank.get_account_data(pubkey).await }
+        async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+            self.bank.get_multiple_accounts(pubkeys).await
+        }
+        async fn get_balance(&self, pubkey: &Pubkey) -> ClientResult<u64> { self.bank.get_balance(pubkey).await }
+        async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+            self.bank.get_minimum_balance_for_rent_exemption(data_len).await
+        }
+        async fn get_latest_blockhash(&self) -> ClientResult<Hash> { self.bank.get_latest_blockhash().await }
+        async fn is_blockhash_valid(&self, blockhash: &Hash) -> ClientResult<bool> { self.bank.is_blockhash_valid(blockhash).await }
+        async fn get_signature_status(&self, signature: &Signature) -> ClientResult<Option<TransactionResult<()>>> {
+            self.bank.get_signature_status(signature).await
+        }
+        async fn get_signature_slot(&self, signature: &Signature) -> ClientResult<Option<u64>> { self.bank.get_signature_slot(signature).await }
+        async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+            if self.lands {
+                self.bank.send_and_confirm_transaction(transaction).await?;
+            }
+            Err((self.failure)())
+        }
+        async fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> ClientResult<Signature> { self.bank.request_airdrop(pubkey, lamports).await }
+        async fn confirm_transaction(&self, signature: &Signature) -> ClientResult<bool> { self.bank.confirm_transaction(signature).await }
+    }
+
+    fn connection_refused() -> ClientError {
+        ClientError::from(std::io::Error::from(std::io::ErrorKind::ConnectionRefused))
+    }
+
+    fn confirmation_timeout() -> ClientError {
+        ClientError::from(RpcError::ForUser("unable to confirm transaction".to_string()))
+    }
+
+    #[tokio::test]
+    async fn send_failures_tell_whether_the_transaction_may_have_landed() -> Result<(), Box<dyn Error>> {
+        let bank = Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let payer = Keypair::new();
+        bank.request_airdrop(&payer.pubkey(), 10_u64.pow(9)).await?;
+
+        let send = |rpc_client: Arc<dyn RpcBackend>, lamports: u64| {
+            let payer = payer.insecure_clone();
+            async move {
+                let multisig = BaseMultisig::new(BaseMultisigCreateArgs {
+                    rpc_client: rpc_client.clone(),
+                    program_id: squads_multisig_program::ID,
+                    multisig_create_keypair: Keypair::new(),
+                    creator: payer.pubkey(),
+                    cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE,
+                }).await.unwrap();
+                let transfer = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), lamports);
+                let mut tx = multisig.get_transaction_from_instructions(payer.pubkey(), &[transfer]).await.unwrap();
+                tx.sign(&[&payer], tx.message.recent_blockhash);
+                (tx.signatures[0], multisig.send_and_confirm_transaction(&tx).await)
+            }
+        };
+        let failing = |failure: fn() -> ClientError, lands: bool| -> Arc<dyn RpcBackend> {
+            Arc::new(FailingSend { bank: bank.clone(), failure, lands })
+        };
+
+        let (_, result) = send(failing(connection_refused, false), 1_000_000).await;
+        assert_eq!(Err(BaseMultisigError::FailedToSendTransaction), result);
+
+        let (_, result) = send(failing(confirmation_timeout, false), 2_000_000).await;
+        assert_eq!(Err(BaseMultisigError::TransactionNotConfirmed), result);
+
+        // Landed although the confirmation was lost
+        let (signature, result) = send(failing(confirmation_timeout, true), 3_000_000).await;
+        assert_eq!(Ok(signature), result);
+
+        let (_, result) = send(bank.clone(), 10 * 10_u64.pow(9)).await;
+        assert_eq!(Err(BaseMultisigError::TransactionFailed), result);
+
         Ok(())
     }
 }
\ No newline at end of file
@@ -1,14 +1,25 @@
 use super::{
-    base_multisig::{BaseMultisig, BaseMultisigCreateArgs},
+    base_multisig::{
+        transaction_create_lock, BaseMultisig, BaseMultisigCreateArgs,
+        MAX_TRANSACTION_CREATE_ATTEMPTS,
+    },
     base_multisig_trait::BaseMultisigTrait,
     error::BaseMultisigError,
 };
 use async_trait::async_trait;
 use solana_sdk::{
-    instruction::Instruction, pubkey::Pubkey, signer::Signer, system_instruction, system_program,
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction::{self, SystemInstruction},
+    system_program,
     transaction::Transaction,
 };
 use squads_multisig::{
+    anchor_lang::AccountDeserialize,
     client::{
         self, config_transaction_create, config_transaction_execute, multisig_create_v2,
         proposal_create, vault_transaction_create, vault_transaction_execute,
@@ -17,11 +28,22 @@ use squads_multisig::{
         ProposalCreateArgs, VaultTransactionCreateAccounts, VaultTransactionExecuteAccounts,
     },
     pda::{get_proposal_pda, get_transaction_pda},
-    squads_multisig_program,
-    state::{ConfigAction, Member, Permission, Permissions, TransactionMessage},
+    squads_multisig_program::state::VaultTransaction,
+    state::{ConfigAction, ConfigTransaction, Member, Permission, Permissions, TransactionMessage},
     vault_transaction::VaultTransactionMessageExt,
 };
 
+/// Operations that occupy a new transaction index of the multisig.
+#[derive(Clone)]
+pub enum TransactionCreateAction {
+    AddMember { new_member: Member },
+    RemoveMember { old_member: Pubkey },
+    ChangeThreshold { new_threshold: u16 },
+    TransferFromVault { receiver: Pubkey, lamports: u64 },
+    /// Arbitrary instructions signed by the multisig vault, e.g. a call into another program.
+    VaultInstructions { instructions: Vec<Instruction>, memo: Option<String> },
+}
+
 #[async_trait]
 pub trait BusinessAnalystMultisigTrait<Args = BaseMultisigCreateArgs>:
     BaseMultisigTrait<Args, Error = BaseMultisigError>
@@ -38,6 +60,20 @@ pub trait BusinessAnalystMultisigTrait<Args = BaseMultisigCreateArgs>:
         threshold: u16,
         time_lock: u32,
     ) -> Result<Transaction, Self::Error>;
+    /// Like `instruction_create_multisig`, but the creator only joins with `Initiate`: it proposes
+    /// transactions while voting and executing is left to `members`.
+    fn instruction_create_governed_multisig(
+        &self,
+        members: &[Member],
+        threshold: u16,
+        time_lock: u32,
+    ) -> Instruction;
+    async fn transaction_create_governed_multisig(
+        &self,
+        members: &[Member],
+        threshold: u16,
+        time_lock: u32,
+    ) -> Result<Transaction, Self::Error>;
 
     async fn instructions_add_member(
         &self,
@@ -55,6 +91,12 @@ pub trait BusinessAnalystMultisigTrait<Args = BaseMultisigCreateArgs>:
         receiver: Pubkey,
         lamports: u64,
     ) -> Result<Instruction, Self::Error>;
+    async fn instruction_vault_transaction_create(
+        &self,
+        creator: Pubkey,
+        instructions: &[Instruction],
+        memo: Option<String>,
+    ) -> Result<Instruction, Self::Error>;
     async fn instruction_proposal_create(
         &self,
         creator: Pubkey,
@@ -63,17 +105,69 @@ pub trait BusinessAnalystMultisigTrait<Args = BaseMultisigCreateArgs>:
         &self,
         executer: Pubkey,
     ) -> Result<Instruction, Self::Error>;
+    async fn instruction_config_transaction_execute_at(
+        &self,
+        executer: Pubkey,
+        transaction_index: u64,
+    ) -> Result<Instruction, Self::Error>;
     async fn instruction_vault_transaction_execute(
         &self,
         sender: Pubkey,
         receiver: Pubkey,
         lamports: u64,
     ) -> Result<Instruction, Self::Error>;
+    /// Executes the vault transaction at `transaction_index`, whatever instructions it was created with.
+    async fn instruction_vault_transaction_execute_at(
+        &self,
+        executer: Pubkey,
+        transaction_index: u64,
+    ) -> Result<Instruction, Self::Error>;
+    /// Executes the config or vault transaction at `transaction_index`, whichever kind it is.
+    async fn instruction_transaction_execute_at(
+        &self,
+        executer: Pubkey,
+        transaction_index: u64,
+    ) -> Result<Instruction, Self::Error>;
     async fn instruction_change_threshold(
         &self,
         changer: Pubkey,
         new_threshold: u16,
     ) -> Result<Instruction, Self::Error>;
+    /// Reads back the instructions a vault transaction runs when executed.
+    async fn get_vault_transaction_instructions(
+        &self,
+        transaction_index: u64,
+    ) -> Result<Vec<Instruction>, Self::Error>;
+
+    /// Creates the transaction for `action` together with its proposal and sends it.
+    /// Creation is serialized per multisig, and a transaction index taken by someone else
+    /// in the meantime is retried with the next one. Returns the index that was used.
+    async fn submit_transaction_create(
+        &self,
+        creator: &Keypair,
+        action: TransactionCreateAction,
+    ) -> Result<(u64, Signature), Self::Error>;
+
+    /// Reads back the action a transaction of this multisig was created for.
+    async fn get_transaction_create_action(
+        &self,
+        transaction_index: u64,
+    ) -> Result<TransactionCreateAction, Self::Error>;
+
+    /// Submits the action of the stale transaction at `transaction_index` again, under a new index.
+    async fn recreate_stale_transaction(
+        &self,
+        creator: &Keypair,
+        transaction_index: u64,
+    ) -> Result<(u64, Signature), Self::Error> {
+        if !self.is_stale(transaction_index).await? {
+            return Err(Self::Error::ProposalIsNotStale);
+        }
+
+        let action = self.get_transaction_create_action(transaction_index).await?;
+
+        self.submit_transaction_create(creator, action).await
+    }
 
     async fn transaction_add_member(
         &self,
@@ -148,6 +242,20 @@ pub trait BusinessAnalystMultisigTrait<Args = BaseMultisigCreateArgs>:
             .await?)
     }
 
+    async fn transaction_vault_transaction_execute_at(
+        &self,
+        executer: Pubkey,
+        transaction_index: u64,
+    ) -> Result<Transaction, Self::Error> {
+        let ix = self
+            .instruction_vault_transaction_execute_at(executer, transaction_index)
+            .await?;
+
+        Ok(self
+            .get_transaction_from_instructions(executer, &[ix])
+            .await?)
+    }
+
     async fn transaction_change_threshold(
         &self,
         changer: Pubkey,
@@ -163,6 +271,245 @@ pub trait BusinessAnalystMultisigTrait<Args = BaseMultisigCreateArgs>:
     }
 }
 
+impl BaseMultisig {
+    /// Builds the instruction that creates `action` at `transaction_index`.
+    fn instruction_transaction_create_at(
+        &self,
+        creator: Pubkey,
+        action: &TransactionCreateAction,
+        transaction_index: u64,
+    ) -> Result<Instruction, BaseMultisigError> {
+        match action {
+            TransactionCreateAction::AddMember { new_member } => Ok(self.config_transaction_create_at(
+                creator,
+                vec![ConfigAction::AddMember { new_member: new_member.clone() }],
+                format!("Add {} as member to multisig {}", new_member.key, self.multisig_pda),
+                transaction_index,
+            )),
+            TransactionCreateAction::RemoveMember { old_member } => Ok(self.config_transaction_create_at(
+                creator,
+                vec![ConfigAction::RemoveMember { old_member: *old_member }],
+                format!("Remove {} member from multisig {}", old_member, self.multisig_pda),
+                transaction_index,
+            )),
+            TransactionCreateAction::ChangeThreshold { new_threshold } => Ok(self.config_transaction_create_at(
+                creator,
+                vec![ConfigAction::ChangeThreshold { new_threshold: *new_threshold }],
+                format!("Changing threshold to {} on multisig {}", new_threshold, self.multisig_pda),
+                transaction_index,
+            )),
+            TransactionCreateAction::TransferFromVault { receiver, lamports } => self.vault_transaction_create_at(
+                creator,
+                &[system_instruction::transfer(&self.vault_pda, receiver, *lamports)],
+                Some(format!("Sending {lamports} lamports from {} to {}", self.vault_pda, receiver)),
+                transaction_index,
+            ),
+            TransactionCreateAction::VaultInstructions { instructions, memo } => {
+                self.vault_transaction_create_at(creator, instructions, memo.clone(), transaction_index)
+            }
+        }
+    }
+
+    fn config_transaction_create_at(
+        &self,
+        creator: Pubkey,
+        actions: Vec<ConfigAction>,
+        memo: String,
+        transaction_index: u64,
+    ) -> Instruction {
+        let program_id: Pubkey = self.program_id;
+        let (transaction_pda, _) =
+            get_transaction_pda(&self.multisig_pda, transaction_index, Some(&program_id));
+
+        config_transaction_create(
+            ConfigTransactionCreateAccounts {
+                multisig: self.multisig_pda,
+                transaction: transaction_pda,
+                creator,
+                rent_payer: creator,
+                system_program: system_program::ID,
+            },
+            ConfigTransactionCreateArgs {
+                memo: Some(memo),
+                actions,
+            },
+            Some(program_id),
+        )
+    }
+
+    fn vault_transaction_create_at(
+        &self,
+        creator: Pubkey,
+        instructions: &[Instruction],
+        memo: Option<String>,
+        transaction_index: u64,
+    ) -> Result<Instruction, BaseMultisigError> {
+        let program_id: Pubkey = self.program_id;
+        let (transaction_pda, _) =
+            get_transaction_pda(&self.multisig_pda, transaction_index, Some(&program_id));
+        let vault_index = 0;
+
+        let message = match TransactionMessage::try_compile(&self.vault_pda, instructions, &[]) {
+            Ok(message) => message,
+            Err(_) => return Err(BaseMultisigError::FailedToBuildVaultTransactionCreateInstruction),
+        };
+
+        Ok(vault_transaction_create(
+            VaultTransactionCreateAccounts {
+                multisig: self.multisig_pda,
+                transaction: transaction_pda,
+                creator,
+                rent_payer: creator,
+                system_program: system_program::id(),
+            },
+            vault_index,
+            0,
+            &message,
+            memo,
+            Some(program_id),
+        ))
+    }
+
+    fn instruction_proposal_create_at(&self, creator: Pubkey, transaction_index: u64) -> Instruction {
+        let program_id: Pubkey = self.program_id;
+        let (proposal_pda, _) =
+            get_proposal_pda(&self.multisig_pda, transaction_index, Some(&program_id));
+
+        proposal_create(
+            client::ProposalCreateAccounts {
+                multisig: self.multisig_pda,
+                proposal: proposal_pda,
+                creator,
+                rent_payer: creator,
+                system_program: system_program::ID,
+            },
+            ProposalCreateArgs {
+                transaction_index,
+                draft: false,
+            },
+            Some(program_id),
+        )
+    }
+
+    async fn is_transaction_pda_in_use(&self, transaction_index: u64) -> Result<bool, BaseMultisigError> {
+        let program_id: Pubkey = self.program_id;
+        let (transaction_pda, _) =
+            get_transaction_pda(&self.multisig_pda, transaction_index, Some(&program_id));
+
+        match self
+            .rpc_client
+            .get_account_with_commitment(&transaction_pda, self.rpc_client.commitment())
+            .await
+        {
+            Ok(response) => Ok(response.value.is_some()),
+            Err(_) => Err(BaseMultisigError::FailedToFetchMultisigConfigAccount),
+        }
+    }
+
+    async fn fetch_transaction_account(&self, transaction_index: u64) -> Result<Account, BaseMultisigError> {
+        let program_id: Pubkey = self.program_id;
+        let (transaction_pda, _) =
+            get_transaction_pda(&self.multisig_pda, transaction_index, Some(&program_id));
+
+        match self
+            .rpc_client
+            .get_account_with_commitment(&transaction_pda, self.rpc_client.commitment())
+            .await
+        {
+            Ok(response) => match response.value {
+                Some(account) => Ok(account),
+                None => Err(BaseMultisigError::FailedToFetchTransactionAccount),
+            },
+            Err(_) => Err(BaseMultisigError::FailedToFetchTransactionAccount),
+        }
+    }
+
+    /// Rebuilds the instructions of a stored vault transaction message.
+    /// Transactions using address lookup tables are not supported.
+    fn decompile_vault_transaction(transaction: &VaultTransaction) -> Result<Vec<Instruction>, BaseMultisigError> {
+        let message = &transaction.message;
+        if !message.address_table_lookups.is_empty() {
+            return Err(BaseMultisigError::UnsupportedTransactionAction);
+        }
+
+        let account_key = |index: u8| {
+            message
+                .account_keys
+                .get(index as usize)
+                .copied()
+                .ok_or(BaseMultisigError::UnsupportedTransactionAction)
+        };
+
+        let mut instructions = Vec::with_capacity(message.instructions.len());
+        for compiled in &message.instructions {
+            let mut accounts = Vec::with_capacity(compiled.account_indexes.len());
+            for index in &compiled.account_indexes {
+                accounts.push(AccountMeta {
+                    pubkey: account_key(*index)?,
+                    is_signer: message.is_signer_index(*index as usize),
+                    is_writable: message.is_static_writable_index(*index as usize),
+                });
+            }
+
+            instructions.push(Instruction {
+                program_id: account_key(compiled.program_id_index)?,
+                accounts,
+                data: compiled.data.clone(),
+            });
+        }
+
+        Ok(instructions)
+    }
+
+    fn multisig_create_instruction(&self, members: Vec<Member>, threshold: u16, time_lock: u32) -> Instruction {
+        multisig_create_v2(
+            MultisigCreateAccountsV2 {
+                program_config: self.program_config_pda,
+                treasury: self.treasury,
+                multisig: self.multisig_pda,
+                create_key: self.multisig_create_keypair.pubkey(),
+                creator: self.creator,
+                system_program: system_program::ID,
+            },
+            MultisigCreateArgsV2 {
+                members,
+                threshold,
+                time_lock,
+                config_authority: None,
+                rent_collector: None,
+                memo: Some("Deploy my own Squad".to_string()),
+            },
+            Some(self.program_id),
+        )
+    }
+
+    /// Whether `transaction` failed to send with `error` for good, so it can not land anymore.
+    /// An unconfirmed one may still land until its blockhash expires.
+    async fn is_transaction_dropped(
+        &self,
+        error: BaseMultisigError,
+        transaction: &Transaction,
+    ) -> Result<bool, BaseMultisigError> {
+        match error {
+            BaseMultisigError::TransactionFailed | BaseMultisigError::FailedToSendTransaction => return Ok(true),
+            BaseMultisigError::TransactionNotConfirmed => {}
+            _ => return Ok(false),
+        }
+
+        // Checked before the status, it may have landed until the blockhash expired
+        match self.rpc_client.is_blockhash_valid(&transaction.message.recent_blockhash).await {
+            Ok(true) => return Ok(false),
+            Ok(false) => {}
+            Err(_) => return Err(BaseMultisigError::ErrorOnGettingLatestBlockHash),
+        }
+
+        match self.rpc_client.get_signature_status(&transaction.signatures[0]).await {
+            Ok(status) => Ok(status.is_none()),
+            Err(_) => Err(BaseMultisigError::TransactionNotConfirmed),
+        }
+    }
+}
+
 #[async_trait]
 impl BusinessAnalystMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
     async fn transaction_create_multisig(
@@ -198,25 +545,38 @@ impl BusinessAnalystMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
             members.push(creator);
         }
 
-        multisig_create_v2(
-            MultisigCreateAccountsV2 {
-                program_config: self.program_config_pda,
-                treasury: self.treasury,
-                multisig: self.multisig_pda,
-                create_key: self.multisig_create_keypair.pubkey(),
-                creator: self.creator,
-                system_program: system_program::ID,
-            },
-            MultisigCreateArgsV2 {
-                members,
-                threshold,
-                time_lock,
-                config_authority: None,
-                rent_collector: None,
-                memo: Some("Deploy my own Squad".to_string()),
-            },
-            Some(squads_multisig_program::ID),
-        )
+        self.multisig_create_instruction(members, threshold, time_lock)
+    }
+
+    fn instruction_create_governed_multisig(
+        &self,
+        members: &[Member],
+        threshold: u16,
+        time_lock: u32,
+    ) -> Instruction {
+        let mut members: Vec<Member> = members.to_vec();
+        match members.iter_mut().find(|member| member.key == self.creator) {
+            Some(creator) => creator.permissions.mask |= Permission::Initiate as u8,
+            None => members.push(Member {
+                key: self.creator,
+                permissions: Permissions::from_vec(&[Permission::Initiate]),
+            }),
+        }
+
+        self.multisig_create_instruction(members, threshold, time_lock)
+    }
+
+    async fn transaction_create_governed_multisig(
+        &self,
+        members: &[Member],
+        threshold: u16,
+        time_lock: u32,
+    ) -> Result<Transaction, Self::Error> {
+        let instruction = self.instruction_create_governed_multisig(members, threshold, time_lock);
+
+        Ok(self
+            .get_transaction_from_instructions(self.creator, &[instruction])
+            .await?)
     }
 
     async fn instructions_add_member(
@@ -224,33 +584,10 @@ impl BusinessAnalystMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
         adder: Pubkey,
         new_member: Member,
     ) -> Result<Instruction, Self::Error> {
-        let program_id: Pubkey = squads_multisig_program::ID;
         let transaction_index = self.get_multisig_transaction_index().await? + 1;
-        let (transaction_pda, _) =
-            get_transaction_pda(&self.multisig_pda, transaction_index, Some(&program_id));
+        let action = TransactionCreateAction::AddMember { new_member };
 
-        let add_member_ix = config_transaction_create(
-            ConfigTransactionCreateAccounts {
-                multisig: self.multisig_pda,
-                transaction: transaction_pda,
-                creator: adder,
-                rent_payer: adder,
-                system_program: system_program::ID,
-            },
-            ConfigTransactionCreateArgs {
-                memo: Some(format!(
-                    "Add {} as member to multisig {}",
-                    new_member.key.to_string(),
-                    self.multisig_pda
-                )),
-                actions: vec![ConfigAction::AddMember {
-                    new_member: new_member,
-                }],
-            },
-            Some(program_id),
-        );
-
-        Ok(add_member_ix)
+        self.instruction_transaction_create_at(adder, &action, transaction_index)
     }
 
     async fn instructions_remove_member(
@@ -258,33 +595,10 @@ impl BusinessAnalystMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
         remover: Pubkey,
         old_member_pubkey: Pubkey,
     ) -> Result<Instruction, Self::Error> {
-        let program_id: Pubkey = squads_multisig_program::ID;
         let transaction_index = self.get_multisig_transaction_index().await? + 1;
-        let (transaction_pda, _) =
-            get_transaction_pda(&self.multisig_pda, transaction_index, Some(&program_id));
-
-        let remove_member_ix = config_transaction_create(
-            ConfigTransactionCreateAccounts {
-                multisig: self.multisig_pda,
-                transaction: transaction_pda,
-                creator: remover,
-                rent_payer: remover,
-                system_program: system_program::ID,
-            },
-            ConfigTransactionCreateArgs {
-                memo: Some(format!(
-                    "Remove {} member from multisig {}",
-                    old_member_pubkey.to_string(),
-                    self.multisig_pda
-                )),
-                actions: vec![ConfigAction::RemoveMember {
-                    old_member: old_member_pubkey,
-                }],
-            },
-            Some(program_id),
-        );
+        let action = TransactionCreateAction::RemoveMember { old_member: old_member_pubkey };
 
-        Ok(remove_member_ix)
+        self.instruction_transaction_create_at(remover, &action, transaction_index)
     }
 
     async fn instruction_transfer_from_vault(
@@ -293,83 +607,58 @@ impl BusinessAnalystMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
         receiver: Pubkey,
         lamports: u64,
     ) -> Result<Instruction, Self::Error> {
-        let program_id: Pubkey = squads_multisig_program::ID;
         let transaction_index = self.get_multisig_transaction_index().await? + 1;
-        let (transaction_pda, _) =
-            get_transaction_pda(&self.multisig_pda, transaction_index, Some(&program_id));
-        let vault_index = 0;
+        let action = TransactionCreateAction::TransferFromVault { receiver, lamports };
 
-        let message = TransactionMessage::try_compile(
-            &self.vault_pda,
-            &[system_instruction::transfer(
-                &self.vault_pda,
-                &receiver,
-                lamports,
-            )],
-            &[],
-        )
-        .unwrap();
+        self.instruction_transaction_create_at(sender, &action, transaction_index)
+    }
 
-        let transfer_from_vault_ix = vault_transaction_create(
-            VaultTransactionCreateAccounts {
-                multisig: self.multisig_pda,
-                transaction: transaction_pda,
-                creator: sender,
-                rent_payer: sender,
-                system_program: system_program::id(),
-            },
-            vault_index,
-            0,
-            &message,
-            Some(format!(
-                "Sending {lamports} lamports from {} to {}",
-                self.vault_pda.to_string(),
-                receiver.to_string()
-            )),
-            Some(program_id),
-        );
+    async fn instruction_vault_transaction_create(
+        &self,
+        creator: Pubkey,
+        instructions: &[Instruction],
+        memo: Option<String>,
+    ) -> Result<Instruction, Self::Error> {
+        let transaction_index = self.get_multisig_transaction_index().await? + 1;
 
-        Ok(transfer_from_vault_ix)
+        self.vault_transaction_create_at(creator, instructions, memo, transaction_index)
     }
 
     async fn instruction_proposal_create(
         &self,
         creator: Pubkey,
     ) -> Result<Instruction, Self::Error> {
-        let program_id: Pubkey = squads_multisig_program::ID;
         let transaction_index = self.get_multisig_transaction_index().await?;
-        let (proposal_pda, _) =
-            get_proposal_pda(&self.multisig_pda, transaction_index, Some(&program_id));
-
-        let proposal_create_ix = proposal_create(
-            client::ProposalCreateAccounts {
-                multisig: self.multisig_pda,
-                proposal: proposal_pda,
-                creator: creator,
-                rent_payer: creator,
-                system_program: system_program::ID,
-            },
-            ProposalCreateArgs {
-                transaction_index,
-                draft: false,
-            },
-            Some(program_id),
-        );
 
-        Ok(proposal_create_ix)
+        Ok(self.instruction_proposal_create_at(creator, transaction_index))
     }
 
     async fn instruction_config_transaction_execute(
         &self,
         executer: Pubkey,
     ) -> Result<Instruction, Self::Error> {
-        let program_id: Pubkey = squads_multisig_program::ID;
         let transaction_index = self.get_multisig_transaction_index().await?;
+
+        self.instruction_config_transaction_execute_at(executer, transaction_index)
+            .await
+    }
+
+    async fn instruction_config_transaction_execute_at(
+        &self,
+        executer: Pubkey,
+        transaction_index: u64,
+    ) -> Result<Instruction, Self::Error> {
+        let program_id: Pubkey = self.program_id;
         let (proposal_pda, _) =
             get_proposal_pda(&self.multisig_pda, transaction_index, Some(&program_id));
         let (transaction_pda, _) =
             get_transaction_pda(&self.multisig_pda, transaction_index, Some(&program_id));
 
+        // Unlike vault transactions, stale config transactions can not be executed even if approved.
+        if self.is_stale(transaction_index).await? {
+            return Err(Self::Error::ProposalIsStale);
+        }
+
         let config_transaction_execute_ix = config_transaction_execute(
             ConfigTransactionExecuteAccounts {
                 multisig: self.multisig_pda,
@@ -392,7 +681,7 @@ impl BusinessAnalystMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
         receiver: Pubkey,
         lamports: u64,
     ) -> Result<Instruction, Self::Error> {
-        let program_id: Pubkey = squads_multisig_program::ID;
+        let program_id: Pubkey = self.program_id;
         let transaction_index = self.get_multisig_transaction_index().await?;
         let (transaction_pda, _) =
             get_transaction_pda(&self.multisig_pda, transaction_index, Some(&program_id));
@@ -431,35 +720,175 @@ impl BusinessAnalystMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
         }
     }
 
-    async fn instruction_change_threshold(
+    async fn instruction_vault_transaction_execute_at(
         &self,
-        changer: Pubkey,
-        new_threshold: u16,
+        executer: Pubkey,
+        transaction_index: u64,
     ) -> Result<Instruction, Self::Error> {
-        let program_id: Pubkey = squads_multisig_program::ID;
-        let transaction_index = self.get_multisig_transaction_index().await? + 1;
+        let program_id: Pubkey = self.program_id;
         let (transaction_pda, _) =
             get_transaction_pda(&self.multisig_pda, transaction_index, Some(&program_id));
+        let (proposal_pda, _) =
+            get_proposal_pda(&self.multisig_pda, transaction_index, Some(&program_id));
+        let vault_index = 0;
 
-        let change_threshold_ix = config_transaction_create(
-            ConfigTransactionCreateAccounts {
+        let instructions = self.get_vault_transaction_instructions(transaction_index).await?;
+        // Compiling is deterministic, so the accounts come out in the order the stored message has them.
+        let message = match TransactionMessage::try_compile(&self.vault_pda, &instructions, &[]) {
+            Ok(message) => message,
+            Err(_) => return Err(Self::Error::FailedToBuildVaultTransactionExecuteInstruction),
+        };
+
+        match vault_transaction_execute(
+            VaultTransactionExecuteAccounts {
                 multisig: self.multisig_pda,
                 transaction: transaction_pda,
-                creator: changer,
-                rent_payer: changer,
-                system_program: system_program::ID,
-            },
-            ConfigTransactionCreateArgs {
-                memo: Some(format!(
-                    "Changing threshold to {} on multisig {}",
-                    new_threshold, self.multisig_pda
-                )),
-                actions: vec![ConfigAction::ChangeThreshold { new_threshold }],
+                member: executer,
+                proposal: proposal_pda,
             },
+            vault_index,
+            0,
+            &message,
+            &[],
             Some(program_id),
-        );
+        ) {
+            Ok(ix) => Ok(ix),
+            Err(_) => Err(Self::Error::FailedToBuildVaultTransactionExecuteInstruction),
+        }
+    }
+
+    async fn instruction_transaction_execute_at(
+        &self,
+        executer: Pubkey,
+        transaction_index: u64,
+    ) -> Result<Instruction, Self::Error> {
+        let transaction_account = self.fetch_transaction_account(transaction_index).await?;
+
+        if ConfigTransaction::try_deserialize(&mut transaction_account.data.as_slice()).is_ok() {
+            return self
+                .instruction_config_transaction_execute_at(executer, transaction_index)
+                .await;
+        }
 
-        Ok(change_threshold_ix)
+        self.instruction_vault_transaction_execute_at(executer, transaction_index)
+            .await
+    }
+
+    async fn get_vault_transaction_instructions(
+        &self,
+        transaction_index: u64,
+    ) -> Result<Vec<Instruction>, Self::Error> {
+        let transaction_account = self.fetch_transaction_account(transaction_index).await?;
+
+        match VaultTransaction::try_deserialize(&mut transaction_account.data.as_slice()) {
+            Ok(transaction) => BaseMultisig::decompile_vault_transaction(&transaction),
+            Err(_) => Err(Self::Error::FailedToDeserializeTransactionData),
+        }
+    }
+
+    async fn instruction_change_threshold(
+        &self,
+        changer: Pubkey,
+        new_threshold: u16,
+    ) -> Result<Instruction, Self::Error> {
+        let transaction_index = self.get_multisig_transaction_index().await? + 1;
+        let action = TransactionCreateAction::ChangeThreshold { new_threshold };
+
+        self.instruction_transaction_create_at(changer, &action, transaction_index)
+    }
+
+    async fn submit_transaction_create(
+        &self,
+        creator: &Keypair,
+        action: TransactionCreateAction,
+    ) -> Result<(u64, Signature), Self::Error> {
+        let lock = transaction_create_lock(&self.multisig_pda);
+        let _guard = lock.lock().await;
+
+        for _ in 0..MAX_TRANSACTION_CREATE_ATTEMPTS {
+            let transaction_index = self.refresh().await?.multisig.transaction_index + 1;
+
+            let create_ix = self.instruction_transaction_create_at(creator.pubkey(), &action, transaction_index)?;
+            let proposal_ix = self.instruction_proposal_create_at(creator.pubkey(), transaction_index);
+
+            let mut tx = self
+                .get_transaction_from_instructions(creator.pubkey(), &[create_ix, proposal_ix])
+                .await?;
+            let recent_blockhash = tx.message.recent_blockhash;
+            if tx.try_sign(&[creator], recent_blockhash).is_err() {
+                return Err(Self::Error::FailedToSignTransaction);
+            }
+
+            let error = match self.send_and_confirm_transaction(&tx).await {
+                Ok(signature) => return Ok((transaction_index, signature)),
+                Err(error) => error,
+            };
+
+            // Only once ours can not land is a taken index someone else's, before that it may be our own
+            if self.is_transaction_dropped(error, &tx).await?
+                && self.is_transaction_pda_in_use(transaction_index).await?
+            {
+                continue;
+            }
+            return Err(error);
+        }
+
+        Err(Self::Error::TransactionIndexConflict)
+    }
+
+    async fn get_transaction_create_action(
+        &self,
+        transaction_index: u64,
+    ) -> Result<TransactionCreateAction, Self::Error> {
+        let transaction_account = self.fetch_transaction_account(transaction_index).await?;
+
+        if let Ok(config_transaction) =
+            ConfigTransaction::try_deserialize(&mut transaction_account.data.as_slice())
+        {
+            return match config_transaction.actions.as_slice() {
+                [ConfigAction::AddMember { new_member }] => Ok(TransactionCreateAction::AddMember {
+                    new_member: new_member.clone(),
+                }),
+                [ConfigAction::RemoveMember { old_member }] => {
+                    Ok(TransactionCreateAction::RemoveMember {
+                        old_member: *old_member,
+                    })
+                }
+                [ConfigAction::ChangeThreshold { new_threshold }] => {
+                    Ok(TransactionCreateAction::ChangeThreshold {
+                        new_threshold: *new_threshold,
+                    })
+                }
+                _ => Err(Self::Error::UnsupportedTransactionAction),
+            };
+        }
+
+        let vault_transaction =
+            match VaultTransaction::try_deserialize(&mut transaction_account.data.as_slice()) {
+                Ok(transaction) => transaction,
+                Err(_) => return Err(Self::Error::UnsupportedTransactionAction),
+            };
+        let instructions = BaseMultisig::decompile_vault_transaction(&vault_transaction)?;
+
+        // A single system transfer out of the vault is what `instruction_transfer_from_vault` builds.
+        if let [instruction] = instructions.as_slice() {
+            if instruction.program_id == system_program::ID {
+                if let (Ok(SystemInstruction::Transfer { lamports }), Some(receiver)) = (
+                    limited_deserialize::<SystemInstruction>(&instruction.data),
+                    instruction.accounts.get(1),
+                ) {
+                    return Ok(TransactionCreateAction::TransferFromVault {
+                        receiver: receiver.pubkey,
+                        lamports,
+                    });
+                }
+            }
+        }
+
+        Ok(TransactionCreateAction::VaultInstructions {
+            instructions,
+            memo: None,
+        })
     }
 }
 
@@ -467,45 +896,47 @@ impl BusinessAnalystMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
 mod tests {
     use std::{error::Error, sync::Arc};
 
-    use crate::dao_module::error::BaseMultisigError;
-
     use super::*;
-    use solana_client::nonblocking::rpc_client::RpcClient;
+    use crate::multisig_utils::base_multisig::{ProposalSummary, DEFAULT_MULTISIG_CACHE_MAX_AGE, MAX_PROPOSALS_PER_PAGE};
+    use crate::rpc_utils::{in_process_bank::InProcessBank, rpc_backend::RpcBackend};
     use solana_sdk::{
+        hash::Hash,
         native_token::LAMPORTS_PER_SOL,
         signature::{Keypair, Signature},
     };
+    use squads_multisig::squads_multisig_program;
     use tokio;
 
     async fn transaction_sign_and_send(
         tx: &mut Transaction,
         keys: &[&Keypair],
-        multisig_rpc: &RpcClient,
+        multisig: &BaseMultisig,
     ) -> Result<(), Box<dyn Error>> {
-        let recent_blockhash = multisig_rpc.get_latest_blockhash().await.unwrap();
+        let recent_blockhash = multisig.get_rpc_client().get_latest_blockhash().await.unwrap();
         let _ = tx.try_sign(keys, recent_blockhash);
-        let _ = multisig_rpc.send_and_confirm_transaction(tx).await?;
+        let _ = multisig.send_and_confirm_transaction(tx).await?;
         Ok(())
     }
 
     async fn get_base_multisig(
-        rpc_client: &RpcClient,
+        rpc_client: &Arc<dyn RpcBackend>,
         multisig_create_keypair: &Keypair,
         creator: &Keypair,
         members: &[Member],
     ) -> Result<BaseMultisig, BaseMultisigError> {
         let result = BaseMultisig::new(BaseMultisigCreateArgs {
-            rpc_client: RpcClient::new(rpc_client.url()),
+            rpc_client: rpc_client.clone(),
+            program_id: squads_multisig_program::ID,
             multisig_create_keypair: multisig_create_keypair.insecure_clone(),
-            creator: creator.pubkey().clone(),
+            creator: creator.pubkey(),
+            cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE,
         })
         .await?;
 
         let mut tx = result.transaction_create_multisig(members, 1, 0).await?;
-        let _ =
-            transaction_sign_and_send(&mut tx, &[&creator, &multisig_create_keypair], rpc_client)
-                .await
-                .unwrap();
+        transaction_sign_and_send(&mut tx, &[creator, multisig_create_keypair], &result)
+            .await
+            .unwrap();
 
         Ok(result)
     }
@@ -519,12 +950,12 @@ mod tests {
     }
 
     pub async fn airdrop(
-        rpc_client: &RpcClient,
+        rpc_client: &dyn RpcBackend,
         address: &Pubkey,
         amount: u64,
     ) -> Result<Signature, Box<dyn Error>> {
         let sig = rpc_client
-            .request_airdrop(&address, (amount * LAMPORTS_PER_SOL) as u64)
+            .request_airdrop(address, amount * LAMPORTS_PER_SOL)
             .await?;
         println!(
             "🚀Airdropping {} SOL to {} with sig {}",
@@ -541,11 +972,12 @@ mod tests {
 
     #[tokio::test]
     async fn create_multisig_no_members() -> Result<(), Box<dyn Error>> {
-        let rpc_client = RpcClient::new("http://127.0.0.1:8899".to_string());
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
         let creator: Keypair = Keypair::new();
         let create_key = Keypair::new();
 
-        let _ = airdrop(&rpc_client, &creator.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &creator.pubkey(), 1).await?;
         let base_multisig = get_base_multisig(&rpc_client, &create_key, &creator, &[])
             .await
             .unwrap();
@@ -554,13 +986,73 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn create_governed_multisig() -> Result<(), Box<dyn Error>> {
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let creator: Keypair = Keypair::new();
+        let create_key = Keypair::new();
+        let _ = airdrop(rpc_client.as_ref(), &creator.pubkey(), 1).await?;
+
+        let base_multisig = BaseMultisig::new(BaseMultisigCreateArgs {
+            rpc_client: rpc_client.clone(),
+            program_id: squads_multisig_program::ID,
+            multisig_create_keypair: create_key.insecure_clone(),
+            creator: creator.pubkey(),
+            cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE,
+        })
+        .await?;
+        let investor = Member {
+            key: Keypair::new().pubkey(),
+            permissions: Permissions::from_vec(&[Permission::Vote, Permission::Execute]),
+        };
+        let mut tx = base_multisig
+            .transaction_create_governed_multisig(std::slice::from_ref(&investor), 1, 0)
+            .await?;
+        transaction_sign_and_send(&mut tx, &[&creator, &create_key], &base_multisig).await?;
+
+        let members = base_multisig.get_multisig_members().await?;
+        assert_eq!(2, members.len());
+        let creator_member = members.iter().find(|member| member.key == creator.pubkey()).unwrap();
+        assert_eq!(Permissions::from_vec(&[Permission::Initiate]), creator_member.permissions);
+
+        // A creator listed among the members keeps its permissions and can still propose
+        let create_key = Keypair::new();
+        let base_multisig = BaseMultisig::new(BaseMultisigCreateArgs {
+            rpc_client: rpc_client.clone(),
+            program_id: squads_multisig_program::ID,
+            multisig_create_keypair: create_key.insecure_clone(),
+            creator: creator.pubkey(),
+            cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE,
+        })
+        .await?;
+        let voting_creator = Member {
+            key: creator.pubkey(),
+            permissions: Permissions::from_vec(&[Permission::Vote]),
+        };
+        let mut tx = base_multisig
+            .transaction_create_governed_multisig(&[voting_creator, investor], 1, 0)
+            .await?;
+        transaction_sign_and_send(&mut tx, &[&creator, &create_key], &base_multisig).await?;
+
+        let members = base_multisig.get_multisig_members().await?;
+        assert_eq!(2, members.len());
+        let creator_member = members.iter().find(|member| member.key == creator.pubkey()).unwrap();
+        assert_eq!(
+            Permissions::from_vec(&[Permission::Initiate, Permission::Vote]),
+            creator_member.permissions
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn add_member() -> Result<(), Box<dyn Error>> {
-        let rpc_client = RpcClient::new("http://127.0.0.1:8899".to_string());
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
         let creator: Keypair = Keypair::new();
         let create_key = Keypair::new();
 
-        let _ = airdrop(&rpc_client, &creator.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &creator.pubkey(), 1).await?;
         let base_multisig = get_base_multisig(&rpc_client, &create_key, &creator, &[])
             .await
             .unwrap();
@@ -575,7 +1067,7 @@ mod tests {
             .transaction_add_member(creator.pubkey(), new_member)
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -583,7 +1075,7 @@ mod tests {
             .transaction_proposal_create(creator.pubkey())
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -591,7 +1083,7 @@ mod tests {
             .transaction_proposal_approve(creator.pubkey())
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -599,7 +1091,7 @@ mod tests {
             .transaction_config_transaction_execute(creator.pubkey())
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -609,7 +1101,8 @@ mod tests {
 
     #[tokio::test]
     async fn change_threshold() -> Result<(), Box<dyn Error>> {
-        let rpc_client = RpcClient::new("http://127.0.0.1:8899".to_string());
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
         let creator: Keypair = Keypair::new();
         let create_key = Keypair::new();
 
@@ -619,7 +1112,7 @@ mod tests {
             permissions: Permissions::from_vec(&[Permission::Vote]),
         };
 
-        let _ = airdrop(&rpc_client, &creator.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &creator.pubkey(), 1).await?;
         let base_multisig = get_base_multisig(&rpc_client, &create_key, &creator, &[new_member])
             .await
             .unwrap();
@@ -629,7 +1122,7 @@ mod tests {
             .transaction_change_threshold(creator.pubkey(), 2)
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -637,7 +1130,7 @@ mod tests {
             .transaction_proposal_create(creator.pubkey())
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -645,7 +1138,7 @@ mod tests {
             .transaction_proposal_approve(creator.pubkey())
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -653,7 +1146,7 @@ mod tests {
             .transaction_config_transaction_execute(creator.pubkey())
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -663,7 +1156,8 @@ mod tests {
 
     #[tokio::test]
     async fn vault_transaction_member_approve() -> Result<(), Box<dyn Error>> {
-        let rpc_client = RpcClient::new("http://127.0.0.1:8899".to_string());
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
         let creator: Keypair = Keypair::new();
         let create_key = Keypair::new();
 
@@ -673,15 +1167,15 @@ mod tests {
             permissions: Permissions::from_vec(&[Permission::Vote]),
         };
 
-        let _ = airdrop(&rpc_client, &creator.pubkey(), 1).await?;
-        let _ = airdrop(&rpc_client, &member.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &creator.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &member.pubkey(), 1).await?;
 
         let base_multisig = get_base_multisig(&rpc_client, &create_key, &creator, &[new_member])
             .await
             .unwrap();
         let multisig = get_ba_multisig(&base_multisig).await.unwrap();
 
-        let _ = airdrop(&rpc_client, &multisig.get_vault_pda(), 3).await?;
+        let _ = airdrop(rpc_client.as_ref(), &multisig.get_vault_pda(), 3).await?;
 
         let mut tx = multisig
             .transaction_transfer_from_vault(
@@ -691,7 +1185,7 @@ mod tests {
             )
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -699,7 +1193,7 @@ mod tests {
             .transaction_proposal_create(creator.pubkey())
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -707,7 +1201,7 @@ mod tests {
             .transaction_proposal_approve(member.pubkey())
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&member], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&member], &base_multisig)
             .await
             .unwrap();
 
@@ -719,7 +1213,7 @@ mod tests {
             )
             .await
             .unwrap();
-        transaction_sign_and_send(&mut tx, &[&creator], &rpc_client)
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
             .await
             .unwrap();
 
@@ -729,8 +1223,219 @@ mod tests {
                 .get_balance(&multisig.get_vault_pda())
                 .await
                 .unwrap(),
-            1 * LAMPORTS_PER_SOL
+            LAMPORTS_PER_SOL
         );
         Ok(())
     }
+
+    #[tokio::test]
+    async fn concurrent_transaction_create() -> Result<(), Box<dyn Error>> {
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let creator: Keypair = Keypair::new();
+        let create_key = Keypair::new();
+
+        let _ = airdrop(rpc_client.as_ref(), &creator.pubkey(), 1).await?;
+        let base_multisig = get_base_multisig(&rpc_client, &create_key, &creator, &[])
+            .await
+            .unwrap();
+        let same_multisig = BaseMultisig::new(base_multisig.get_multisig_create_args()).await?;
+
+        let member = Keypair::new();
+        let new_member = Member {
+            key: member.pubkey(),
+            permissions: Permissions::from_vec(&[Permission::Vote]),
+        };
+        let (first, second) = tokio::join!(
+            base_multisig.submit_transaction_create(
+                &creator,
+                TransactionCreateAction::AddMember { new_member }
+            ),
+            same_multisig.submit_transaction_create(
+                &creator,
+                TransactionCreateAction::ChangeThreshold { new_threshold: 1 }
+            )
+        );
+
+        let mut indexes = vec![first.unwrap().0, second.unwrap().0];
+        indexes.sort();
+        assert_eq!(vec![1, 2], indexes);
+        assert_eq!(2, base_multisig.get_multisig_transaction_index().await.unwrap());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn transaction_create_moves_past_an_index_taken_by_another_writer() -> Result<(), Box<dyn Error>> {
+        let bank = Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let rpc_client: Arc<dyn RpcBackend> = bank.clone();
+        let creator: Keypair = Keypair::new();
+        let create_key = Keypair::new();
+
+        let _ = airdrop(rpc_client.as_ref(), &creator.pubkey(), 1).await?;
+        let base_multisig = get_base_multisig(&rpc_client, &create_key, &creator, &[])
+            .await
+            .unwrap();
+
+        // Another process takes index 1 after our refresh, right before our transaction arrives
+        let competing_action = TransactionCreateAction::ChangeThreshold { new_threshold: 1 };
+        let mut competing_tx = base_multisig
+            .get_transaction_from_instructions(
+                creator.pubkey(),
+                &[
+                    base_multisig.instruction_transaction_create_at(creator.pubkey(), &competing_action, 1)?,
+                    base_multisig.instruction_proposal_create_at(creator.pubkey(), 1),
+                ],
+            )
+            .await?;
+        competing_tx.sign(&[&creator], competing_tx.message.recent_blockhash);
+        bank.front_run(competing_tx);
+
+        let new_member = Member {
+            key: Pubkey::new_unique(),
+            permissions: Permissions::from_vec(&[Permission::Vote]),
+        };
+        let (transaction_index, _) = base_multisig
+            .submit_transaction_create(&creator, TransactionCreateAction::AddMember { new_member })
+            .await?;
+
+        assert_eq!(2, transaction_index);
+        assert!(matches!(
+            base_multisig.get_transaction_create_action(1).await?,
+            TransactionCreateAction::ChangeThreshold { new_threshold: 1 }
+        ));
+        assert!(matches!(
+            base_multisig.get_transaction_create_action(2).await?,
+            TransactionCreateAction::AddMember { .. }
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_transaction_is_dropped_once_its_blockhash_expired() -> Result<(), Box<dyn Error>> {
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let creator: Keypair = Keypair::new();
+        let base_multisig = BaseMultisig::new(BaseMultisigCreateArgs {
+            rpc_client: rpc_client.clone(),
+            program_id: squads_multisig_program::ID,
+            multisig_create_keypair: Keypair::new(),
+            creator: creator.pubkey(),
+            cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE,
+        })
+        .await?;
+
+        let transfer = system_instruction::transfer(&creator.pubkey(), &Pubkey::new_unique(), 1_000_000);
+        let mut tx = base_multisig.get_transaction_from_instructions(creator.pubkey(), &[transfer]).await?;
+        tx.sign(&[&creator], tx.message.recent_blockhash);
+        assert!(!base_multisig.is_transaction_dropped(BaseMultisigError::TransactionNotConfirmed, &tx).await?);
+        assert!(base_multisig.is_transaction_dropped(BaseMultisigError::TransactionFailed, &tx).await?);
+
+        tx.sign(&[&creator], Hash::new_unique());
+        assert!(base_multisig.is_transaction_dropped(BaseMultisigError::TransactionNotConfirmed, &tx).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn proposals_are_listed_in_pages() -> Result<(), Box<dyn Error>> {
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let creator: Keypair = Keypair::new();
+        let create_key = Keypair::new();
+
+        let _ = airdrop(rpc_client.as_ref(), &creator.pubkey(), 1).await?;
+        let base_multisig = get_base_multisig(&rpc_client, &create_key, &creator, &[])
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            base_multisig
+                .submit_transaction_create(&creator, TransactionCreateAction::ChangeThreshold { new_threshold: 1 })
+                .await?;
+        }
+
+        let indexes = |proposals: Vec<ProposalSummary>| -> Vec<u64> {
+            proposals.iter().map(|proposal| proposal.transaction_index).collect()
+        };
+        assert_eq!(vec![2, 3], indexes(base_multisig.list_proposals(None, 2).await?));
+        assert_eq!(vec![1], indexes(base_multisig.list_proposals(Some(2), 2).await?));
+        assert_eq!(vec![1, 2, 3], indexes(base_multisig.list_proposals(Some(10), MAX_PROPOSALS_PER_PAGE).await?));
+        assert!(base_multisig.list_proposals(Some(1), 2).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stale_proposal_is_flagged_and_recreated() -> Result<(), Box<dyn Error>> {
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let creator: Keypair = Keypair::new();
+        let create_key = Keypair::new();
+        let receiver = Pubkey::new_unique();
+
+        let _ = airdrop(rpc_client.as_ref(), &creator.pubkey(), 1).await?;
+        let base_multisig = get_base_multisig(&rpc_client, &create_key, &creator, &[])
+            .await
+            .unwrap();
+        let multisig = get_ba_multisig(&base_multisig).await.unwrap();
+
+        let (transfer_index, _) = multisig
+            .submit_transaction_create(
+                &creator,
+                TransactionCreateAction::TransferFromVault { receiver, lamports: 1_000 },
+            )
+            .await
+            .unwrap();
+        let (threshold_index, _) = multisig
+            .submit_transaction_create(
+                &creator,
+                TransactionCreateAction::ChangeThreshold { new_threshold: 1 },
+            )
+            .await
+            .unwrap();
+
+        let mut tx = multisig
+            .transaction_proposal_approve(creator.pubkey())
+            .await
+            .unwrap();
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
+            .await
+            .unwrap();
+        let mut tx = multisig
+            .transaction_config_transaction_execute(creator.pubkey())
+            .await
+            .unwrap();
+        transaction_sign_and_send(&mut tx, &[&creator], &base_multisig)
+            .await
+            .unwrap();
+
+        assert_eq!(threshold_index, multisig.get_stale_transaction_index().await.unwrap());
+        assert!(multisig.is_stale(transfer_index).await.unwrap());
+        assert!(matches!(
+            multisig.transaction_proposal_approve(creator.pubkey()).await,
+            Err(BaseMultisigError::ProposalIsStale)
+        ));
+
+        let proposals = multisig.list_proposals(None, MAX_PROPOSALS_PER_PAGE).await.unwrap();
+        let flagged: Vec<u64> = proposals
+            .iter()
+            .filter(|proposal| proposal.is_stale)
+            .map(|proposal| proposal.transaction_index)
+            .collect();
+        assert_eq!(2, proposals.len());
+        assert_eq!(vec![transfer_index], flagged);
+
+        let (recreated_index, _) = multisig
+            .recreate_stale_transaction(&creator, transfer_index)
+            .await
+            .unwrap();
+        assert_eq!(threshold_index + 1, recreated_index);
+        assert!(!multisig.is_stale(recreated_index).await.unwrap());
+        assert!(matches!(
+            multisig.get_transaction_create_action(recreated_index).await.unwrap(),
+            TransactionCreateAction::TransferFromVault { receiver: r, lamports: 1_000 } if r == receiver
+        ));
+        assert!(matches!(
+            multisig.recreate_stale_transaction(&creator, recreated_index).await,
+            Err(BaseMultisigError::ProposalIsNotStale)
+        ));
+        Ok(())
+    }
 }
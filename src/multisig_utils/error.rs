@@ -1,7 +1,7 @@
 use thiserror::Error;
 use solana_program::program_error::ProgramError;
 
-#[derive(Error, Debug, Copy, Clone)]
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum BaseMultisigError {
     #[error("Failed to fetch program config account")]
     FailedToFetchProgramConfigAccount,
@@ -20,7 +20,32 @@ pub enum BaseMultisigError {
     #[error("Error on getting latest block hash")]
     ErrorOnGettingLatestBlockHash,
     #[error("Proposal status is not Approved")]
-    ProposalStatusIsNotApproved
+    ProposalStatusIsNotApproved,
+    /// The node did not take the transaction, so nothing landed
+    #[error("Failed to send transaction")]
+    FailedToSendTransaction,
+    #[error("Failed to sign transaction")]
+    FailedToSignTransaction,
+    #[error("Transaction index is still taken after all retries")]
+    TransactionIndexConflict,
+    #[error("Proposal is stale, the multisig config changed after it was created")]
+    ProposalIsStale,
+    #[error("Proposal is not stale")]
+    ProposalIsNotStale,
+    #[error("Failed to fetch transaction account")]
+    FailedToFetchTransactionAccount,
+    #[error("Transaction action can not be re-created")]
+    UnsupportedTransactionAction,
+    #[error("Failed to build vault_transaction_create instruction")]
+    FailedToBuildVaultTransactionCreateInstruction,
+    #[error("Failed to deserialize transaction account")]
+    FailedToDeserializeTransactionData,
+    /// Rejected in preflight or failed on chain, so nothing changed
+    #[error("Transaction failed")]
+    TransactionFailed,
+    /// Sent, but neither a confirmation nor a failure was seen; it may still land
+    #[error("Transaction was sent but its confirmation is unknown")]
+    TransactionNotConfirmed
 }
 
 impl From<BaseMultisigError> for ProgramError {
@@ -1,9 +1,34 @@
 use async_trait::async_trait;
+use solana_sdk::signature::{Keypair, Signature, Signer};
 use super::{base_multisig::{BaseMultisig, BaseMultisigCreateArgs}, base_multisig_trait::BaseMultisigTrait, error::BaseMultisigError};
 
 #[async_trait]
 pub trait InvestorMultisigTrait<Args = BaseMultisigCreateArgs> : BaseMultisigTrait<Args, Error = BaseMultisigError>{
+    /// Casts `investor`'s approval on the proposal of `transaction_index` and sends it.
+    async fn vote_approve(&self, investor: &Keypair, transaction_index: u64) -> Result<Signature, Self::Error> {
+        let ix = self.instruction_proposal_approve_at(investor.pubkey(), transaction_index).await?;
+        let mut tx = self.get_transaction_from_instructions(investor.pubkey(), &[ix]).await?;
+
+        let recent_blockhash = tx.message.recent_blockhash;
+        if tx.try_sign(&[investor], recent_blockhash).is_err() {
+            return Err(Self::Error::FailedToSignTransaction);
+        }
+
+        self.send_and_confirm_transaction(&tx).await
+    }
+
+    /// Casts `investor`'s rejection on the proposal of `transaction_index` and sends it.
+    async fn vote_reject(&self, investor: &Keypair, transaction_index: u64) -> Result<Signature, Self::Error> {
+        let ix = self.instruction_proposal_reject_at(investor.pubkey(), transaction_index).await?;
+        let mut tx = self.get_transaction_from_instructions(investor.pubkey(), &[ix]).await?;
 
+        let recent_blockhash = tx.message.recent_blockhash;
+        if tx.try_sign(&[investor], recent_blockhash).is_err() {
+            return Err(Self::Error::FailedToSignTransaction);
+        }
+
+        self.send_and_confirm_transaction(&tx).await
+    }
 }
 
 #[async_trait]
@@ -16,31 +41,34 @@ impl InvestorMultisigTrait<BaseMultisigCreateArgs> for BaseMultisig {
 mod tests {
     use std::{error::Error, sync::Arc};
 
-    use crate::dao_module::{business_analyst_multisig_trait::BusinessAnalystMultisigTrait, error::BaseMultisigError};
+    use crate::multisig_utils::business_analyst_multisig_trait::{BusinessAnalystMultisigTrait, TransactionCreateAction};
 
     use super::*;
-    use solana_client::nonblocking::rpc_client::RpcClient;
-    use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::{Keypair, Signature}, signer::Signer, transaction::Transaction};
+    use crate::multisig_utils::base_multisig::DEFAULT_MULTISIG_CACHE_MAX_AGE;
+    use crate::rpc_utils::{in_process_bank::InProcessBank, rpc_backend::RpcBackend};
+    use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::{Keypair, Signature}, signer::Signer, system_instruction, transaction::Transaction};
     use squads_multisig::{squads_multisig_program, state::ProposalStatus};
     use squads_multisig_program::{Member, Permission, Permissions};
     use tokio;
 
-    async fn transaction_sign_and_send(tx: &mut Transaction, keys: &[&Keypair], multisig_rpc: &RpcClient) -> Result<(), Box<dyn Error>> {
-        let recent_blockhash = multisig_rpc.get_latest_blockhash().await.unwrap();
+    async fn transaction_sign_and_send(tx: &mut Transaction, keys: &[&Keypair], multisig: &BaseMultisig) -> Result<(), Box<dyn Error>> {
+        let recent_blockhash = multisig.get_rpc_client().get_latest_blockhash().await.unwrap();
         let _ = tx.try_sign(keys, recent_blockhash);
-        let _ = multisig_rpc.send_and_confirm_transaction(tx).await?;
+        let _ = multisig.send_and_confirm_transaction(tx).await?;
         Ok(())
     }
 
-    async fn get_base_multisig(rpc_client: &RpcClient, multisig_create_keypair: &Keypair, creator: &Keypair, members: &[Member]) -> Result<BaseMultisig, BaseMultisigError> {
+    async fn get_base_multisig(rpc_client: &Arc<dyn RpcBackend>, multisig_create_keypair: &Keypair, creator: &Keypair, members: &[Member]) -> Result<BaseMultisig, BaseMultisigError> {
         let result = BaseMultisig::new(BaseMultisigCreateArgs {
-            rpc_client: RpcClient::new(rpc_client.url()),
+            rpc_client: rpc_client.clone(),
+            program_id: squads_multisig_program::ID,
             multisig_create_keypair: multisig_create_keypair.insecure_clone(),
-            creator: creator.pubkey().clone()
+            creator: creator.pubkey(),
+            cache_max_age: DEFAULT_MULTISIG_CACHE_MAX_AGE,
         }).await?;
 
         let mut tx = result.transaction_create_multisig(members, 1, 0).await?;
-        let _ = transaction_sign_and_send(&mut tx, &[&creator, &multisig_create_keypair], rpc_client).await.unwrap();
+        transaction_sign_and_send(&mut tx, &[creator, multisig_create_keypair], &result).await.unwrap();
 
         Ok(result)
     }
@@ -57,8 +85,8 @@ mod tests {
         Ok(multisig)
     }
 
-    pub async fn airdrop(rpc_client: &RpcClient, address: &Pubkey, amount: u64) -> Result<Signature, Box<dyn Error>> {
-        let sig = rpc_client.request_airdrop(&address, (amount * LAMPORTS_PER_SOL) as u64).await?;
+    pub async fn airdrop(rpc_client: &dyn RpcBackend, address: &Pubkey, amount: u64) -> Result<Signature, Box<dyn Error>> {
+        let sig = rpc_client.request_airdrop(address, amount * LAMPORTS_PER_SOL).await?;
         println!("🚀Airdropping {} SOL to {} with sig {}",amount, address, sig );
         loop {
             let confirmed = rpc_client.confirm_transaction(&sig).await?;
@@ -71,7 +99,8 @@ mod tests {
 
     #[tokio::test]
     async fn create_multisig_with_investor() -> Result<(), Box<dyn Error>> {
-        let rpc_client = RpcClient::new("http://127.0.0.1:8899".to_string());
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
         let ba: Keypair = Keypair::new();
         let investor_key: Keypair = Keypair::new();
         let create_key = Keypair::new();
@@ -81,7 +110,7 @@ mod tests {
             permissions: Permissions::from_vec(&[Permission::Vote]),
         };
 
-        let _ = airdrop(&rpc_client, &ba.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &ba.pubkey(), 1).await?;
         let base_multisig = get_base_multisig(&rpc_client, &create_key, &ba, &[investor]).await.unwrap();
         let investor_multisig = get_investor_multisig(&base_multisig).await.unwrap();
 
@@ -92,7 +121,8 @@ mod tests {
 
     #[tokio::test]
     async fn approve_proposal() -> Result<(), Box<dyn Error>> {
-        let rpc_client = RpcClient::new("http://127.0.0.1:8899".to_string());
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
         let ba: Keypair = Keypair::new();
         let investor_key: Keypair = Keypair::new();
         let create_key = Keypair::new();
@@ -102,20 +132,20 @@ mod tests {
             permissions: Permissions::from_vec(&[Permission::Vote]),
         };
 
-        let _ = airdrop(&rpc_client, &ba.pubkey(), 1).await?;
-        let _ = airdrop(&rpc_client, &investor_key.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &ba.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &investor_key.pubkey(), 1).await?;
         let base_multisig = get_base_multisig(&rpc_client, &create_key, &ba, &[investor]).await.unwrap();
         let ba_multisig = get_ba_multisig(&base_multisig).await.unwrap();
         let investor_multisig = get_investor_multisig(&base_multisig).await.unwrap();
 
         let mut tx = ba_multisig.transaction_change_threshold(ba.pubkey(), 2).await.unwrap();
-        transaction_sign_and_send(&mut tx, &[&ba], &rpc_client).await.unwrap();
+        transaction_sign_and_send(&mut tx, &[&ba], &base_multisig).await.unwrap();
 
         let mut tx = ba_multisig.transaction_proposal_create(ba.pubkey()).await.unwrap();
-        transaction_sign_and_send(&mut tx, &[&ba], &rpc_client).await.unwrap();
+        transaction_sign_and_send(&mut tx, &[&ba], &base_multisig).await.unwrap();
 
         let mut tx = investor_multisig.transaction_proposal_approve(investor_key.pubkey()).await.unwrap();
-        transaction_sign_and_send(&mut tx, &[&investor_key], &rpc_client).await.unwrap();
+        transaction_sign_and_send(&mut tx, &[&investor_key], &base_multisig).await.unwrap();
 
         let proposal_status = investor_multisig.get_current_proposal_status().await.unwrap();
 
@@ -128,7 +158,8 @@ mod tests {
 
     #[tokio::test]
     async fn proposal_cancel() -> Result<(), Box<dyn Error>> {
-        let rpc_client = RpcClient::new("http://127.0.0.1:8899".to_string());
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
         let ba: Keypair = Keypair::new();
         let investor_key: Keypair = Keypair::new();
         let create_key = Keypair::new();
@@ -138,24 +169,24 @@ mod tests {
             permissions: Permissions::from_vec(&[Permission::Vote]),
         };
 
-        let _ = airdrop(&rpc_client, &ba.pubkey(), 1).await?;
-        let _ = airdrop(&rpc_client, &investor_key.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &ba.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &investor_key.pubkey(), 1).await?;
 
         let base_multisig = get_base_multisig(&rpc_client, &create_key, &ba, &[investor]).await.unwrap();
         let ba_multisig = get_ba_multisig(&base_multisig).await.unwrap();
         let investor_multisig = get_investor_multisig(&base_multisig).await.unwrap();
 
         let mut tx = ba_multisig.transaction_change_threshold(ba.pubkey(), 2).await.unwrap();
-        transaction_sign_and_send(&mut tx, &[&ba], &rpc_client).await.unwrap();
+        transaction_sign_and_send(&mut tx, &[&ba], &base_multisig).await.unwrap();
 
         let mut tx = ba_multisig.transaction_proposal_create(ba.pubkey()).await.unwrap();
-        transaction_sign_and_send(&mut tx, &[&ba], &rpc_client).await.unwrap();
+        transaction_sign_and_send(&mut tx, &[&ba], &base_multisig).await.unwrap();
 
         let mut tx = investor_multisig.transaction_proposal_approve(ba.pubkey()).await.unwrap();
-        transaction_sign_and_send(&mut tx, &[&ba], &rpc_client).await.unwrap();
+        transaction_sign_and_send(&mut tx, &[&ba], &base_multisig).await.unwrap();
 
         let mut tx = investor_multisig.transaction_proposal_cancel(investor_key.pubkey()).await.unwrap();
-        transaction_sign_and_send(&mut tx, &[&investor_key], &rpc_client).await.unwrap();
+        transaction_sign_and_send(&mut tx, &[&investor_key], &base_multisig).await.unwrap();
 
         let proposal_status = investor_multisig.get_current_proposal_status().await.unwrap();
 
@@ -164,4 +195,86 @@ mod tests {
             _ => panic!("Proposal status not Cancelled")
         }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn vote_on_vault_instructions() -> Result<(), Box<dyn Error>> {
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let ba: Keypair = Keypair::new();
+        let investor_key: Keypair = Keypair::new();
+        let create_key = Keypair::new();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let investor = Member {
+            key: investor_key.pubkey(),
+            permissions: Permissions::from_vec(&[Permission::Vote]),
+        };
+
+        let _ = airdrop(rpc_client.as_ref(), &ba.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &investor_key.pubkey(), 1).await?;
+        let base_multisig = get_base_multisig(&rpc_client, &create_key, &ba, &[investor]).await.unwrap();
+        let ba_multisig = get_ba_multisig(&base_multisig).await.unwrap();
+        let investor_multisig = get_investor_multisig(&base_multisig).await.unwrap();
+        let vault = ba_multisig.get_vault_pda();
+        let _ = airdrop(rpc_client.as_ref(), &vault, 2).await?;
+
+        let instructions = vec![
+            system_instruction::transfer(&vault, &first, LAMPORTS_PER_SOL / 2),
+            system_instruction::transfer(&vault, &second, LAMPORTS_PER_SOL / 4),
+        ];
+        let (transaction_index, _) = ba_multisig.submit_transaction_create(
+            &ba,
+            TransactionCreateAction::VaultInstructions { instructions: instructions.clone(), memo: None }
+        ).await.unwrap();
+
+        assert_eq!(instructions, ba_multisig.get_vault_transaction_instructions(transaction_index).await.unwrap());
+
+        investor_multisig.vote_approve(&investor_key, transaction_index).await.unwrap();
+
+        let mut tx = ba_multisig.transaction_vault_transaction_execute_at(ba.pubkey(), transaction_index).await.unwrap();
+        transaction_sign_and_send(&mut tx, &[&ba], &base_multisig).await.unwrap();
+
+        assert_eq!(LAMPORTS_PER_SOL / 2, rpc_client.get_balance(&first).await.unwrap());
+        assert_eq!(LAMPORTS_PER_SOL / 4, rpc_client.get_balance(&second).await.unwrap());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn vote_reject() -> Result<(), Box<dyn Error>> {
+        let rpc_client: Arc<dyn RpcBackend> =
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let ba: Keypair = Keypair::new();
+        let investor_key: Keypair = Keypair::new();
+        let create_key = Keypair::new();
+
+        let investor = Member {
+            key: investor_key.pubkey(),
+            permissions: Permissions::from_vec(&[Permission::Vote]),
+        };
+
+        let _ = airdrop(rpc_client.as_ref(), &ba.pubkey(), 1).await?;
+        let _ = airdrop(rpc_client.as_ref(), &investor_key.pubkey(), 1).await?;
+        let base_multisig = get_base_multisig(&rpc_client, &create_key, &ba, &[investor]).await.unwrap();
+        let ba_multisig = get_ba_multisig(&base_multisig).await.unwrap();
+        let investor_multisig = get_investor_multisig(&base_multisig).await.unwrap();
+
+        let vault = ba_multisig.get_vault_pda();
+        let (transaction_index, _) = ba_multisig.submit_transaction_create(
+            &ba,
+            TransactionCreateAction::VaultInstructions {
+                instructions: vec![system_instruction::transfer(&vault, &Pubkey::new_unique(), 1)],
+                memo: None
+            }
+        ).await.unwrap();
+
+        // With a threshold of one out of two voters, one rejection still leaves the proposal passable
+        investor_multisig.vote_reject(&investor_key, transaction_index).await.unwrap();
+        let proposal = investor_multisig.get_proposal(transaction_index).await.unwrap().unwrap();
+        assert!(matches!(proposal.status, ProposalStatus::Active { .. }));
+
+        investor_multisig.vote_reject(&ba, transaction_index).await.unwrap();
+        let proposal = investor_multisig.get_proposal(transaction_index).await.unwrap().unwrap();
+        assert!(matches!(proposal.status, ProposalStatus::Rejected { .. }));
+        Ok(())
+    }
+}
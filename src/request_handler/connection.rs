@@ -0,0 +1,441 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use amqprs::callbacks::{ChannelCallback, ConnectionCallback};
+use amqprs::channel::{BasicCancelArguments, BasicConsumeArguments, Channel};
+use amqprs::connection::{Connection, OpenConnectionArguments};
+use amqprs::{Ack, BasicProperties, Cancel, Close, CloseChannel, Nack, Return};
+use async_trait::async_trait;
+use tokio::sync::{watch, Notify};
+
+use crate::request_handler::consumers::registry::CommandRegistry;
+use crate::request_handler::consumers::RabbitMQConsumer;
+use crate::request_handler::delivery::{DeliveryTopology, RetryPolicy};
+use crate::request_handler::idempotency::IdempotencyStore;
+use crate::request_handler::in_flight::InFlight;
+use crate::request_handler::topology::ExchangeTopology;
+
+/// How long shutdown waits for the deliveries being handled, including their Solana confirmations.
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Everything about the broker: where to connect, the topology to declare and how to retry, reconnect
+/// and shut down.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokerConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub consumer_tag: String,
+    pub topology: ExchangeTopology,
+    pub retry_policy: RetryPolicy,
+    pub reconnect_policy: ReconnectPolicy,
+    /// How long shutdown waits for the requests being handled
+    pub drain_timeout: Duration,
+}
+
+impl Default for BrokerConfig {
+    fn default() -> Self {
+        BrokerConfig {
+            host: "localhost".to_string(),
+            port: 5672,
+            username: "guest".to_string(),
+            password: "guest".to_string(),
+            consumer_tag: "request.consumer".to_string(),
+            topology: ExchangeTopology::default(),
+            retry_policy: RetryPolicy::default(),
+            reconnect_policy: ReconnectPolicy::default(),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        }
+    }
+}
+
+fn env_or(name: &str, default: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+impl BrokerConfig {
+    /// Reads `RABBIT_HOST`, `RABBIT_PORT`, `RABBIT_USER`, `RABBIT_PASSWORD`, `QUEUE_NAME`, `REQUEST_EXCHANGE`,
+    /// `REQUEST_BINDINGS` (comma separated), `RESPONSE_EXCHANGE`, `RESPONSE_QUEUE`, `EVENT_EXCHANGE` and
+    /// `SHUTDOWN_TIMEOUT_SECS`, falling back to the defaults for the ones that are unset.
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = BrokerConfig::default();
+
+        let port = match std::env::var("RABBIT_PORT") {
+            Ok(port) => match port.parse() {
+                Ok(port) => port,
+                Err(..) => return Err(format!("RABBIT_PORT is not a port: {}", port)),
+            },
+            Err(..) => defaults.port,
+        };
+        let drain_timeout = match std::env::var("SHUTDOWN_TIMEOUT_SECS") {
+            Ok(secs) => match secs.parse() {
+                Ok(secs) => Duration::from_secs(secs),
+                Err(..) => return Err(format!("SHUTDOWN_TIMEOUT_SECS is not a number of seconds: {}", secs)),
+            },
+            Err(..) => defaults.drain_timeout,
+        };
+        let bindings = match std::env::var("REQUEST_BINDINGS") {
+            Ok(bindings) => bindings
+                .split(',')
+                .map(str::trim)
+                .filter(|binding| !binding.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(..) => defaults.topology.bindings,
+        };
+
+        return Ok(BrokerConfig {
+            host: env_or("RABBIT_HOST", &defaults.host),
+            port,
+            username: env_or("RABBIT_USER", &defaults.username),
+            password: env_or("RABBIT_PASSWORD", &defaults.password),
+            consumer_tag: defaults.consumer_tag,
+            topology: ExchangeTopology {
+                request_exchange: env_or("REQUEST_EXCHANGE", &defaults.topology.request_exchange),
+                request_queue: env_or("QUEUE_NAME", &defaults.topology.request_queue),
+                bindings,
+                response_exchange: env_or("RESPONSE_EXCHANGE", &defaults.topology.response_exchange),
+                response_queue: std::env::var("RESPONSE_QUEUE").ok(),
+                event_exchange: env_or("EVENT_EXCHANGE", &defaults.topology.event_exchange),
+            },
+            retry_policy: defaults.retry_policy,
+            reconnect_policy: defaults.reconnect_policy,
+            drain_timeout,
+        });
+    }
+}
+
+/// Where the supervised connection stands, see `ConnectionManager::state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connecting,
+    /// The topology is declared and the consumer receives deliveries
+    Connected,
+    /// The connection was lost or could not be opened, attempt `attempt` starts after `delay`
+    Reconnecting { attempt: u32, delay: Duration },
+    /// Shut down, no further attempts are made
+    Closed,
+}
+
+/// How long to wait before reconnecting: `initial_delay * 2^(attempt - 1)`, at most `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.initial_delay.saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)));
+        delay.min(self.max_delay)
+    }
+}
+
+/// Tells the manager the broker closed the connection, the channel or the consumer.
+struct SupervisedCallback {
+    lost: Arc<Notify>,
+}
+
+#[async_trait]
+impl ConnectionCallback for SupervisedCallback {
+    async fn close(&mut self, _connection: &Connection, close: Close) -> Result<(), amqprs::error::Error> {
+        eprintln!("[{:?} RABBITMQ ERROR] Broker closed the connection: {}", chrono::Utc::now(), close);
+        self.lost.notify_one();
+        Ok(())
+    }
+
+    async fn blocked(&mut self, _connection: &Connection, reason: String) {
+        eprintln!("[{:?} RABBITMQ ERROR] Connection blocked: {}", chrono::Utc::now(), reason);
+    }
+
+    async fn unblocked(&mut self, _connection: &Connection) {
+        println!("[{:?} RABBITMQ INFO] Connection unblocked", chrono::Utc::now());
+    }
+}
+
+#[async_trait]
+impl ChannelCallback for SupervisedCallback {
+    async fn close(&mut self, _channel: &Channel, close: CloseChannel) -> Result<(), amqprs::error::Error> {
+        eprintln!("[{:?} RABBITMQ ERROR] Broker closed the channel: {}", chrono::Utc::now(), close);
+        self.lost.notify_one();
+        Ok(())
+    }
+
+    async fn cancel(&mut self, _channel: &Channel, cancel: Cancel) -> Result<(), amqprs::error::Error> {
+        eprintln!(
+            "[{:?} RABBITMQ ERROR] Broker cancelled consumer {}",
+            chrono::Utc::now(),
+            cancel.consumer_tag()
+        );
+        self.lost.notify_one();
+        Ok(())
+    }
+
+    async fn flow(&mut self, _channel: &Channel, active: bool) -> Result<bool, amqprs::error::Error> {
+        println!("[{:?} RABBITMQ INFO] Channel flow active: {}", chrono::Utc::now(), active);
+        Ok(active)
+    }
+
+    async fn publish_ack(&mut self, _channel: &Channel, _ack: Ack) {}
+
+    async fn publish_nack(&mut self, _channel: &Channel, _nack: Nack) {}
+
+    async fn publish_return(&mut self, _channel: &Channel, ret: Return, _properties: BasicProperties, _content: Vec<u8>) {
+        eprintln!("[{:?} RABBITMQ ERROR] Reply was returned: {}", chrono::Utc::now(), ret);
+    }
+}
+
+/// An open connection with the consumer running on its channel.
+struct Session {
+    connection: Connection,
+    channel: Channel,
+    consumer_tag: String,
+}
+
+impl Session {
+    /// Stops new deliveries; the ones the broker already sent are requeued when the channel closes.
+    async fn cancel_consumer(&self) {
+        let args = BasicCancelArguments::new(&self.consumer_tag);
+        if let Err(err) = self.channel.basic_cancel(args).await {
+            eprintln!("[{:?} RABBITMQ ERROR] Could not cancel consumer: {}", chrono::Utc::now(), err);
+        }
+    }
+
+    async fn close(self) {
+        // Either may already be gone with the broker, there is nothing left to do then
+        let _ = self.channel.close().await;
+        let _ = self.connection.close().await;
+    }
+}
+
+/// Keeps a consumer on the request queue: connects, declares the topology and consumes, and starts over
+/// with backoff whenever the broker goes away, until shutdown.
+pub struct ConnectionManager {
+    config: BrokerConfig,
+    registry: Arc<CommandRegistry>,
+    idempotency: Arc<dyn IdempotencyStore>,
+    state: watch::Sender<ConnectionState>,
+    in_flight: InFlight,
+}
+
+impl ConnectionManager {
+    pub fn new(
+        config: BrokerConfig,
+        registry: Arc<CommandRegistry>,
+        idempotency: Arc<dyn IdempotencyStore>,
+    ) -> Self {
+        let (state, _) = watch::channel(ConnectionState::Connecting);
+
+        ConnectionManager {
+            config,
+            registry,
+            idempotency,
+            state,
+            in_flight: InFlight::new(),
+        }
+    }
+
+    /// Follows the connection state, e.g. for health checks.
+    pub fn state(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    fn set_state(&self, state: ConnectionState) {
+        self.state.send_replace(state);
+    }
+
+    async fn connect(&self, lost: Arc<Notify>) -> Result<Session, String> {
+        let connection_arguments = OpenConnectionArguments::new(
+            &self.config.host,
+            self.config.port,
+            &self.config.username,
+            &self.config.password,
+        );
+        let connection = match Connection::open(&connection_arguments).await {
+            Ok(connection) => connection,
+            Err(err) => return Err(format!("Connection to RabbitMQ failed: {}", err)),
+        };
+        if let Err(err) = connection.register_callback(SupervisedCallback { lost: lost.clone() }).await {
+            return Err(format!("Could not register connection callback: {}", err));
+        }
+
+        let channel = match connection.open_channel(None).await {
+            Ok(channel) => channel,
+            Err(err) => return Err(format!("Could not open channel: {}", err)),
+        };
+        if let Err(err) = channel.register_callback(SupervisedCallback { lost }).await {
+            return Err(format!("Could not register channel callback: {}", err));
+        }
+
+        let queue_name = self.config.topology.declare(&channel).await?;
+
+        // declare the retry queues and the dead-letter exchange of the queue
+        let delivery = DeliveryTopology::new(&queue_name, self.config.retry_policy);
+        delivery.declare(&channel).await?;
+
+        // deliveries are acknowledged by the consumer once handled
+        let args = BasicConsumeArguments::new(&queue_name, &self.config.consumer_tag)
+            .manual_ack(true)
+            .finish();
+        let consumer = RabbitMQConsumer::new(
+            self.registry.clone(),
+            self.idempotency.clone(),
+            self.config.topology.clone(),
+            delivery,
+            self.in_flight.clone(),
+        );
+        let consumer_tag = match channel.basic_consume(consumer, args).await {
+            Ok(consumer_tag) => consumer_tag,
+            Err(err) => return Err(format!("Could not consume {}: {}", queue_name, err)),
+        };
+
+        return Ok(Session { connection, channel, consumer_tag });
+    }
+
+    /// Refuses new deliveries and waits up to the drain timeout for the ones being handled.
+    async fn drain(&self) {
+        self.in_flight.close();
+        let in_flight = self.in_flight.count();
+        if in_flight == 0 {
+            return;
+        }
+
+        println!(
+            "[{:?} RABBITMQ INFO] Waiting up to {:?} for {} request(s) being handled",
+            chrono::Utc::now(),
+            self.config.drain_timeout,
+            in_flight
+        );
+        if !self.in_flight.wait_idle(self.config.drain_timeout).await {
+            eprintln!(
+                "[{:?} RABBITMQ ERROR] {} request(s) still running after {:?}, they are redelivered once the channel closes",
+                chrono::Utc::now(),
+                self.in_flight.count(),
+                self.config.drain_timeout
+            );
+        }
+    }
+
+    /// Runs until `shutdown` completes, then stops consuming, lets the requests being handled finish
+    /// and closes the connection.
+    pub async fn run(&self, shutdown: impl Future<Output = ()>) -> Result<(), String> {
+        tokio::pin!(shutdown);
+        let mut attempt = 0;
+
+        loop {
+            let lost = Arc::new(Notify::new());
+            let session = tokio::select! {
+                session = self.connect(lost.clone()) => session,
+                _ = &mut shutdown => break,
+            };
+
+            match session {
+                Ok(session) => {
+                    attempt = 0;
+                    self.set_state(ConnectionState::Connected);
+                    println!(
+                        "[{:?} RABBITMQ INFO] Consuming {} on {}:{}",
+                        chrono::Utc::now(),
+                        self.config.topology.request_queue,
+                        self.config.host,
+                        self.config.port
+                    );
+
+                    tokio::select! {
+                        _ = session.connection.listen_network_io_failure() => {
+                            eprintln!("[{:?} RABBITMQ ERROR] Connection lost", chrono::Utc::now());
+                        }
+                        _ = lost.notified() => {}
+                        _ = &mut shutdown => {
+                            session.cancel_consumer().await;
+                            self.drain().await;
+                            session.close().await;
+                            break;
+                        }
+                    }
+                    session.close().await;
+                }
+                Err(err) => eprintln!("[{:?} RABBITMQ ERROR] {}", chrono::Utc::now(), err),
+            }
+
+            attempt += 1;
+            let delay = self.config.reconnect_policy.delay(attempt);
+            self.set_state(ConnectionState::Reconnecting { attempt, delay });
+            println!(
+                "[{:?} RABBITMQ INFO] Reconnecting in {:?} (attempt {})",
+                chrono::Utc::now(),
+                delay,
+                attempt
+            );
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = &mut shutdown => break,
+            }
+        }
+
+        // Handlers of a lost connection may still be sending transactions and recording their outcome
+        self.drain().await;
+        self.set_state(ConnectionState::Closed);
+        println!("[{:?} RABBITMQ INFO] Connection closed", chrono::Utc::now());
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_handler::idempotency::memory::MemoryIdempotencyStore;
+    use crate::request_handler::idempotency::DEFAULT_LEASE;
+
+    #[test]
+    fn reconnect_delay_backs_off_up_to_the_maximum() {
+        let policy = ReconnectPolicy::default();
+        let delays: Vec<u64> = (1..=7).map(|attempt| policy.delay(attempt).as_secs()).collect();
+
+        assert_eq!(vec![1, 2, 4, 8, 16, 30, 30], delays);
+        assert_eq!(Duration::from_secs(30), policy.delay(u32::MAX));
+    }
+
+    #[tokio::test]
+    async fn retries_until_shutdown() {
+        // Nothing listens on port 1, so every attempt fails right away
+        let config = BrokerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 1,
+            reconnect_policy: ReconnectPolicy {
+                initial_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(5),
+            },
+            ..BrokerConfig::default()
+        };
+        let manager = ConnectionManager::new(
+            config,
+            Arc::new(CommandRegistry::new()),
+            Arc::new(MemoryIdempotencyStore::new(DEFAULT_LEASE)),
+        );
+
+        let mut state = manager.state();
+        let shutdown = async move {
+            state
+                .wait_for(|state| matches!(state, ConnectionState::Reconnecting { attempt: 3, .. }))
+                .await
+                .unwrap();
+        };
+        manager.run(shutdown).await.unwrap();
+
+        assert_eq!(ConnectionState::Closed, *manager.state().borrow());
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::dao_module::services::dao_service::{DaoService, CreatedProposal};
+use crate::multisig_utils::business_analyst_multisig_trait::TransactionCreateAction;
+use crate::request_handler::consumers::common::{parse_pubkey, MemberSchema, PERMISSION_NAMES};
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct AddMemberSchema {
+    multisig_pda: String,
+    member: MemberSchema,
+}
+
+pub struct AddMember {
+    service: Arc<DaoService>,
+}
+
+impl AddMember {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        AddMember { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for AddMember {
+    type Request = AddMemberSchema;
+    type Response = CreatedProposal;
+
+    const NAME: &'static str = "add_member";
+    const ROUTING_KEY: &'static str = "dao.add_member";
+
+    fn schema() -> Value {
+        json!({
+            "multisig_pda": "string",
+            "member": { "key": "string", "permissions": [PERMISSION_NAMES] }
+        })
+    }
+
+    fn validate(&self, request: &AddMemberSchema) -> Vec<Violation> {
+        let mut validator = Validator::new();
+        validator.pubkey("$.multisig_pda", &request.multisig_pda);
+        request.member.validate("$.member", &mut validator);
+
+        validator.finish()
+    }
+
+    async fn handle(&self, request: AddMemberSchema) -> Result<CreatedProposal, CommandError> {
+        let multisig_pda = parse_pubkey("multisig_pda", &request.multisig_pda)?;
+        let new_member = request.member.to_member()?;
+
+        Ok(self
+            .service
+            .propose(&multisig_pda, TransactionCreateAction::AddMember { new_member })
+            .await?)
+    }
+}
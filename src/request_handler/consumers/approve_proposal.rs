@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::dao_module::services::dao_service::{DaoService, ProposalUpdate};
+use crate::request_handler::consumers::common::{parse_pubkey, parse_transaction};
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct ApproveProposalSchema {
+    multisig_pda: String,
+    transaction_index: u64,
+    approver: String,
+    transaction: String,
+}
+
+pub struct ApproveProposal {
+    service: Arc<DaoService>,
+}
+
+impl ApproveProposal {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        ApproveProposal { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for ApproveProposal {
+    type Request = ApproveProposalSchema;
+    type Response = ProposalUpdate;
+
+    const NAME: &'static str = "approve_proposal";
+    const ROUTING_KEY: &'static str = "proposal.approve";
+
+    fn schema() -> Value {
+        json!({
+            "multisig_pda": "string",
+            "transaction_index": "unsigned integer",
+            "approver": "string",
+            "transaction": "string"
+        })
+    }
+
+    fn validate(&self, request: &ApproveProposalSchema) -> Vec<Violation> {
+        Validator::new()
+            .pubkey("$.multisig_pda", &request.multisig_pda)
+            .positive("$.transaction_index", request.transaction_index)
+            .pubkey("$.approver", &request.approver)
+            .transaction("$.transaction", &request.transaction)
+            .finish()
+    }
+
+    async fn handle(&self, request: ApproveProposalSchema) -> Result<ProposalUpdate, CommandError> {
+        let multisig_pda = parse_pubkey("multisig_pda", &request.multisig_pda)?;
+        let approver = parse_pubkey("approver", &request.approver)?;
+        let transaction = parse_transaction("transaction", &request.transaction)?;
+
+        Ok(self
+            .service
+            .approve_proposal(&multisig_pda, request.transaction_index, &approver, &transaction)
+            .await?)
+    }
+}
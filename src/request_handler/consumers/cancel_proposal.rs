@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::dao_module::services::dao_service::{DaoService, ProposalUpdate};
+use crate::request_handler::consumers::common::{parse_pubkey, parse_transaction};
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct CancelProposalSchema {
+    multisig_pda: String,
+    transaction_index: u64,
+    canceler: String,
+    transaction: String,
+}
+
+pub struct CancelProposal {
+    service: Arc<DaoService>,
+}
+
+impl CancelProposal {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        CancelProposal { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for CancelProposal {
+    type Request = CancelProposalSchema;
+    type Response = ProposalUpdate;
+
+    const NAME: &'static str = "cancel_proposal";
+    const ROUTING_KEY: &'static str = "proposal.cancel";
+
+    fn schema() -> Value {
+        json!({
+            "multisig_pda": "string",
+            "transaction_index": "unsigned integer",
+            "canceler": "string",
+            "transaction": "string"
+        })
+    }
+
+    fn validate(&self, request: &CancelProposalSchema) -> Vec<Violation> {
+        Validator::new()
+            .pubkey("$.multisig_pda", &request.multisig_pda)
+            .positive("$.transaction_index", request.transaction_index)
+            .pubkey("$.canceler", &request.canceler)
+            .transaction("$.transaction", &request.transaction)
+            .finish()
+    }
+
+    async fn handle(&self, request: CancelProposalSchema) -> Result<ProposalUpdate, CommandError> {
+        let multisig_pda = parse_pubkey("multisig_pda", &request.multisig_pda)?;
+        let canceler = parse_pubkey("canceler", &request.canceler)?;
+        let transaction = parse_transaction("transaction", &request.transaction)?;
+
+        Ok(self
+            .service
+            .cancel_proposal(&multisig_pda, request.transaction_index, &canceler, &transaction)
+            .await?)
+    }
+}
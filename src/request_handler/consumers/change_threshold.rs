@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::dao_module::services::dao_service::{DaoService, CreatedProposal};
+use crate::multisig_utils::business_analyst_multisig_trait::TransactionCreateAction;
+use crate::request_handler::consumers::common::parse_pubkey;
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct ChangeThresholdSchema {
+    multisig_pda: String,
+    threshold: u16,
+}
+
+pub struct ChangeThreshold {
+    service: Arc<DaoService>,
+}
+
+impl ChangeThreshold {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        ChangeThreshold { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for ChangeThreshold {
+    type Request = ChangeThresholdSchema;
+    type Response = CreatedProposal;
+
+    const NAME: &'static str = "change_threshold";
+    const ROUTING_KEY: &'static str = "dao.change_threshold";
+
+    fn schema() -> Value {
+        json!({ "multisig_pda": "string", "threshold": "unsigned integer" })
+    }
+
+    fn validate(&self, request: &ChangeThresholdSchema) -> Vec<Violation> {
+        // The upper bound depends on the members at execution time, Squads enforces it
+        Validator::new()
+            .pubkey("$.multisig_pda", &request.multisig_pda)
+            .range("$.threshold", request.threshold as u64, 1, u16::MAX as u64)
+            .finish()
+    }
+
+    async fn handle(&self, request: ChangeThresholdSchema) -> Result<CreatedProposal, CommandError> {
+        let multisig_pda = parse_pubkey("multisig_pda", &request.multisig_pda)?;
+
+        Ok(self
+            .service
+            .propose(
+                &multisig_pda,
+                TransactionCreateAction::ChangeThreshold { new_threshold: request.threshold },
+            )
+            .await?)
+    }
+}
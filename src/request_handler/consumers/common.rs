@@ -0,0 +1,188 @@
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, sanitize::Sanitize, transaction::Transaction};
+use squads_multisig::state::{Member, Permission, Permissions, ProposalStatus};
+
+use crate::dao_module::services::dao_service::{CreatedDao, CreatedProposal, DaoInfo, ProposalUpdate};
+use crate::multisig_utils::base_multisig::ProposalSummary;
+use crate::request_handler::response::{CommandError, CommandOutput};
+use crate::request_handler::validation::Validator;
+
+const PERMISSIONS: [(&str, Permission); 3] = [
+    ("initiate", Permission::Initiate),
+    ("vote", Permission::Vote),
+    ("execute", Permission::Execute),
+];
+
+/// Schema type of a permission name.
+pub const PERMISSION_NAMES: &str = "initiate | vote | execute";
+
+pub fn parse_pubkey(field: &str, value: &str) -> Result<Pubkey, CommandError> {
+    return match Pubkey::from_str(value) {
+        Ok(pubkey) => Ok(pubkey),
+        Err(..) => Err(CommandError::invalid_payload(format!("Invalid {}: {}", field, value))),
+    };
+}
+
+/// A transaction signed by a member, as bincode serialized and base64 encoded by wallets for `sendTransaction`.
+pub fn parse_transaction(field: &str, value: &str) -> Result<Transaction, CommandError> {
+    let bytes = match STANDARD.decode(value) {
+        Ok(bytes) => bytes,
+        Err(..) => return Err(CommandError::invalid_payload(format!("Invalid {}: not base64", field))),
+    };
+
+    let transaction: Transaction = match bincode::deserialize(&bytes) {
+        Ok(transaction) => transaction,
+        Err(..) => return Err(CommandError::invalid_payload(format!("Invalid {}: not a transaction", field))),
+    };
+
+    return match transaction.sanitize() {
+        Ok(()) => Ok(transaction),
+        Err(..) => Err(CommandError::invalid_payload(format!("Invalid {}: refers to accounts it does not list", field))),
+    };
+}
+
+/// A multisig member as sent over the bus, with permissions named `initiate`, `vote` and `execute`.
+#[derive(Deserialize, Debug)]
+pub struct MemberSchema {
+    key: String,
+    permissions: Vec<String>,
+}
+
+impl MemberSchema {
+    /// Checks the member at `path`; the permission names are already checked by the schema.
+    pub fn validate(&self, path: &str, validator: &mut Validator) {
+        validator.pubkey(&format!("{}.key", path), &self.key);
+        if self.permissions.is_empty() {
+            validator.push(&format!("{}.permissions", path), Some(PERMISSION_NAMES), "At least one permission is required");
+        }
+    }
+
+    pub fn has_permission(&self, name: &str) -> bool {
+        self.permissions.iter().any(|permission| permission == name)
+    }
+
+    pub fn to_member(&self) -> Result<Member, CommandError> {
+        let key = parse_pubkey("member key", &self.key)?;
+
+        let mut permissions = Vec::new();
+        for name in &self.permissions {
+            match PERMISSIONS.iter().find(|(known, _)| known == name) {
+                Some((_, permission)) => permissions.push(*permission),
+                None => return Err(CommandError::invalid_payload(format!("Unknown permission: {}", name))),
+            }
+        }
+
+        Ok(Member {
+            key,
+            permissions: Permissions::from_vec(&permissions),
+        })
+    }
+}
+
+pub fn member_json(member: &Member) -> Value {
+    let permissions: Vec<&str> = PERMISSIONS
+        .iter()
+        .filter(|(_, permission)| member.permissions.has(*permission))
+        .map(|(name, _)| *name)
+        .collect();
+
+    json!({ "key": member.key.to_string(), "permissions": permissions })
+}
+
+#[allow(deprecated)]
+pub fn status_name(status: &ProposalStatus) -> &'static str {
+    match status {
+        ProposalStatus::Draft { .. } => "Draft",
+        ProposalStatus::Active { .. } => "Active",
+        ProposalStatus::Rejected { .. } => "Rejected",
+        ProposalStatus::Approved { .. } => "Approved",
+        ProposalStatus::Executing => "Executing",
+        ProposalStatus::Executed { .. } => "Executed",
+        ProposalStatus::Cancelled { .. } => "Cancelled",
+        _ => "Unknown",
+    }
+}
+
+pub fn proposal_json(multisig_pda: &Pubkey, proposal: &ProposalSummary) -> Value {
+    json!({
+        "multisig_pda": multisig_pda.to_string(),
+        "transaction_index": proposal.transaction_index,
+        "proposal_pda": proposal.proposal_pda.to_string(),
+        "status": status_name(&proposal.status),
+        "is_stale": proposal.is_stale,
+    })
+}
+
+impl From<CreatedDao> for CommandOutput {
+    fn from(dao: CreatedDao) -> Self {
+        CommandOutput::message(format!("Multisig {} created", dao.multisig_pda))
+            .with_result(json!({
+                "multisig_pda": dao.multisig_pda.to_string(),
+                "vault_pda": dao.vault_pda.to_string(),
+                "create_key": dao.create_key.to_string(),
+            }))
+            .with_signature(dao.signature)
+    }
+}
+
+impl From<CreatedProposal> for CommandOutput {
+    fn from(proposal: CreatedProposal) -> Self {
+        CommandOutput::message(format!(
+            "Transaction #{} of multisig {} proposed",
+            proposal.transaction_index, proposal.multisig_pda
+        ))
+        .with_result(json!({
+            "multisig_pda": proposal.multisig_pda.to_string(),
+            "transaction_index": proposal.transaction_index,
+            "proposal_pda": proposal.proposal_pda.to_string(),
+        }))
+        .with_signature(proposal.signature)
+    }
+}
+
+impl From<ProposalUpdate> for CommandOutput {
+    fn from(update: ProposalUpdate) -> Self {
+        CommandOutput::message(format!(
+            "Proposal #{} of multisig {} is {}",
+            update.proposal.transaction_index,
+            update.multisig_pda,
+            status_name(&update.proposal.status)
+        ))
+        .with_result(proposal_json(&update.multisig_pda, &update.proposal))
+        .with_signature(update.signature)
+    }
+}
+
+impl From<DaoInfo> for CommandOutput {
+    fn from(dao: DaoInfo) -> Self {
+        let members: Vec<Value> = dao.members.iter().map(member_json).collect();
+        let proposals: Vec<Value> = dao
+            .proposals
+            .iter()
+            .map(|proposal| proposal_json(&dao.multisig_pda, proposal))
+            .collect();
+
+        CommandOutput::message(format!(
+            "Multisig {} has {} members and threshold {}",
+            dao.multisig_pda,
+            dao.members.len(),
+            dao.threshold
+        ))
+        .with_result(json!({
+            "multisig_pda": dao.multisig_pda.to_string(),
+            "vault_pda": dao.vault_pda.to_string(),
+            "create_key": dao.create_key.to_string(),
+            "threshold": dao.threshold,
+            "time_lock": dao.time_lock,
+            "members": members,
+            "transaction_index": dao.transaction_index,
+            "stale_transaction_index": dao.stale_transaction_index,
+            "vault_balance": dao.vault_balance,
+            "proposals": proposals,
+        }))
+    }
+}
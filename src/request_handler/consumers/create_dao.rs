@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::dao_module::services::dao_service::{DaoService, CreatedDao};
+use crate::request_handler::consumers::common::{MemberSchema, PERMISSION_NAMES};
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct CreateDaoSchema {
+    members: Vec<MemberSchema>,
+    threshold: u16,
+    #[serde(default)]
+    time_lock: u32,
+}
+
+pub struct CreateDao {
+    service: Arc<DaoService>,
+}
+
+impl CreateDao {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        CreateDao { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for CreateDao {
+    type Request = CreateDaoSchema;
+    type Response = CreatedDao;
+
+    const NAME: &'static str = "create_dao";
+    const ROUTING_KEY: &'static str = "dao.create";
+
+    fn schema() -> Value {
+        json!({
+            "members": [{ "key": "string", "permissions": [PERMISSION_NAMES] }],
+            "threshold": "unsigned integer",
+            "time_lock": "unsigned integer, optional"
+        })
+    }
+
+    fn validate(&self, request: &CreateDaoSchema) -> Vec<Violation> {
+        let mut validator = Validator::new();
+        for (index, member) in request.members.iter().enumerate() {
+            member.validate(&format!("$.members[{}]", index), &mut validator);
+        }
+
+        // The payer only joins as proposer, so the members have to be able to pass and execute proposals
+        let voters = request.members.iter().filter(|member| member.has_permission("vote")).count();
+        validator.range("$.threshold", request.threshold as u64, 1, voters as u64);
+        if !request.members.iter().any(|member| member.has_permission("execute")) {
+            validator.push("$.members", Some(PERMISSION_NAMES), "At least one member needs the execute permission");
+        }
+
+        validator.finish()
+    }
+
+    async fn handle(&self, request: CreateDaoSchema) -> Result<CreatedDao, CommandError> {
+        let mut members = Vec::new();
+        for member in &request.members {
+            members.push(member.to_member()?);
+        }
+
+        Ok(self
+            .service
+            .create_dao(&members, request.threshold, request.time_lock)
+            .await?)
+    }
+}
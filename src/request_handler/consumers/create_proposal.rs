@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+
+use crate::dao_module::services::dao_service::{DaoService, CreatedProposal};
+use crate::multisig_utils::business_analyst_multisig_trait::TransactionCreateAction;
+use crate::request_handler::consumers::common::parse_pubkey;
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct AccountMetaSchema {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InstructionSchema {
+    program_id: String,
+    accounts: Vec<AccountMetaSchema>,
+    data: Vec<u8>,
+}
+
+impl InstructionSchema {
+    fn to_instruction(&self) -> Result<Instruction, CommandError> {
+        let mut accounts = Vec::new();
+        for account in &self.accounts {
+            accounts.push(AccountMeta {
+                pubkey: parse_pubkey("account pubkey", &account.pubkey)?,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            });
+        }
+
+        Ok(Instruction {
+            program_id: parse_pubkey("program_id", &self.program_id)?,
+            accounts,
+            data: self.data.clone(),
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateProposalSchema {
+    multisig_pda: String,
+    instructions: Vec<InstructionSchema>,
+    memo: Option<String>,
+}
+
+pub struct CreateProposal {
+    service: Arc<DaoService>,
+}
+
+impl CreateProposal {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        CreateProposal { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for CreateProposal {
+    type Request = CreateProposalSchema;
+    type Response = CreatedProposal;
+
+    const NAME: &'static str = "create_proposal";
+    const ROUTING_KEY: &'static str = "proposal.create";
+
+    fn schema() -> Value {
+        json!({
+            "multisig_pda": "string",
+            "instructions": [{
+                "program_id": "string",
+                "accounts": [{ "pubkey": "string", "is_signer": "boolean", "is_writable": "boolean" }],
+                "data": ["unsigned integer"]
+            }],
+            "memo": "string, optional"
+        })
+    }
+
+    fn validate(&self, request: &CreateProposalSchema) -> Vec<Violation> {
+        let mut validator = Validator::new();
+        validator.pubkey("$.multisig_pda", &request.multisig_pda);
+        if request.instructions.is_empty() {
+            validator.push("$.instructions", Some("array"), "At least one instruction is required");
+        }
+
+        for (index, instruction) in request.instructions.iter().enumerate() {
+            let path = format!("$.instructions[{}]", index);
+            validator.pubkey(&format!("{}.program_id", path), &instruction.program_id);
+            for (account_index, account) in instruction.accounts.iter().enumerate() {
+                validator.pubkey(&format!("{}.accounts[{}].pubkey", path, account_index), &account.pubkey);
+            }
+        }
+
+        validator.finish()
+    }
+
+    async fn handle(&self, request: CreateProposalSchema) -> Result<CreatedProposal, CommandError> {
+        let multisig_pda = parse_pubkey("multisig_pda", &request.multisig_pda)?;
+
+        let mut instructions = Vec::new();
+        for instruction in &request.instructions {
+            instructions.push(instruction.to_instruction()?);
+        }
+
+        Ok(self
+            .service
+            .propose(
+                &multisig_pda,
+                TransactionCreateAction::VaultInstructions { instructions, memo: request.memo },
+            )
+            .await?)
+    }
+}
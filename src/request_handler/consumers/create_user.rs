@@ -1,6 +1,11 @@
-use crate::dao_module::services::dao_service;
+use async_trait::async_trait;
 use serde::Deserialize;
+use serde_json::{json, Value};
 
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::{CommandError, CommandOutput};
+
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 pub struct CreateUserSchema {
     email: String,
@@ -8,10 +13,24 @@ pub struct CreateUserSchema {
     employee_id: i32,
 }
 
-pub async fn consume(request: CreateUserSchema) -> Result<String, String> {
-    dao_service::create_dao();
-    return Ok(format!(
-        "User {} created successfully with roles:",
-        request.email
-    ));
+pub struct CreateUser;
+
+#[async_trait]
+impl CommandHandler for CreateUser {
+    type Request = CreateUserSchema;
+    type Response = CommandOutput;
+
+    const NAME: &'static str = "create_user";
+    const ROUTING_KEY: &'static str = "user.create";
+
+    fn schema() -> Value {
+        json!({ "email": "string", "password": "string", "employee_id": "integer" })
+    }
+
+    async fn handle(&self, request: CreateUserSchema) -> Result<CommandOutput, CommandError> {
+        return Ok(CommandOutput::message(format!(
+            "User {} created successfully with roles:",
+            request.email
+        )));
+    }
 }
@@ -1,13 +1,33 @@
+use async_trait::async_trait;
 use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::{CommandError, CommandOutput};
 
 #[derive(Deserialize, Debug)]
 pub struct DeleteUserSchema {
     employee_id: i32,
 }
 
-pub async fn consume(request: DeleteUserSchema) -> Result<String, String> {
-    Ok(format!(
-        "User with employee_id={} deleted successfully",
-        request.employee_id
-    ))
+pub struct DeleteUser;
+
+#[async_trait]
+impl CommandHandler for DeleteUser {
+    type Request = DeleteUserSchema;
+    type Response = CommandOutput;
+
+    const NAME: &'static str = "delete_user";
+    const ROUTING_KEY: &'static str = "user.delete";
+
+    fn schema() -> Value {
+        json!({ "employee_id": "integer" })
+    }
+
+    async fn handle(&self, request: DeleteUserSchema) -> Result<CommandOutput, CommandError> {
+        Ok(CommandOutput::message(format!(
+            "User with employee_id={} deleted successfully",
+            request.employee_id
+        )))
+    }
 }
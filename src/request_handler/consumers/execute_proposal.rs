@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::dao_module::services::dao_service::{DaoService, ProposalUpdate};
+use crate::request_handler::consumers::common::{parse_pubkey, parse_transaction};
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct ExecuteProposalSchema {
+    multisig_pda: String,
+    transaction_index: u64,
+    executor: String,
+    transaction: String,
+}
+
+pub struct ExecuteProposal {
+    service: Arc<DaoService>,
+}
+
+impl ExecuteProposal {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        ExecuteProposal { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for ExecuteProposal {
+    type Request = ExecuteProposalSchema;
+    type Response = ProposalUpdate;
+
+    const NAME: &'static str = "execute_proposal";
+    const ROUTING_KEY: &'static str = "proposal.execute";
+
+    fn schema() -> Value {
+        json!({
+            "multisig_pda": "string",
+            "transaction_index": "unsigned integer",
+            "executor": "string",
+            "transaction": "string"
+        })
+    }
+
+    fn validate(&self, request: &ExecuteProposalSchema) -> Vec<Violation> {
+        Validator::new()
+            .pubkey("$.multisig_pda", &request.multisig_pda)
+            .positive("$.transaction_index", request.transaction_index)
+            .pubkey("$.executor", &request.executor)
+            .transaction("$.transaction", &request.transaction)
+            .finish()
+    }
+
+    async fn handle(&self, request: ExecuteProposalSchema) -> Result<ProposalUpdate, CommandError> {
+        let multisig_pda = parse_pubkey("multisig_pda", &request.multisig_pda)?;
+        let executor = parse_pubkey("executor", &request.executor)?;
+        let transaction = parse_transaction("transaction", &request.transaction)?;
+
+        Ok(self
+            .service
+            .execute_proposal(&multisig_pda, request.transaction_index, &executor, &transaction)
+            .await?)
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::dao_module::services::dao_service::{DaoService, DaoInfo};
+use crate::request_handler::consumers::common::parse_pubkey;
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct GetDaoSchema {
+    multisig_pda: String,
+    /// Lists the proposals before this transaction index instead of the newest ones
+    #[serde(default)]
+    proposals_before: Option<u64>,
+}
+
+pub struct GetDao {
+    service: Arc<DaoService>,
+}
+
+impl GetDao {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        GetDao { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for GetDao {
+    type Request = GetDaoSchema;
+    type Response = DaoInfo;
+
+    const NAME: &'static str = "get_dao";
+    const ROUTING_KEY: &'static str = "dao.get";
+
+    fn schema() -> Value {
+        json!({
+            "multisig_pda": "string",
+            "proposals_before": "unsigned integer, optional"
+        })
+    }
+
+    fn validate(&self, request: &GetDaoSchema) -> Vec<Violation> {
+        Validator::new().pubkey("$.multisig_pda", &request.multisig_pda).finish()
+    }
+
+    async fn handle(&self, request: GetDaoSchema) -> Result<DaoInfo, CommandError> {
+        let multisig_pda = parse_pubkey("multisig_pda", &request.multisig_pda)?;
+
+        Ok(self.service.get_dao(&multisig_pda, request.proposals_before).await?)
+    }
+}
@@ -1,50 +1,282 @@
+mod add_member;
+mod approve_proposal;
+mod cancel_proposal;
+mod change_threshold;
+pub mod common;
+mod create_dao;
+mod create_proposal;
 mod create_user;
 mod delete_user;
+mod execute_proposal;
+mod get_dao;
+mod recreate_proposal;
+pub mod registry;
+mod remove_member;
+mod transfer_from_vault;
+
+use std::sync::Arc;
 
 use amqp_serde::types::{FieldName, FieldValue};
-use amqprs::channel::{BasicAckArguments, Channel};
+use amqprs::channel::{BasicAckArguments, BasicNackArguments, Channel};
 use amqprs::consumer::AsyncConsumer;
 use amqprs::{BasicProperties, Deliver};
 use async_trait::async_trait;
-use serde::Deserialize;
-use serde_json;
 
-use crate::request_handler::consumers::create_user::CreateUserSchema;
-use crate::request_handler::consumers::delete_user::DeleteUserSchema;
+use crate::dao_module::services::dao_service::DaoService;
+use crate::request_handler::consumers::add_member::AddMember;
+use crate::request_handler::consumers::approve_proposal::ApproveProposal;
+use crate::request_handler::consumers::cancel_proposal::CancelProposal;
+use crate::request_handler::consumers::change_threshold::ChangeThreshold;
+use crate::request_handler::consumers::create_dao::CreateDao;
+use crate::request_handler::consumers::create_proposal::CreateProposal;
+use crate::request_handler::consumers::create_user::CreateUser;
+use crate::request_handler::consumers::delete_user::DeleteUser;
+use crate::request_handler::consumers::execute_proposal::ExecuteProposal;
+use crate::request_handler::consumers::get_dao::GetDao;
+use crate::request_handler::consumers::recreate_proposal::RecreateProposal;
+use crate::request_handler::consumers::remove_member::RemoveMember;
+use crate::request_handler::consumers::transfer_from_vault::TransferFromVault;
+use crate::request_handler::consumers::registry::CommandRegistry;
+use crate::request_handler::delivery::{retry_count, DeliveryTopology, Disposition};
+use crate::request_handler::envelope::CommandRequest;
+use crate::request_handler::idempotency::{idempotency_key, with_heartbeat, Claim, IdempotencyStore};
+use crate::request_handler::in_flight::InFlight;
+use crate::request_handler::publisher::{publish, Publication};
+use crate::request_handler::response::{CommandError, ErrorCode, ResponseEnvelope};
+use crate::request_handler::topology::ExchangeTopology;
+
+/// The commands of the DAO service. A new command is a module with a `CommandHandler`, registered here.
+pub fn dao_commands(service: Arc<DaoService>) -> CommandRegistry {
+    let mut registry = CommandRegistry::new();
+    registry
+        .register(CreateUser)
+        .register(DeleteUser)
+        .register(CreateDao::new(service.clone()))
+        .register(AddMember::new(service.clone()))
+        .register(RemoveMember::new(service.clone()))
+        .register(ChangeThreshold::new(service.clone()))
+        .register(TransferFromVault::new(service.clone()))
+        .register(CreateProposal::new(service.clone()))
+        .register(ApproveProposal::new(service.clone()))
+        .register(CancelProposal::new(service.clone()))
+        .register(ExecuteProposal::new(service.clone()))
+        .register(RecreateProposal::new(service.clone()))
+        .register(GetDao::new(service));
+
+    return registry;
+}
 
-pub struct RabbitMQConsumer {}
+pub struct RabbitMQConsumer {
+    registry: Arc<CommandRegistry>,
+    idempotency: Arc<dyn IdempotencyStore>,
+    exchanges: ExchangeTopology,
+    topology: DeliveryTopology,
+    in_flight: InFlight,
+}
 
 impl RabbitMQConsumer {
-    pub fn new() -> RabbitMQConsumer {
-        return RabbitMQConsumer {};
+    pub fn new(
+        registry: Arc<CommandRegistry>,
+        idempotency: Arc<dyn IdempotencyStore>,
+        exchanges: ExchangeTopology,
+        topology: DeliveryTopology,
+        in_flight: InFlight,
+    ) -> RabbitMQConsumer {
+        return RabbitMQConsumer { registry, idempotency, exchanges, topology, in_flight };
+    }
+
+    /// Routing key of the response and event of a request: the one of its command, or the one the
+    /// request came with when the command is unknown.
+    fn routing_key<'a>(&self, deliver: &'a Deliver, response: &ResponseEnvelope) -> &'a str {
+        return match response.command.as_deref().and_then(|command| self.registry.routing_key(command)) {
+            Some(routing_key) => routing_key,
+            None => deliver.routing_key(),
+        };
+    }
+
+    /// Sends the reply to the caller's `reply_to` queue, or else to the response exchange.
+    async fn reply(&self, channel: &Channel, deliver: &Deliver, basic_properties: &BasicProperties, response: &ResponseEnvelope) {
+        let routing_key = self.routing_key(deliver, response);
+        let reply = Publication::reply(basic_properties, response, &self.exchanges.response_exchange, routing_key);
+        if let Err(err) = publish(channel, reply).await {
+            eprintln!("[{:?} RABBITMQ ERROR] {}", chrono::Utc::now(), err);
+        }
+    }
+
+    /// Announces a command that sent transactions on the event exchange.
+    async fn publish_event(&self, channel: &Channel, deliver: &Deliver, response: &ResponseEnvelope) {
+        let routing_key = self.routing_key(deliver, response);
+        if let Some(event) = Publication::event(response, &self.exchanges.event_exchange, routing_key) {
+            if let Err(err) = publish(channel, event).await {
+                eprintln!("[{:?} RABBITMQ ERROR] {}", chrono::Utc::now(), err);
+            }
+        }
+    }
+}
+
+async fn ack(channel: &Channel, delivery_tag: u64) {
+    if let Err(err) = channel
+        .basic_ack(BasicAckArguments::new(delivery_tag, false))
+        .await
+    {
+        eprintln!(
+            "[{:?} RABBITMQ ERROR] Could not send acknowledgement: {}",
+            chrono::Utc::now(),
+            err
+        );
     }
 }
 
-fn load_schema<'a, T: Deserialize<'a>>(raw_json: &'a str) -> Result<T, String> {
-    return match serde_json::from_str::<T>(raw_json) {
-        Ok(json_schema) => Ok(json_schema),
-        Err(..) => Err("Could not parse raw string into json".to_string()),
+/// Puts the delivery back on the request queue, for when it could not be moved anywhere else.
+async fn nack_requeue(channel: &Channel, delivery_tag: u64) {
+    if let Err(err) = channel
+        .basic_nack(BasicNackArguments::new(delivery_tag, false, true))
+        .await
+    {
+        eprintln!(
+            "[{:?} RABBITMQ ERROR] Could not send negative acknowledgement: {}",
+            chrono::Utc::now(),
+            err
+        );
+    }
+}
+
+fn read_command(basic_properties: &BasicProperties) -> Result<String, CommandError> {
+    let command_header_key: FieldName = "command".try_into().unwrap();
+    let headers = match basic_properties.headers() {
+        Some(headers) => headers,
+        None => return Err(CommandError::new(ErrorCode::MalformedMessage, "Headers was not provided")),
     };
+    let command = match headers.get(&command_header_key) {
+        Some(command) => command,
+        None => {
+            return Err(CommandError::new(
+                ErrorCode::MalformedMessage,
+                "'command' header was not provided",
+            ))
+        }
+    };
+
+    return match command {
+        FieldValue::S(command) => Ok(command.to_string()),
+        _ => Err(CommandError::new(
+            ErrorCode::MalformedMessage,
+            "'command' header must be a string",
+        )),
+    };
+}
+
+/// The response to one message.
+pub struct Handled {
+    pub response: ResponseEnvelope,
+    /// The response was stored by an earlier delivery of the same request and the command did not run
+    pub replayed: bool,
+}
+
+impl Handled {
+    fn fresh(response: ResponseEnvelope) -> Self {
+        Handled { response, replayed: false }
+    }
 }
 
-async fn run_consumer(consumer_name: &str, raw_json_schema: &str) -> Result<String, String> {
-    return match consumer_name {
-        "create_user" => {
-            let json: CreateUserSchema = load_schema(raw_json_schema)?;
+/// Runs the command of one message and wraps the outcome in the envelope sent back to the caller.
+/// Requests in an envelope run at most once per idempotency key, duplicates get the stored response.
+pub async fn handle_message(
+    registry: &CommandRegistry,
+    idempotency: &dyn IdempotencyStore,
+    basic_properties: &BasicProperties,
+    content: &[u8],
+) -> Handled {
+    let command = match read_command(basic_properties) {
+        Ok(command) => command,
+        Err(err) => return Handled::fresh(ResponseEnvelope::from_result(None, Err(err))),
+    };
 
-            create_user::consume(json).await
+    let raw_string = match std::str::from_utf8(content) {
+        Ok(raw_string) => raw_string,
+        Err(..) => {
+            return Handled::fresh(ResponseEnvelope::from_result(
+                Some(&command),
+                Err(CommandError::new(
+                    ErrorCode::MalformedMessage,
+                    "Could not parse byte content into raw string",
+                )),
+            ))
         }
-        "delete_user" => {
-            let json: DeleteUserSchema = load_schema(raw_json_schema)?;
+    };
+
+    let request = match CommandRequest::parse(raw_string) {
+        Ok(request) => request,
+        Err(err) => return Handled::fresh(ResponseEnvelope::from_result(Some(&command), Err(err))),
+    };
+    if let Some(metadata) = request.metadata.as_ref() {
+        println!(
+            "[{:?} RABBITMQ INFO] {} message {} from {} issued at {}",
+            chrono::Utc::now(),
+            command,
+            metadata.message_id,
+            metadata.actor,
+            metadata.issued_at
+        );
+    }
 
-            delete_user::consume(json).await
+    let message_id = request.message_id().map(str::to_string);
+    let key = idempotency_key(&request).map(str::to_string);
+    if let Some(key) = key.as_deref() {
+        let error = match idempotency.claim(key, &command).await {
+            Ok(Claim::Acquired) => None,
+            Ok(Claim::Completed(response)) => {
+                println!(
+                    "[{:?} RABBITMQ INFO] {} {} was already handled, replaying its response",
+                    chrono::Utc::now(),
+                    command,
+                    key
+                );
+                return Handled { response, replayed: true };
+            }
+            // Retried like any transient failure until the other delivery finished or gave up
+            Ok(Claim::InProgress { since }) => Some(CommandError::transient(format!(
+                "{} {} is already being handled since {}",
+                command, key, since
+            ))),
+            // Final, and not stored under the key which keeps answering for its own command
+            Ok(Claim::OtherCommand { command: claimed_by }) => Some(CommandError::new(
+                ErrorCode::InvalidPayload,
+                format!("Idempotency key {} was already used for {}", key, claimed_by),
+            )),
+            Err(err) => Some(CommandError::from(err)),
+        };
+        if let Some(error) = error {
+            let response = ResponseEnvelope::from_result(Some(&command), Err(error));
+            return Handled::fresh(response.with_message_id(message_id.as_deref()));
         }
-        unknown_command => Err(format!("Unknown command: {}", unknown_command)),
+    }
+
+    let dispatch = registry.dispatch(&command, request);
+    let result = match key.as_deref() {
+        Some(key) => with_heartbeat(idempotency, key, dispatch).await,
+        None => dispatch.await,
     };
+    let response = ResponseEnvelope::from_result(Some(&command), result).with_message_id(message_id.as_deref());
+
+    if let Some(key) = key.as_deref() {
+        // A request that will be retried must run again, anything else is final
+        let stored = match response.error_code {
+            Some(code) if code.is_transient() => idempotency.release(key).await,
+            _ => idempotency.complete(key, &response).await,
+        };
+        if let Err(err) = stored {
+            eprintln!("[{:?} RABBITMQ ERROR] Could not record outcome of {}: {}", chrono::Utc::now(), key, err);
+        }
+    }
+
+    return Handled::fresh(response);
 }
 
 #[async_trait]
 impl AsyncConsumer for RabbitMQConsumer {
+    /// The delivery is only acknowledged once it was handled and, when needed, moved to a retry queue
+    /// or the dead-letter exchange, so a crash mid-handling leaves it on the queue.
     async fn consume(
         &mut self,
         channel: &Channel,
@@ -52,60 +284,222 @@ impl AsyncConsumer for RabbitMQConsumer {
         basic_properties: BasicProperties,
         content: Vec<u8>,
     ) {
-        channel
-            .basic_ack(BasicAckArguments::new(deliver.delivery_tag(), false))
-            .await
-            .expect("Could not send acknowledgement!");
-
-        let raw_string = match std::str::from_utf8(&content) {
-            Ok(raw_string) => raw_string,
-            Err(..) => {
-                println!("Could not parse byte content into raw string");
-                return;
-            }
-        };
-
-        let command_header_key: FieldName = "command".try_into().unwrap();
-        let headers = match basic_properties.headers() {
-            Some(headers) => headers,
-            None => {
-                println!("Headers was not provided");
-                return;
-            }
-        };
-        let command = match headers.get(&command_header_key) {
-            Some(command) => command,
+        // Held until the delivery is acked, so shutdown waits for the reply and the acknowledgement
+        let _in_flight = match self.in_flight.begin() {
+            Some(guard) => guard,
             None => {
-                println!("'command' header was not provided");
+                println!(
+                    "[{:?} RABBITMQ INFO] Shutting down, returning delivery to the queue",
+                    chrono::Utc::now()
+                );
+                nack_requeue(channel, deliver.delivery_tag()).await;
                 return;
             }
         };
 
-        let command = match command {
-            FieldValue::S(command) => command.to_string(),
-            _ => {
-                println!("'command' header must be a string");
-                return;
-            }
-        };
+        let retry_count = retry_count(&basic_properties);
+        let handled = handle_message(&self.registry, self.idempotency.as_ref(), &basic_properties, &content).await;
+        let response = handled.response;
 
-        let result: Result<String, String> = run_consumer(&command, raw_string).await;
+        if response.is_ok() {
+            println!(
+                "[{:?} RABBITMQ INFO] {}",
+                chrono::Utc::now(),
+                response.message
+            );
+        } else {
+            eprintln!(
+                "[{:?} RABBITMQ ERROR] {}",
+                chrono::Utc::now(),
+                response.message
+            );
+        }
 
-        match result {
-            Ok(success_message) => {
-                println!(
-                    "[{:?} RABBITMQ INFO] {}",
-                    chrono::Utc::now(),
-                    success_message
-                );
+        // The first delivery already retried or dead-lettered the request, a duplicate only gets its reply
+        if handled.replayed {
+            self.reply(channel, &deliver, &basic_properties, &response).await;
+            ack(channel, deliver.delivery_tag()).await;
+            return;
+        }
+
+        match self.topology.disposition(&response, retry_count) {
+            Disposition::Ack => {
+                self.reply(channel, &deliver, &basic_properties, &response).await;
+                self.publish_event(channel, &deliver, &response).await;
+                ack(channel, deliver.delivery_tag()).await;
             }
-            Err(error_message) => {
-                eprintln!(
-                    "[{:?} RABBITMQ ERROR] {}",
-                    chrono::Utc::now(),
-                    error_message
-                );
+            Disposition::Retry { attempt } => {
+                let (args, properties) = self.topology.retry_message(&basic_properties, attempt);
+                match channel.basic_publish(properties, content, args).await {
+                    Ok(()) => {
+                        println!(
+                            "[{:?} RABBITMQ INFO] Retry {} of {} in {:?}",
+                            chrono::Utc::now(),
+                            attempt,
+                            self.topology.retry_policy.max_retries,
+                            self.topology.retry_policy.delay(attempt)
+                        );
+                        ack(channel, deliver.delivery_tag()).await;
+                    }
+                    Err(err) => {
+                        eprintln!(
+                            "[{:?} RABBITMQ ERROR] Could not schedule retry: {}",
+                            chrono::Utc::now(),
+                            err
+                        );
+                        nack_requeue(channel, deliver.delivery_tag()).await;
+                    }
+                }
+            }
+            Disposition::DeadLetter => {
+                let (args, properties) = self.topology.dead_letter_message(&basic_properties, &response);
+                match channel.basic_publish(properties, content, args).await {
+                    Ok(()) => {
+                        self.reply(channel, &deliver, &basic_properties, &response).await;
+                        ack(channel, deliver.delivery_tag()).await;
+                    }
+                    Err(err) => {
+                        eprintln!(
+                            "[{:?} RABBITMQ ERROR] Could not dead-letter request: {}",
+                            chrono::Utc::now(),
+                            err
+                        );
+                        nack_requeue(channel, deliver.delivery_tag()).await;
+                    }
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao_module::repositories::dao_repository::DaoRepository;
+    use crate::request_handler::idempotency::memory::MemoryIdempotencyStore;
+    use crate::request_handler::idempotency::DEFAULT_LEASE;
+    use crate::rpc_utils::in_process_bank::InProcessBank;
+    use amqp_serde::types::FieldTable;
+    use solana_sdk::account::Account;
+    use solana_sdk::native_token::LAMPORTS_PER_SOL;
+    use solana_sdk::{signature::Keypair, signer::Signer, system_program};
+    use squads_multisig::squads_multisig_program;
+
+    fn properties_with_command(command: &str) -> BasicProperties {
+        let mut headers = FieldTable::new();
+        headers.insert(
+            "command".try_into().unwrap(),
+            FieldValue::S(command.try_into().unwrap()),
+        );
+
+        BasicProperties::default().with_headers(headers).finish()
+    }
+
+    fn test_registry() -> CommandRegistry {
+        let rpc_client = Arc::new(InProcessBank::with_squads(squads_multisig_program::ID));
+        let payer = Keypair::new();
+        rpc_client.set_account(payer.pubkey(), Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program::ID));
+        let keystore_dir = std::env::temp_dir().join(format!("dao-keystore-{}", Keypair::new().pubkey()));
+        let service = DaoService::new(
+            rpc_client,
+            squads_multisig_program::ID,
+            payer,
+            DaoRepository::new(keystore_dir),
+        );
+
+        dao_commands(Arc::new(service))
+    }
+
+    #[tokio::test]
+    async fn handle_message_envelopes() {
+        let registry = test_registry();
+        let store = MemoryIdempotencyStore::new(DEFAULT_LEASE);
+        assert_eq!(13, registry.commands().len());
+
+        let response = handle_message(&registry, &store, &properties_with_command("delete_user"), br#"{"employee_id": 1}"#).await.response;
+        assert!(response.is_ok());
+        assert_eq!(Some("delete_user".to_string()), response.command);
+
+        let response = handle_message(&registry, &store, &properties_with_command("delete_user"), b"{").await.response;
+        assert_eq!(Some(ErrorCode::MalformedMessage), response.error_code);
+
+        let envelope = br#"{
+            "schema_version": 1,
+            "message_id": "b7c1",
+            "issued_at": "2024-06-01T12:00:00Z",
+            "actor": "backend",
+            "payload": { "employee_id": 1 }
+        }"#;
+        let response = handle_message(&registry, &store, &properties_with_command("delete_user"), envelope).await.response;
+        assert!(response.is_ok());
+        assert_eq!(Some("b7c1".to_string()), response.message_id);
+
+        let response = handle_message(&registry, &store, &properties_with_command("launch_rocket"), b"{}").await.response;
+        assert_eq!(Some(ErrorCode::UnknownCommand), response.error_code);
+
+        let response = handle_message(&registry, &store, &BasicProperties::default(), b"{}").await.response;
+        assert_eq!(Some(ErrorCode::MalformedMessage), response.error_code);
+        assert_eq!(None, response.command);
+
+        let response = handle_message(&registry, &store, &properties_with_command("get_dao"), br#"{"multisig_pda": "dao"}"#).await.response;
+        assert_eq!(Some(ErrorCode::InvalidPayload), response.error_code);
+        assert_eq!("$.multisig_pda", response.violations[0].path);
+
+        let content = br#"{"members": [{"key": "member", "permissions": ["own"]}], "threshold": 0}"#;
+        let response = handle_message(&registry, &store, &properties_with_command("create_dao"), content).await.response;
+        let paths: Vec<&str> = response.violations.iter().map(|violation| violation.path.as_str()).collect();
+        assert_eq!(vec!["$.members[0].permissions[0]"], paths);
+
+        let content = br#"{"members": [{"key": "member", "permissions": ["vote"]}], "threshold": 3}"#;
+        let response = handle_message(&registry, &store, &properties_with_command("create_dao"), content).await.response;
+        let paths: Vec<&str> = response.violations.iter().map(|violation| violation.path.as_str()).collect();
+        assert_eq!(vec!["$.members[0].key", "$.threshold", "$.members"], paths);
+
+        let multisig_pda = Keypair::new().pubkey().to_string();
+        let content = format!(r#"{{"multisig_pda": "{}"}}"#, multisig_pda);
+        let response = handle_message(&registry, &store, &properties_with_command("get_dao"), content.as_bytes()).await.response;
+        assert_eq!(Some(ErrorCode::CommandFailed), response.error_code);
+        assert_eq!(format!("Multisig {} is not managed by this service", multisig_pda), response.message);
+    }
+
+    #[tokio::test]
+    async fn duplicate_requests_replay_the_stored_response() {
+        let registry = test_registry();
+        let store = MemoryIdempotencyStore::new(DEFAULT_LEASE);
+        let content = format!(
+            r#"{{
+                "schema_version": 1,
+                "message_id": "{}",
+                "issued_at": "2024-06-01T12:00:00Z",
+                "actor": "backend",
+                "idempotency_key": "create-dao-42",
+                "payload": {{ "members": [{{ "key": "{}", "permissions": ["vote", "execute"] }}], "threshold": 1 }}
+            }}"#,
+            "b7c1",
+            Keypair::new().pubkey()
+        );
+
+        let first = handle_message(&registry, &store, &properties_with_command("create_dao"), content.as_bytes()).await;
+        assert!(first.response.is_ok(), "{}", first.response.message);
+        assert!(!first.replayed);
+
+        // A redelivery, or the backend retrying under a new message id, does not create a second DAO
+        let content = content.replace("b7c1", "d4e2");
+        let second = handle_message(&registry, &store, &properties_with_command("create_dao"), content.as_bytes()).await;
+        assert!(second.replayed);
+        assert_eq!(first.response, second.response);
+
+        assert_eq!(Claim::Acquired, store.claim("f9a0", "create_dao").await.unwrap());
+        let content = content.replace("create-dao-42", "f9a0");
+        let busy = handle_message(&registry, &store, &properties_with_command("create_dao"), content.as_bytes()).await;
+        assert!(!busy.replayed);
+        assert_eq!(Some(ErrorCode::TransientFailure), busy.response.error_code);
+
+        // The same key for another command does not replay the DAO creation
+        let content = content.replace("f9a0", "create-dao-42");
+        let reused = handle_message(&registry, &store, &properties_with_command("get_dao"), content.as_bytes()).await;
+        assert!(!reused.replayed);
+        assert_eq!(Some(ErrorCode::InvalidPayload), reused.response.error_code);
+        assert_eq!("Idempotency key create-dao-42 was already used for create_dao", reused.response.message);
+    }
+}
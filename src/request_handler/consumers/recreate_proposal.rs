@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::dao_module::services::dao_service::{CreatedProposal, DaoService};
+use crate::request_handler::consumers::common::parse_pubkey;
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct RecreateProposalSchema {
+    multisig_pda: String,
+    transaction_index: u64,
+}
+
+pub struct RecreateProposal {
+    service: Arc<DaoService>,
+}
+
+impl RecreateProposal {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        RecreateProposal { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for RecreateProposal {
+    type Request = RecreateProposalSchema;
+    type Response = CreatedProposal;
+
+    const NAME: &'static str = "recreate_proposal";
+    const ROUTING_KEY: &'static str = "proposal.recreate";
+
+    fn schema() -> Value {
+        json!({
+            "multisig_pda": "string",
+            "transaction_index": "unsigned integer"
+        })
+    }
+
+    fn validate(&self, request: &RecreateProposalSchema) -> Vec<Violation> {
+        Validator::new()
+            .pubkey("$.multisig_pda", &request.multisig_pda)
+            .positive("$.transaction_index", request.transaction_index)
+            .finish()
+    }
+
+    async fn handle(&self, request: RecreateProposalSchema) -> Result<CreatedProposal, CommandError> {
+        let multisig_pda = parse_pubkey("multisig_pda", &request.multisig_pda)?;
+
+        Ok(self
+            .service
+            .recreate_proposal(&multisig_pda, request.transaction_index)
+            .await?)
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::request_handler::envelope::CommandRequest;
+use crate::request_handler::response::{CommandError, CommandOutput, CommandResult, ErrorCode};
+use crate::request_handler::validation::{schema_violations, Violation};
+
+/// One DAO command: the `command` header it answers to, the body it expects and what it hands back.
+#[async_trait]
+pub trait CommandHandler: Send + Sync + 'static {
+    type Request: DeserializeOwned + Send;
+    type Response: Into<CommandOutput> + Send;
+
+    /// Value of the `command` header routed to this handler
+    const NAME: &'static str;
+
+    /// Routing key of requests on the request exchange, `<family>.<action>` like `proposal.approve`.
+    /// Responses and events of the command are published under it too.
+    const ROUTING_KEY: &'static str;
+
+    /// Schema version of `Request`. Payloads of older versions go through `upgrades` first.
+    const VERSION: u32 = 1;
+
+    /// Steps from each older schema version to the next one, so senders can move to a new version
+    /// after this service is deployed.
+    fn upgrades() -> Vec<SchemaUpgrade> {
+        Vec::new()
+    }
+
+    /// Fields of the request body and their JSON types, listed by the registry.
+    /// Bodies are checked against it before they are deserialized, see `schema_violations`.
+    fn schema() -> Value;
+
+    /// Semantic checks of a request that matches the schema, e.g. valid public keys or amounts above 0.
+    fn validate(&self, _request: &Self::Request) -> Vec<Violation> {
+        Vec::new()
+    }
+
+    async fn handle(&self, request: Self::Request) -> Result<Self::Response, CommandError>;
+}
+
+/// Rewrites a payload of schema version `from_version` into version `from_version + 1`.
+#[derive(Clone, Copy)]
+pub struct SchemaUpgrade {
+    pub from_version: u32,
+    pub upgrade: fn(Value) -> Result<Value, Violation>,
+}
+
+/// A registered command with its request schema.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CommandDescription {
+    pub name: &'static str,
+    pub routing_key: &'static str,
+    pub version: u32,
+    pub schema: Value,
+}
+
+/// Brings `payload` from `schema_version` up to `version`, one upgrade at a time.
+fn upgrade_payload(
+    command: &str,
+    version: u32,
+    upgrades: &[SchemaUpgrade],
+    schema_version: u32,
+    mut payload: Value,
+) -> Result<Value, CommandError> {
+    if schema_version == 0 || schema_version > version {
+        return Err(CommandError::new(
+            ErrorCode::UnsupportedSchemaVersion,
+            format!("{} supports schema versions up to {}, got {}", command, version, schema_version),
+        ));
+    }
+
+    for from_version in schema_version..version {
+        let upgrade = match upgrades.iter().find(|upgrade| upgrade.from_version == from_version) {
+            Some(upgrade) => upgrade,
+            None => {
+                return Err(CommandError::new(
+                    ErrorCode::UnsupportedSchemaVersion,
+                    format!("{} can not upgrade schema version {} to {}", command, from_version, version),
+                ))
+            }
+        };
+
+        payload = match (upgrade.upgrade)(payload) {
+            Ok(payload) => payload,
+            Err(violation) => return Err(CommandError::invalid_request(command, vec![violation])),
+        };
+    }
+
+    return Ok(payload);
+}
+
+/// Object safe side of `CommandHandler`, so handlers with different request types share one map.
+#[async_trait]
+trait RegisteredCommand: Send + Sync {
+    fn routing_key(&self) -> &'static str;
+
+    fn version(&self) -> u32;
+
+    fn schema(&self) -> Value;
+
+    async fn run(&self, schema_version: u32, payload: Value) -> CommandResult;
+}
+
+#[async_trait]
+impl<H: CommandHandler> RegisteredCommand for H {
+    fn routing_key(&self) -> &'static str {
+        H::ROUTING_KEY
+    }
+
+    fn version(&self) -> u32 {
+        H::VERSION
+    }
+
+    fn schema(&self) -> Value {
+        H::schema()
+    }
+
+    async fn run(&self, schema_version: u32, payload: Value) -> CommandResult {
+        let value = upgrade_payload(H::NAME, H::VERSION, &H::upgrades(), schema_version, payload)?;
+
+        let violations = schema_violations(&H::schema(), &value);
+        if !violations.is_empty() {
+            return Err(CommandError::invalid_request(H::NAME, violations));
+        }
+
+        // Values the schema can not describe, like an integer too large for its field, end up here
+        let request: H::Request = match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(err) => return Err(CommandError::invalid_request(H::NAME, vec![Violation::new("$", None, err.to_string())])),
+        };
+
+        let violations = self.validate(&request);
+        if !violations.is_empty() {
+            return Err(CommandError::invalid_request(H::NAME, violations));
+        }
+
+        return match self.handle(request).await {
+            Ok(response) => Ok(response.into()),
+            Err(err) => Err(err),
+        };
+    }
+}
+
+/// The commands the consumer dispatches to, by name.
+#[derive(Default)]
+pub struct CommandRegistry {
+    commands: BTreeMap<&'static str, Box<dyn RegisteredCommand>>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        CommandRegistry::default()
+    }
+
+    /// Registers `handler` under `H::NAME`, replacing a handler registered under the same name.
+    pub fn register<H: CommandHandler>(&mut self, handler: H) -> &mut Self {
+        self.commands.insert(H::NAME, Box::new(handler));
+        self
+    }
+
+    pub fn contains(&self, command: &str) -> bool {
+        self.commands.contains_key(command)
+    }
+
+    /// Supported commands and their schemas, sorted by name.
+    pub fn commands(&self) -> Vec<CommandDescription> {
+        self.commands
+            .iter()
+            .map(|(name, command)| CommandDescription {
+                name,
+                routing_key: command.routing_key(),
+                version: command.version(),
+                schema: command.schema(),
+            })
+            .collect()
+    }
+
+    pub fn routing_key(&self, command: &str) -> Option<&'static str> {
+        self.commands.get(command).map(|command| command.routing_key())
+    }
+
+    pub async fn dispatch(&self, command: &str, request: CommandRequest) -> CommandResult {
+        return match self.commands.get(command) {
+            Some(handler) => handler.run(request.schema_version, request.payload).await,
+            None => Err(CommandError::new(
+                ErrorCode::UnknownCommand,
+                format!("Unknown command: {}", command),
+            )),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Deserialize)]
+    struct EchoRequest {
+        text: String,
+    }
+
+    /// Version 1 of `echo` called its field `txt`.
+    fn rename_txt(mut payload: Value) -> Result<Value, Violation> {
+        let text = match payload.as_object_mut().and_then(|object| object.remove("txt")) {
+            Some(text) => text,
+            None => return Err(Violation::new("$.txt", Some("string"), "Missing field txt")),
+        };
+        payload["text"] = text;
+
+        Ok(payload)
+    }
+
+    struct Echo;
+
+    #[async_trait]
+    impl CommandHandler for Echo {
+        type Request = EchoRequest;
+        type Response = CommandOutput;
+
+        const NAME: &'static str = "echo";
+        const ROUTING_KEY: &'static str = "test.echo";
+        const VERSION: u32 = 2;
+
+        fn upgrades() -> Vec<SchemaUpgrade> {
+            vec![SchemaUpgrade { from_version: 1, upgrade: rename_txt }]
+        }
+
+        fn schema() -> Value {
+            json!({ "text": "string" })
+        }
+
+        fn validate(&self, request: &EchoRequest) -> Vec<Violation> {
+            if request.text.is_empty() {
+                return vec![Violation::new("$.text", None, "Must not be empty")];
+            }
+            Vec::new()
+        }
+
+        async fn handle(&self, request: EchoRequest) -> Result<CommandOutput, CommandError> {
+            Ok(CommandOutput::message(request.text))
+        }
+    }
+
+    fn request(schema_version: u32, payload: Value) -> CommandRequest {
+        CommandRequest { schema_version, metadata: None, payload }
+    }
+
+    #[tokio::test]
+    async fn registry_dispatches_by_name_and_version() {
+        let mut registry = CommandRegistry::new();
+        registry.register(Echo);
+
+        assert!(registry.contains("echo"));
+        assert_eq!(
+            vec![CommandDescription {
+                name: "echo",
+                routing_key: "test.echo",
+                version: 2,
+                schema: json!({ "text": "string" })
+            }],
+            registry.commands()
+        );
+        assert_eq!(Some("test.echo"), registry.routing_key("echo"));
+        assert_eq!(None, registry.routing_key("shout"));
+
+        let output = registry.dispatch("echo", request(2, json!({ "text": "hello" }))).await.unwrap();
+        assert_eq!("hello", output.message);
+        let output = registry.dispatch("echo", request(1, json!({ "txt": "hello" }))).await.unwrap();
+        assert_eq!("hello", output.message);
+
+        let err = registry.dispatch("echo", request(1, json!({ "text": "hello" }))).await.unwrap_err();
+        assert_eq!(vec![Violation::new("$.txt", Some("string"), "Missing field txt")], err.violations);
+        let err = registry.dispatch("echo", request(3, json!({ "text": "hello" }))).await.unwrap_err();
+        assert_eq!(ErrorCode::UnsupportedSchemaVersion, err.code);
+
+        let err = registry.dispatch("echo", request(2, json!({ "txt": "hello" }))).await.unwrap_err();
+        assert_eq!(ErrorCode::InvalidPayload, err.code);
+        assert_eq!(
+            vec![Violation::new("$.text", Some("string"), "Missing field text")],
+            err.violations
+        );
+
+        let err = registry.dispatch("echo", request(2, json!({ "text": "" }))).await.unwrap_err();
+        assert_eq!(vec![Violation::new("$.text", None, "Must not be empty")], err.violations);
+
+        let err = registry.dispatch("shout", request(1, json!({}))).await.unwrap_err();
+        assert_eq!(ErrorCode::UnknownCommand, err.code);
+    }
+}
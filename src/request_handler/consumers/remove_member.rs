@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::dao_module::services::dao_service::{DaoService, CreatedProposal};
+use crate::multisig_utils::business_analyst_multisig_trait::TransactionCreateAction;
+use crate::request_handler::consumers::common::parse_pubkey;
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct RemoveMemberSchema {
+    multisig_pda: String,
+    member: String,
+}
+
+pub struct RemoveMember {
+    service: Arc<DaoService>,
+}
+
+impl RemoveMember {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        RemoveMember { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for RemoveMember {
+    type Request = RemoveMemberSchema;
+    type Response = CreatedProposal;
+
+    const NAME: &'static str = "remove_member";
+    const ROUTING_KEY: &'static str = "dao.remove_member";
+
+    fn schema() -> Value {
+        json!({ "multisig_pda": "string", "member": "string" })
+    }
+
+    fn validate(&self, request: &RemoveMemberSchema) -> Vec<Violation> {
+        Validator::new()
+            .pubkey("$.multisig_pda", &request.multisig_pda)
+            .pubkey("$.member", &request.member)
+            .finish()
+    }
+
+    async fn handle(&self, request: RemoveMemberSchema) -> Result<CreatedProposal, CommandError> {
+        let multisig_pda = parse_pubkey("multisig_pda", &request.multisig_pda)?;
+        let old_member = parse_pubkey("member", &request.member)?;
+
+        Ok(self
+            .service
+            .propose(&multisig_pda, TransactionCreateAction::RemoveMember { old_member })
+            .await?)
+    }
+}
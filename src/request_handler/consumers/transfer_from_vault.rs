@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::dao_module::services::dao_service::{DaoService, CreatedProposal};
+use crate::multisig_utils::business_analyst_multisig_trait::TransactionCreateAction;
+use crate::request_handler::consumers::common::parse_pubkey;
+use crate::request_handler::consumers::registry::CommandHandler;
+use crate::request_handler::response::CommandError;
+use crate::request_handler::validation::{Validator, Violation};
+
+#[derive(Deserialize, Debug)]
+pub struct TransferFromVaultSchema {
+    multisig_pda: String,
+    receiver: String,
+    lamports: u64,
+}
+
+pub struct TransferFromVault {
+    service: Arc<DaoService>,
+}
+
+impl TransferFromVault {
+    pub fn new(service: Arc<DaoService>) -> Self {
+        TransferFromVault { service }
+    }
+}
+
+#[async_trait]
+impl CommandHandler for TransferFromVault {
+    type Request = TransferFromVaultSchema;
+    type Response = CreatedProposal;
+
+    const NAME: &'static str = "transfer_from_vault";
+    const ROUTING_KEY: &'static str = "vault.transfer";
+
+    fn schema() -> Value {
+        json!({ "multisig_pda": "string", "receiver": "string", "lamports": "unsigned integer" })
+    }
+
+    fn validate(&self, request: &TransferFromVaultSchema) -> Vec<Violation> {
+        Validator::new()
+            .pubkey("$.multisig_pda", &request.multisig_pda)
+            .pubkey("$.receiver", &request.receiver)
+            .positive("$.lamports", request.lamports)
+            .finish()
+    }
+
+    async fn handle(&self, request: TransferFromVaultSchema) -> Result<CreatedProposal, CommandError> {
+        let multisig_pda = parse_pubkey("multisig_pda", &request.multisig_pda)?;
+        let receiver = parse_pubkey("receiver", &request.receiver)?;
+
+        Ok(self
+            .service
+            .propose(
+                &multisig_pda,
+                TransactionCreateAction::TransferFromVault { receiver, lamports: request.lamports },
+            )
+            .await?)
+    }
+}
@@ -0,0 +1,252 @@
+use std::time::Duration;
+
+use amqp_serde::types::{FieldName, FieldTable, FieldValue};
+use amqprs::channel::{
+    BasicPublishArguments, Channel, ExchangeDeclareArguments, ExchangeType, QueueBindArguments,
+    QueueDeclareArguments,
+};
+use amqprs::BasicProperties;
+
+use crate::request_handler::publisher::PERSISTENT_DELIVERY_MODE;
+use crate::request_handler::response::ResponseEnvelope;
+
+pub const RETRY_COUNT_HEADER: &str = "x-retry-count";
+pub const ERROR_CODE_HEADER: &str = "x-error-code";
+pub const ERROR_MESSAGE_HEADER: &str = "x-error-message";
+
+/// How often and how late a request that failed transiently is handled again.
+/// Attempt `n` waits `base_delay * 2^(n - 1)` in its own retry queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay * 2_u32.saturating_pow(attempt.saturating_sub(1))
+    }
+}
+
+/// What happens to a delivery once its command was handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disposition {
+    /// Handled, successfully or not, with a reply to the caller
+    Ack,
+    /// Failed transiently, handled again after the delay of `attempt`
+    Retry { attempt: u32 },
+    /// Failed permanently or ran out of retries, parked in the dead-letter queue
+    DeadLetter,
+}
+
+/// The queues and exchanges around the request queue: one delayed retry queue per attempt, whose
+/// expired messages are dead-lettered back to the request queue, and a dead-letter exchange and queue.
+#[derive(Debug, Clone)]
+pub struct DeliveryTopology {
+    pub queue: String,
+    pub dead_letter_exchange: String,
+    pub dead_letter_queue: String,
+    pub retry_policy: RetryPolicy,
+}
+
+impl DeliveryTopology {
+    pub fn new(queue: &str, retry_policy: RetryPolicy) -> Self {
+        DeliveryTopology {
+            queue: queue.to_string(),
+            dead_letter_exchange: format!("{}.dlx", queue),
+            dead_letter_queue: format!("{}.dead", queue),
+            retry_policy,
+        }
+    }
+
+    pub fn retry_queue(&self, attempt: u32) -> String {
+        format!("{}.retry.{}", self.queue, attempt)
+    }
+
+    pub fn disposition(&self, response: &ResponseEnvelope, retry_count: u32) -> Disposition {
+        let error_code = match response.error_code {
+            Some(error_code) => error_code,
+            None => return Disposition::Ack,
+        };
+
+        if error_code.is_transient() && retry_count < self.retry_policy.max_retries {
+            return Disposition::Retry { attempt: retry_count + 1 };
+        }
+
+        return Disposition::DeadLetter;
+    }
+
+    /// Declares the dead-letter exchange and queue and the retry queues. The request queue itself is
+    /// declared by the caller, so an existing queue does not have to be re-created with new arguments.
+    pub async fn declare(&self, channel: &Channel) -> Result<(), String> {
+        let exchange_args = ExchangeDeclareArguments::of_type(&self.dead_letter_exchange, ExchangeType::Direct)
+            .durable(true)
+            .finish();
+        if let Err(err) = channel.exchange_declare(exchange_args).await {
+            return Err(format!("Could not declare exchange {}: {}", self.dead_letter_exchange, err));
+        }
+
+        let queue_args = QueueDeclareArguments::durable_client_named(&self.dead_letter_queue).finish();
+        if let Err(err) = channel.queue_declare(queue_args).await {
+            return Err(format!("Could not declare queue {}: {}", self.dead_letter_queue, err));
+        }
+        let bind_args = QueueBindArguments::new(&self.dead_letter_queue, &self.dead_letter_exchange, &self.queue);
+        if let Err(err) = channel.queue_bind(bind_args).await {
+            return Err(format!("Could not bind queue {}: {}", self.dead_letter_queue, err));
+        }
+
+        for attempt in 1..=self.retry_policy.max_retries {
+            let retry_queue = self.retry_queue(attempt);
+            let queue_args = QueueDeclareArguments::durable_client_named(&retry_queue)
+                .arguments(self.retry_queue_arguments(attempt))
+                .finish();
+            if let Err(err) = channel.queue_declare(queue_args).await {
+                return Err(format!("Could not declare queue {}: {}", retry_queue, err));
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn retry_queue_arguments(&self, attempt: u32) -> FieldTable {
+        let delay = self.retry_policy.delay(attempt).as_millis() as i64;
+
+        let mut arguments = FieldTable::new();
+        arguments.insert(field_name("x-message-ttl"), FieldValue::l(delay));
+        // Through the default exchange, straight back into the request queue
+        arguments.insert(field_name("x-dead-letter-exchange"), FieldValue::S("".try_into().unwrap()));
+        arguments.insert(
+            field_name("x-dead-letter-routing-key"),
+            FieldValue::S(self.queue.as_str().try_into().unwrap()),
+        );
+
+        return arguments;
+    }
+
+    /// The request again, headed for the retry queue of `attempt` with the retry count bumped.
+    pub fn retry_message(&self, properties: &BasicProperties, attempt: u32) -> (BasicPublishArguments, BasicProperties) {
+        let mut headers = properties.headers().cloned().unwrap_or_default();
+        headers.insert(field_name(RETRY_COUNT_HEADER), FieldValue::l(attempt as i64));
+
+        let properties = properties
+            .clone()
+            .with_headers(headers)
+            .with_delivery_mode(PERSISTENT_DELIVERY_MODE)
+            .finish();
+
+        return (BasicPublishArguments::new("", &self.retry_queue(attempt)), properties);
+    }
+
+    /// The request with the error that stopped it attached as headers, headed for the dead-letter exchange.
+    pub fn dead_letter_message(
+        &self,
+        properties: &BasicProperties,
+        response: &ResponseEnvelope,
+    ) -> (BasicPublishArguments, BasicProperties) {
+        let mut headers = properties.headers().cloned().unwrap_or_default();
+        if let Some(error_code) = response.error_code {
+            headers.insert(
+                field_name(ERROR_CODE_HEADER),
+                FieldValue::S(error_code.as_str().try_into().unwrap()),
+            );
+        }
+        if let Ok(message) = response.message.as_str().try_into() {
+            headers.insert(field_name(ERROR_MESSAGE_HEADER), FieldValue::S(message));
+        }
+
+        let properties = properties
+            .clone()
+            .with_headers(headers)
+            .with_delivery_mode(PERSISTENT_DELIVERY_MODE)
+            .finish();
+
+        return (
+            BasicPublishArguments::new(&self.dead_letter_exchange, &self.queue),
+            properties,
+        );
+    }
+}
+
+/// How many times the request was retried already, 0 for a first delivery.
+pub fn retry_count(properties: &BasicProperties) -> u32 {
+    let headers = match properties.headers() {
+        Some(headers) => headers,
+        None => return 0,
+    };
+
+    return match headers.get(&field_name(RETRY_COUNT_HEADER)) {
+        Some(FieldValue::l(count)) => (*count).max(0) as u32,
+        Some(FieldValue::I(count)) => (*count).max(0) as u32,
+        Some(FieldValue::i(count)) => *count,
+        _ => 0,
+    };
+}
+
+fn field_name(name: &str) -> FieldName {
+    name.try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_handler::response::{CommandError, CommandOutput, ErrorCode, ResponseEnvelope};
+
+    fn error(code: ErrorCode) -> ResponseEnvelope {
+        ResponseEnvelope::from_result(Some("approve_proposal"), Err(CommandError::new(code, "failed")))
+    }
+
+    #[test]
+    fn disposition_by_error_and_retry_count() {
+        let topology = DeliveryTopology::new("request.rs", RetryPolicy { max_retries: 2, base_delay: Duration::from_secs(1) });
+
+        let ok = ResponseEnvelope::from_result(Some("delete_user"), Ok(CommandOutput::message("deleted")));
+        assert_eq!(Disposition::Ack, topology.disposition(&ok, 0));
+
+        assert_eq!(Disposition::Retry { attempt: 1 }, topology.disposition(&error(ErrorCode::TransientFailure), 0));
+        assert_eq!(Disposition::Retry { attempt: 2 }, topology.disposition(&error(ErrorCode::TransientFailure), 1));
+        assert_eq!(Disposition::DeadLetter, topology.disposition(&error(ErrorCode::TransientFailure), 2));
+        assert_eq!(Disposition::DeadLetter, topology.disposition(&error(ErrorCode::InvalidPayload), 0));
+
+        assert_eq!(Duration::from_secs(1), topology.retry_policy.delay(1));
+        assert_eq!(Duration::from_secs(4), topology.retry_policy.delay(3));
+        assert_eq!("request.rs.retry.2", topology.retry_queue(2));
+    }
+
+    #[test]
+    fn retry_and_dead_letter_headers() {
+        let topology = DeliveryTopology::new("request.rs", RetryPolicy::default());
+        let request = BasicProperties::default().with_correlation_id("42").finish();
+        assert_eq!(0, retry_count(&request));
+
+        let (args, retried) = topology.retry_message(&request, 1);
+        assert_eq!("", args.exchange);
+        assert_eq!("request.rs.retry.1", args.routing_key);
+        assert_eq!(1, retry_count(&retried));
+        assert_eq!(Some(&"42".to_string()), retried.correlation_id());
+        assert_eq!(Some(PERSISTENT_DELIVERY_MODE), retried.delivery_mode());
+
+        let (args, dead) = topology.dead_letter_message(&retried, &error(ErrorCode::InvalidPayload));
+        assert_eq!("request.rs.dlx", args.exchange);
+        assert_eq!("request.rs", args.routing_key);
+        let headers = dead.headers().unwrap();
+        assert_eq!(
+            Some(&FieldValue::S("INVALID_PAYLOAD".try_into().unwrap())),
+            headers.get(&field_name(ERROR_CODE_HEADER))
+        );
+        assert_eq!(
+            Some(&FieldValue::S("failed".try_into().unwrap())),
+            headers.get(&field_name(ERROR_MESSAGE_HEADER))
+        );
+        assert_eq!(1, retry_count(&dead));
+        assert_eq!(Some(PERSISTENT_DELIVERY_MODE), dead.delivery_mode());
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::request_handler::response::{CommandError, ErrorCode};
+use crate::request_handler::validation::{schema_violations, Violation};
+
+/// Schema version of bodies sent without an envelope, as every command had before envelopes existed.
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+/// Who sent a request and when, taken from its envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestMetadata {
+    pub message_id: String,
+    pub issued_at: DateTime<Utc>,
+    pub actor: String,
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawEnvelope {
+    schema_version: u32,
+    message_id: String,
+    issued_at: String,
+    actor: String,
+    idempotency_key: Option<String>,
+    payload: Value,
+}
+
+/// A request body: the command payload in the schema version it was written for, with its metadata
+/// when it came in an envelope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandRequest {
+    pub schema_version: u32,
+    pub metadata: Option<RequestMetadata>,
+    pub payload: Value,
+}
+
+impl CommandRequest {
+    pub fn envelope_schema() -> Value {
+        json!({
+            "schema_version": "unsigned integer",
+            "message_id": "string",
+            "issued_at": "string",
+            "actor": "string",
+            "idempotency_key": "string, optional",
+            "payload": "object"
+        })
+    }
+
+    /// Reads an envelope, recognised by its `schema_version`, or a bare legacy payload.
+    pub fn parse(raw_json: &str) -> Result<CommandRequest, CommandError> {
+        let value: Value = match serde_json::from_str(raw_json) {
+            Ok(value) => value,
+            Err(err) => {
+                let violation = Violation::new("$", Some("object"), format!("Malformed JSON: {}", err));
+                return Err(malformed(vec![violation]));
+            }
+        };
+
+        let is_envelope = matches!(&value, Value::Object(object) if object.contains_key("schema_version"));
+        if !is_envelope {
+            return Ok(CommandRequest {
+                schema_version: LEGACY_SCHEMA_VERSION,
+                metadata: None,
+                payload: value,
+            });
+        }
+
+        let violations = schema_violations(&CommandRequest::envelope_schema(), &value);
+        if !violations.is_empty() {
+            return Err(malformed(violations));
+        }
+        let envelope: RawEnvelope = match serde_json::from_value(value) {
+            Ok(envelope) => envelope,
+            Err(err) => return Err(malformed(vec![Violation::new("$", None, err.to_string())])),
+        };
+
+        let issued_at = match DateTime::parse_from_rfc3339(&envelope.issued_at) {
+            Ok(issued_at) => issued_at.with_timezone(&Utc),
+            Err(_) => {
+                let violation = Violation::new(
+                    "$.issued_at",
+                    Some("RFC 3339 timestamp"),
+                    format!("Invalid timestamp: {}", envelope.issued_at),
+                );
+                return Err(malformed(vec![violation]));
+            }
+        };
+
+        Ok(CommandRequest {
+            schema_version: envelope.schema_version,
+            metadata: Some(RequestMetadata {
+                message_id: envelope.message_id,
+                issued_at,
+                actor: envelope.actor,
+                idempotency_key: envelope.idempotency_key,
+            }),
+            payload: envelope.payload,
+        })
+    }
+
+    pub fn message_id(&self) -> Option<&str> {
+        self.metadata.as_ref().map(|metadata| metadata.message_id.as_str())
+    }
+}
+
+fn malformed(violations: Vec<Violation>) -> CommandError {
+    let mut error = CommandError::new(
+        ErrorCode::MalformedMessage,
+        format!("Invalid request envelope: {} violation(s)", violations.len()),
+    );
+    error.violations = violations;
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_and_legacy_bodies() {
+        let request = CommandRequest::parse(r#"{"employee_id": 1}"#).unwrap();
+        assert_eq!(LEGACY_SCHEMA_VERSION, request.schema_version);
+        assert_eq!(None, request.metadata);
+        assert_eq!(json!({ "employee_id": 1 }), request.payload);
+
+        let request = CommandRequest::parse(
+            r#"{
+                "schema_version": 2,
+                "message_id": "b7c1",
+                "issued_at": "2024-06-01T12:00:00+02:00",
+                "actor": "backend",
+                "payload": { "employee_id": 1 }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(2, request.schema_version);
+        assert_eq!(Some("b7c1"), request.message_id());
+        let metadata = request.metadata.unwrap();
+        assert_eq!("2024-06-01T10:00:00+00:00", metadata.issued_at.to_rfc3339());
+        assert_eq!(None, metadata.idempotency_key);
+
+        let err = CommandRequest::parse(
+            r#"{"schema_version": 2, "message_id": "b7c1", "issued_at": "yesterday", "actor": "backend", "payload": {}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(ErrorCode::MalformedMessage, err.code);
+        assert_eq!("$.issued_at", err.violations[0].path);
+
+        let err = CommandRequest::parse(r#"{"schema_version": 2, "payload": []}"#).unwrap_err();
+        let paths: Vec<&str> = err.violations.iter().map(|violation| violation.path.as_str()).collect();
+        assert_eq!(vec!["$.actor", "$.issued_at", "$.message_id", "$.payload"], paths);
+    }
+}
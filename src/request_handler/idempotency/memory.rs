@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::request_handler::idempotency::{lease_expired, Claim, IdempotencyError, IdempotencyStore};
+use crate::request_handler::response::ResponseEnvelope;
+
+struct Record {
+    command: String,
+    claimed_at: DateTime<Utc>,
+    response: Option<ResponseEnvelope>,
+}
+
+/// Keeps outcomes in the process, so duplicates are only caught until it restarts.
+/// Meant for tests and single instance deployments without `IDEMPOTENCY_DB`.
+pub struct MemoryIdempotencyStore {
+    lease: Duration,
+    records: Mutex<HashMap<String, Record>>,
+}
+
+impl MemoryIdempotencyStore {
+    pub fn new(lease: Duration) -> Self {
+        MemoryIdempotencyStore {
+            lease,
+            records: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn records(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, Record>>, IdempotencyError> {
+        return match self.records.lock() {
+            Ok(records) => Ok(records),
+            Err(..) => Err(IdempotencyError::Storage("Lock poisoned".to_string())),
+        };
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for MemoryIdempotencyStore {
+    async fn claim(&self, key: &str, command: &str) -> Result<Claim, IdempotencyError> {
+        let now = Utc::now();
+        let mut records = self.records()?;
+
+        if let Some(record) = records.get(key) {
+            if record.command != command {
+                return Ok(Claim::OtherCommand { command: record.command.clone() });
+            }
+            match &record.response {
+                Some(response) => return Ok(Claim::Completed(response.clone())),
+                None if !lease_expired(record.claimed_at, self.lease, now) => {
+                    return Ok(Claim::InProgress { since: record.claimed_at })
+                }
+                None => {}
+            }
+        }
+
+        records.insert(key.to_string(), Record { command: command.to_string(), claimed_at: now, response: None });
+        return Ok(Claim::Acquired);
+    }
+
+    fn lease(&self) -> Duration {
+        self.lease
+    }
+
+    async fn renew(&self, key: &str) -> Result<(), IdempotencyError> {
+        let mut records = self.records()?;
+        if let Some(record) = records.get_mut(key) {
+            if record.response.is_none() {
+                record.claimed_at = Utc::now();
+            }
+        }
+
+        return Ok(());
+    }
+
+    async fn complete(&self, key: &str, response: &ResponseEnvelope) -> Result<(), IdempotencyError> {
+        let mut records = self.records()?;
+        let (command, claimed_at) = match records.get(key) {
+            Some(record) => (record.command.clone(), record.claimed_at),
+            None => (response.command.clone().unwrap_or_default(), Utc::now()),
+        };
+
+        records.insert(key.to_string(), Record { command, claimed_at, response: Some(response.clone()) });
+        return Ok(());
+    }
+
+    async fn release(&self, key: &str) -> Result<(), IdempotencyError> {
+        let mut records = self.records()?;
+        if matches!(records.get(key), Some(Record { response: None, .. })) {
+            records.remove(key);
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_handler::idempotency::tests::store_contract;
+
+    #[tokio::test]
+    async fn memory_store_contract() {
+        let store = MemoryIdempotencyStore::new(Duration::from_secs(60));
+        let expiring = MemoryIdempotencyStore::new(Duration::ZERO);
+
+        store_contract(&store, &expiring).await;
+    }
+}
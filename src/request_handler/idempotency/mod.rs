@@ -0,0 +1,207 @@
+pub mod memory;
+pub mod sqlite;
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::request_handler::envelope::CommandRequest;
+use crate::request_handler::idempotency::memory::MemoryIdempotencyStore;
+use crate::request_handler::idempotency::sqlite::SqliteIdempotencyStore;
+use crate::request_handler::response::{CommandError, ResponseEnvelope};
+
+/// How long a claim holds off duplicates without being renewed before another delivery may take over,
+/// in case the process handling the request died.
+pub const DEFAULT_LEASE: Duration = Duration::from_secs(120);
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyError {
+    #[error("Idempotency store failed: {0}")]
+    Storage(String),
+    #[error("Stored response of {0} could not be read: {1}")]
+    CorruptResponse(String, String),
+}
+
+impl From<IdempotencyError> for CommandError {
+    fn from(err: IdempotencyError) -> Self {
+        // The request was not run, so it can safely be tried again once the store is back
+        CommandError::transient(err.to_string())
+    }
+}
+
+/// What the store knew about a key when it was claimed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Claim {
+    /// First delivery, or a takeover after the lease of a previous claim ran out; run the command
+    Acquired,
+    /// Another delivery claimed the key `since` and has not finished yet
+    InProgress { since: DateTime<Utc> },
+    /// The command already ran, reply with its response
+    Completed(ResponseEnvelope),
+    /// The key belongs to a request for another `command`, so this one is refused rather than answered
+    /// with an unrelated response
+    OtherCommand { command: String },
+}
+
+/// Outcomes of requests by idempotency key. A key is claimed before its command runs, then either
+/// completed with the response or released when the request will be tried again. A key stays bound to
+/// the command that first claimed it.
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    async fn claim(&self, key: &str, command: &str) -> Result<Claim, IdempotencyError>;
+
+    /// How long a claim lasts unless renewed.
+    fn lease(&self) -> Duration;
+
+    /// Starts the lease of a claim still in progress over, while its command is running.
+    async fn renew(&self, key: &str) -> Result<(), IdempotencyError>;
+
+    async fn complete(&self, key: &str, response: &ResponseEnvelope) -> Result<(), IdempotencyError>;
+
+    /// Forgets a claim without an outcome, so the next delivery runs the command again.
+    async fn release(&self, key: &str) -> Result<(), IdempotencyError>;
+}
+
+/// Key a request is deduplicated by: its `idempotency_key`, or else its `message_id`.
+/// Bodies without an envelope carry neither and are never deduplicated.
+pub fn idempotency_key(request: &CommandRequest) -> Option<&str> {
+    let metadata = request.metadata.as_ref()?;
+
+    return match metadata.idempotency_key.as_deref() {
+        Some(key) => Some(key),
+        None => Some(metadata.message_id.as_str()),
+    };
+}
+
+/// Runs `command` while renewing the claim on `key` every third of the lease, so a command waiting on
+/// slow Solana confirmations is not taken over by a redelivery and run twice.
+pub async fn with_heartbeat<F: Future>(idempotency: &dyn IdempotencyStore, key: &str, command: F) -> F::Output {
+    let period = idempotency.lease() / 3;
+    if period.is_zero() {
+        return command.await;
+    }
+
+    tokio::pin!(command);
+    let mut heartbeat = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    loop {
+        tokio::select! {
+            output = &mut command => return output,
+            _ = heartbeat.tick() => {
+                if let Err(err) = idempotency.renew(key).await {
+                    eprintln!("[{:?} RABBITMQ ERROR] Could not renew the claim of {}: {}", Utc::now(), key, err);
+                }
+            }
+        }
+    }
+}
+
+/// The store named by `IDEMPOTENCY_DB`: a SQLite database at that path, or memory when it is unset.
+pub fn store_from_env() -> Result<Arc<dyn IdempotencyStore>, IdempotencyError> {
+    return match std::env::var("IDEMPOTENCY_DB") {
+        Ok(path) => Ok(Arc::new(SqliteIdempotencyStore::open(&path, DEFAULT_LEASE)?)),
+        Err(..) => Ok(Arc::new(MemoryIdempotencyStore::new(DEFAULT_LEASE))),
+    };
+}
+
+/// Whether a claim made at `claimed_at` was abandoned by now.
+fn lease_expired(claimed_at: DateTime<Utc>, lease: Duration, now: DateTime<Utc>) -> bool {
+    return match chrono::Duration::from_std(lease) {
+        Ok(lease) => claimed_at + lease <= now,
+        Err(..) => false,
+    };
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::request_handler::response::{CommandOutput, ErrorCode};
+
+    /// Behaviour every store shares. `expiring` hands out claims with a lease of 0.
+    pub async fn store_contract(store: &dyn IdempotencyStore, expiring: &dyn IdempotencyStore) {
+        assert_eq!(Claim::Acquired, store.claim("b7c1", "transfer_from_vault").await.unwrap());
+        assert!(matches!(
+            store.claim("b7c1", "transfer_from_vault").await.unwrap(),
+            Claim::InProgress { .. }
+        ));
+
+        let response = ResponseEnvelope::from_result(
+            Some("transfer_from_vault"),
+            Ok(CommandOutput::message("Transaction #3 proposed").with_result(json!({ "transaction_index": 3 }))),
+        )
+        .with_message_id(Some("b7c1"));
+        store.complete("b7c1", &response).await.unwrap();
+        assert_eq!(Claim::Completed(response.clone()), store.claim("b7c1", "transfer_from_vault").await.unwrap());
+        store.renew("b7c1").await.unwrap();
+        assert_eq!(Claim::Completed(response), store.claim("b7c1", "transfer_from_vault").await.unwrap());
+
+        assert_eq!(Claim::Acquired, store.claim("d4e2", "create_dao").await.unwrap());
+        store.release("d4e2").await.unwrap();
+        assert_eq!(Claim::Acquired, store.claim("d4e2", "create_dao").await.unwrap());
+
+        let failed = ResponseEnvelope::from_result(Some("create_dao"), Err(CommandError::failed("Threshold too high")));
+        store.complete("d4e2", &failed).await.unwrap();
+        match store.claim("d4e2", "create_dao").await.unwrap() {
+            Claim::Completed(response) => assert_eq!(Some(ErrorCode::CommandFailed), response.error_code),
+            claim => panic!("Expected the stored failure, got {:?}", claim),
+        }
+
+        // An abandoned claim is taken over, a completed one never expires
+        assert_eq!(Claim::Acquired, expiring.claim("f9a0", "approve_proposal").await.unwrap());
+        assert_eq!(Claim::Acquired, expiring.claim("f9a0", "approve_proposal").await.unwrap());
+        expiring.complete("f9a0", &failed).await.unwrap();
+        assert_eq!(Claim::Completed(failed), expiring.claim("f9a0", "approve_proposal").await.unwrap());
+
+        // A key reused by another command is refused, whatever state its claim is in
+        let other = |command: &str| Claim::OtherCommand { command: command.to_string() };
+        assert_eq!(other("transfer_from_vault"), store.claim("b7c1", "create_dao").await.unwrap());
+        assert_eq!(Claim::Acquired, expiring.claim("a3b8", "execute_proposal").await.unwrap());
+        assert_eq!(other("execute_proposal"), expiring.claim("a3b8", "cancel_proposal").await.unwrap());
+        assert_eq!(Claim::Acquired, expiring.claim("a3b8", "execute_proposal").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn heartbeat_keeps_the_claim_while_the_command_runs() {
+        let store = MemoryIdempotencyStore::new(Duration::from_millis(90));
+        assert_eq!(Claim::Acquired, store.claim("b7c1", "approve_proposal").await.unwrap());
+
+        // Still claimed well past the lease, as long as the command runs
+        let command = with_heartbeat(&store, "b7c1", tokio::time::sleep(Duration::from_millis(300)));
+        let duplicate = async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            store.claim("b7c1", "approve_proposal").await.unwrap()
+        };
+        let ((), claim) = tokio::join!(command, duplicate);
+        assert!(matches!(claim, Claim::InProgress { .. }), "{:?}", claim);
+
+        // Without renewals the claim runs out
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(Claim::Acquired, store.claim("b7c1", "approve_proposal").await.unwrap());
+    }
+
+    #[test]
+    fn key_prefers_idempotency_key() {
+        let legacy = CommandRequest::parse(r#"{"employee_id": 1}"#).unwrap();
+        assert_eq!(None, idempotency_key(&legacy));
+
+        let envelope = json!({
+            "schema_version": 1,
+            "message_id": "b7c1",
+            "issued_at": "2024-06-01T12:00:00Z",
+            "actor": "backend",
+            "payload": {}
+        });
+        let request = CommandRequest::parse(&envelope.to_string()).unwrap();
+        assert_eq!(Some("b7c1"), idempotency_key(&request));
+
+        let mut envelope = envelope;
+        envelope["idempotency_key"] = json!("transfer-42");
+        let request = CommandRequest::parse(&envelope.to_string()).unwrap();
+        assert_eq!(Some("transfer-42"), idempotency_key(&request));
+    }
+}
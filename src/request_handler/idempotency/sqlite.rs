@@ -0,0 +1,205 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+
+use crate::request_handler::idempotency::{Claim, IdempotencyError, IdempotencyStore};
+use crate::request_handler::response::ResponseEnvelope;
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS idempotency_records (
+    key TEXT PRIMARY KEY,
+    command TEXT NOT NULL,
+    claimed_at INTEGER NOT NULL,
+    response TEXT
+)";
+
+/// Keeps outcomes in a SQLite database, so duplicates are caught across restarts and by every
+/// instance sharing the file. Timestamps are stored in milliseconds since the epoch.
+/// Statements run on the blocking thread pool, so a busy database does not stall the consumers.
+pub struct SqliteIdempotencyStore {
+    lease: Duration,
+    connection: Arc<Mutex<Connection>>,
+}
+
+fn storage_error(err: rusqlite::Error) -> IdempotencyError {
+    IdempotencyError::Storage(err.to_string())
+}
+
+impl SqliteIdempotencyStore {
+    /// Opens or creates the database at `path`, `:memory:` for a private in-memory one.
+    pub fn open(path: &str, lease: Duration) -> Result<Self, IdempotencyError> {
+        let connection = match Connection::open(path) {
+            Ok(connection) => connection,
+            Err(err) => return Err(storage_error(err)),
+        };
+        if let Err(err) = connection.execute(CREATE_TABLE, []) {
+            return Err(storage_error(err));
+        }
+
+        return Ok(SqliteIdempotencyStore {
+            lease,
+            connection: Arc::new(Mutex::new(connection)),
+        });
+    }
+
+    /// Runs `statements` with the connection on the blocking thread pool.
+    async fn run<T, F>(&self, statements: F) -> Result<T, IdempotencyError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, IdempotencyError> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            let connection = match connection.lock() {
+                Ok(connection) => connection,
+                Err(..) => return Err(IdempotencyError::Storage("Lock poisoned".to_string())),
+            };
+            statements(&connection)
+        });
+
+        return match task.await {
+            Ok(result) => result,
+            Err(err) => Err(IdempotencyError::Storage(err.to_string())),
+        };
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for SqliteIdempotencyStore {
+    async fn claim(&self, key: &str, command: &str) -> Result<Claim, IdempotencyError> {
+        let now = Utc::now().timestamp_millis();
+        let lease = i64::try_from(self.lease.as_millis()).unwrap_or(i64::MAX);
+        let (key, command) = (key.to_string(), command.to_string());
+
+        self.run(move |connection| {
+            // Takes the key when it is new or its claim for the same command was abandoned, in one statement
+            // so concurrent instances can not both win
+            let claimed = connection
+                .execute(
+                    "INSERT INTO idempotency_records (key, command, claimed_at, response) VALUES (?1, ?2, ?3, NULL)
+                     ON CONFLICT(key) DO UPDATE SET claimed_at = excluded.claimed_at
+                     WHERE idempotency_records.response IS NULL AND idempotency_records.claimed_at <= ?4
+                     AND idempotency_records.command = excluded.command",
+                    params![key, command, now, now.saturating_sub(lease)],
+                )
+                .map_err(storage_error)?;
+            if claimed == 1 {
+                return Ok(Claim::Acquired);
+            }
+
+            let (claimed_command, claimed_at, response): (String, i64, Option<String>) = connection
+                .query_row(
+                    "SELECT command, claimed_at, response FROM idempotency_records WHERE key = ?1",
+                    params![key],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .map_err(storage_error)?;
+            if claimed_command != command {
+                return Ok(Claim::OtherCommand { command: claimed_command });
+            }
+
+            return match response {
+                Some(response) => match serde_json::from_str(&response) {
+                    Ok(response) => Ok(Claim::Completed(response)),
+                    Err(err) => Err(IdempotencyError::CorruptResponse(key, err.to_string())),
+                },
+                None => Ok(Claim::InProgress {
+                    since: DateTime::from_timestamp_millis(claimed_at).unwrap_or_default(),
+                }),
+            };
+        })
+        .await
+    }
+
+    fn lease(&self) -> Duration {
+        self.lease
+    }
+
+    async fn renew(&self, key: &str) -> Result<(), IdempotencyError> {
+        let now = Utc::now().timestamp_millis();
+        let key = key.to_string();
+
+        self.run(move |connection| {
+            connection
+                .execute(
+                    "UPDATE idempotency_records SET claimed_at = ?2 WHERE key = ?1 AND response IS NULL",
+                    params![key, now],
+                )
+                .map_err(storage_error)?;
+
+            return Ok(());
+        })
+        .await
+    }
+
+    async fn complete(&self, key: &str, response: &ResponseEnvelope) -> Result<(), IdempotencyError> {
+        let body = match serde_json::to_string(response) {
+            Ok(body) => body,
+            Err(err) => return Err(IdempotencyError::CorruptResponse(key.to_string(), err.to_string())),
+        };
+        let command = response.command.clone().unwrap_or_default();
+        let key = key.to_string();
+
+        self.run(move |connection| {
+            connection
+                .execute(
+                    "INSERT INTO idempotency_records (key, command, claimed_at, response) VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT(key) DO UPDATE SET response = excluded.response",
+                    params![key, command, Utc::now().timestamp_millis(), body],
+                )
+                .map_err(storage_error)?;
+
+            return Ok(());
+        })
+        .await
+    }
+
+    async fn release(&self, key: &str) -> Result<(), IdempotencyError> {
+        let key = key.to_string();
+
+        self.run(move |connection| {
+            connection
+                .execute(
+                    "DELETE FROM idempotency_records WHERE key = ?1 AND response IS NULL",
+                    params![key],
+                )
+                .map_err(storage_error)?;
+
+            return Ok(());
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_handler::idempotency::tests::store_contract;
+    use crate::request_handler::response::CommandError;
+
+    #[tokio::test]
+    async fn sqlite_store_contract() {
+        let store = SqliteIdempotencyStore::open(":memory:", Duration::from_secs(60)).unwrap();
+        let expiring = SqliteIdempotencyStore::open(":memory:", Duration::ZERO).unwrap();
+
+        store_contract(&store, &expiring).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_store_survives_reopening() {
+        let path = std::env::temp_dir().join(format!("idempotency-{}.db", std::process::id()));
+        let path = path.to_str().unwrap();
+        let response = ResponseEnvelope::from_result(Some("get_dao"), Err(CommandError::failed("Unknown DAO")));
+
+        let store = SqliteIdempotencyStore::open(path, Duration::from_secs(60)).unwrap();
+        assert_eq!(Claim::Acquired, store.claim("b7c1", "get_dao").await.unwrap());
+        store.complete("b7c1", &response).await.unwrap();
+        drop(store);
+
+        let store = SqliteIdempotencyStore::open(path, Duration::from_secs(60)).unwrap();
+        assert_eq!(Claim::Completed(response), store.claim("b7c1", "get_dao").await.unwrap());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct Inner {
+    count: AtomicUsize,
+    closed: AtomicBool,
+    idle: Notify,
+}
+
+/// Counts the deliveries being handled, so shutdown can wait for them to finish.
+#[derive(Clone, Default)]
+pub struct InFlight {
+    inner: Arc<Inner>,
+}
+
+/// Held while one delivery is handled.
+pub struct InFlightGuard {
+    inner: Arc<Inner>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.inner.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.inner.idle.notify_waiters();
+        }
+    }
+}
+
+impl InFlight {
+    pub fn new() -> Self {
+        InFlight::default()
+    }
+
+    /// Registers a delivery, `None` once shutdown started and new deliveries must be left alone.
+    pub fn begin(&self) -> Option<InFlightGuard> {
+        // Counted before the check, so `wait_idle` can not miss a delivery that slipped in
+        self.inner.count.fetch_add(1, Ordering::AcqRel);
+        let guard = InFlightGuard { inner: self.inner.clone() };
+        if self.inner.closed.load(Ordering::Acquire) {
+            return None;
+        }
+
+        return Some(guard);
+    }
+
+    /// Refuses deliveries from now on.
+    pub fn close(&self) {
+        self.inner.closed.store(true, Ordering::Release);
+    }
+
+    pub fn count(&self) -> usize {
+        self.inner.count.load(Ordering::Acquire)
+    }
+
+    /// Waits up to `timeout` for the deliveries being handled, `false` when some are still running.
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let idle = async {
+            loop {
+                let notified = self.inner.idle.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                if self.count() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+
+        return tokio::time::timeout(timeout, idle).await.is_ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn waits_for_deliveries_being_handled() {
+        let in_flight = InFlight::new();
+        assert!(in_flight.wait_idle(Duration::ZERO).await);
+
+        let guard = in_flight.begin().unwrap();
+        in_flight.close();
+        assert!(in_flight.begin().is_none());
+        assert_eq!(1, in_flight.count());
+        assert!(!in_flight.wait_idle(Duration::from_millis(10)).await);
+
+        let handler = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(guard);
+        });
+        assert!(in_flight.wait_idle(Duration::from_secs(5)).await);
+        handler.await.unwrap();
+    }
+}
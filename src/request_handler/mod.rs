@@ -1,2 +1,11 @@
+pub mod connection;
 pub mod consumers;
+pub mod delivery;
+pub mod envelope;
+pub mod idempotency;
+pub mod in_flight;
 pub mod processor;
+pub mod publisher;
+pub mod response;
+pub mod topology;
+pub mod validation;
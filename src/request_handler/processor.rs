@@ -1,62 +1,69 @@
-use std::env;
+use std::sync::Arc;
 
-use amqprs::callbacks::{DefaultChannelCallback, DefaultConnectionCallback};
-use amqprs::channel::{BasicConsumeArguments, QueueDeclareArguments};
-use amqprs::connection::{Connection, OpenConnectionArguments};
 use tokio::signal;
-use tokio::sync::Notify;
 
-use crate::request_handler::consumers::RabbitMQConsumer;
+use crate::request_handler::connection::{BrokerConfig, ConnectionManager};
+use crate::request_handler::consumers::registry::CommandRegistry;
+use crate::request_handler::idempotency::IdempotencyStore;
 
+/// Completes on Ctrl+C or, on Unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = signal::ctrl_c().await {
+            eprintln!("[{:?} RABBITMQ ERROR] Failed to listen for ctrl+c because of {}", chrono::Utc::now(), err);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                eprintln!("[{:?} RABBITMQ ERROR] Failed to listen for SIGTERM because of {}", chrono::Utc::now(), err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    println!("[{:?} RABBITMQ INFO] Shutting down", chrono::Utc::now());
+}
+
+/// Serves `registry` on the configured queue, reconnecting whenever the broker goes away, until a
+/// shutdown signal arrives.
 pub async fn start(
-    host: &str,
-    port: u16,
-    username: &str,
-    password: &str,
-    routing_key: String,
-    consumer_tag: &str,
+    config: BrokerConfig,
+    registry: CommandRegistry,
+    idempotency: Arc<dyn IdempotencyStore>,
 ) -> Result<(), String> {
-    // let connection_arguments =
-    //     OpenConnectionArguments::try_from(addr.as_str()).expect("Could not parse RABBITMQ_URI");
-    let connection_arguments = OpenConnectionArguments::new(host, port, username, username);
-
-    let connection = Connection::open(&connection_arguments)
-        .await
-        .expect("Connection to RabbitMQ failed");
-
-    connection
-        .register_callback(DefaultConnectionCallback)
-        .await
-        .unwrap();
-
-    // open a channel on the connection
-    let channel = connection.open_channel(None).await.unwrap();
-    channel
-        .register_callback(DefaultChannelCallback)
-        .await
-        .unwrap();
-
-    // declare a queue
-    let (queue_name, _, _) = channel
-        .queue_declare(QueueDeclareArguments::default().queue(routing_key).finish())
-        .await
-        .unwrap()
-        .unwrap();
-
-    //////////////////////////////////////////////////////////////////
-    // start consumer with given name
-    let args = BasicConsumeArguments::new(&queue_name, consumer_tag);
-
-    channel
-        .basic_consume(RabbitMQConsumer::new(), args)
-        .await
-        .unwrap();
-
-    let guard = Notify::new();
-    guard.notified().await;
-
-    return match signal::ctrl_c().await {
-        Ok(()) => Ok(()),
-        Err(err) => Err(format!("Failed to listen for ctrl+c because of {}", err)),
-    };
+    for command in registry.commands() {
+        println!(
+            "[{:?} RABBITMQ INFO] Serving command {} v{} on {} with schema {}",
+            chrono::Utc::now(),
+            command.name,
+            command.version,
+            command.routing_key,
+            command.schema
+        );
+    }
+    for command in config.topology.unbound_commands(&registry) {
+        eprintln!(
+            "[{:?} RABBITMQ ERROR] No binding of {} matches the routing key of {}",
+            chrono::Utc::now(),
+            config.topology.request_exchange,
+            command
+        );
+    }
+
+    let manager = ConnectionManager::new(config, Arc::new(registry), idempotency);
+
+    return manager.run(shutdown_signal()).await;
 }
@@ -0,0 +1,125 @@
+use amqprs::channel::{BasicPublishArguments, Channel};
+use amqprs::BasicProperties;
+
+use crate::request_handler::response::ResponseEnvelope;
+
+/// A response envelope on its way to an exchange.
+#[derive(Debug, Clone)]
+pub struct Publication {
+    pub exchange: String,
+    pub routing_key: String,
+    pub properties: BasicProperties,
+    pub content: Vec<u8>,
+}
+
+/// `delivery_mode` of messages the broker writes to disk, so they survive a restart in durable queues.
+pub const PERSISTENT_DELIVERY_MODE: u8 = 2;
+
+fn json_properties() -> BasicProperties {
+    let mut properties = BasicProperties::default();
+    properties.with_content_type("application/json").with_delivery_mode(PERSISTENT_DELIVERY_MODE);
+    properties
+}
+
+impl Publication {
+    /// The response to a request with `request_properties`. It goes to the `reply_to` queue of the
+    /// request through the default exchange, or else to `response_exchange` under `routing_key`.
+    /// The `correlation_id` of the request is copied so the caller can match the reply to its request.
+    pub fn reply(
+        request_properties: &BasicProperties,
+        envelope: &ResponseEnvelope,
+        response_exchange: &str,
+        routing_key: &str,
+    ) -> Publication {
+        let mut properties = json_properties();
+        if let Some(correlation_id) = request_properties.correlation_id() {
+            properties.with_correlation_id(correlation_id);
+        }
+
+        let (exchange, routing_key) = match request_properties.reply_to() {
+            Some(reply_to) if !reply_to.is_empty() => ("", reply_to.as_str()),
+            _ => (response_exchange, routing_key),
+        };
+
+        Publication {
+            exchange: exchange.to_string(),
+            routing_key: routing_key.to_string(),
+            properties: properties.finish(),
+            content: envelope.to_json(),
+        }
+    }
+
+    /// The event of a command that sent transactions, `None` for failures and queries.
+    pub fn event(envelope: &ResponseEnvelope, event_exchange: &str, routing_key: &str) -> Option<Publication> {
+        if !envelope.is_ok() || envelope.signatures.is_empty() {
+            return None;
+        }
+
+        Some(Publication {
+            exchange: event_exchange.to_string(),
+            routing_key: routing_key.to_string(),
+            properties: json_properties().finish(),
+            content: envelope.to_json(),
+        })
+    }
+}
+
+pub async fn publish(channel: &Channel, publication: Publication) -> Result<(), String> {
+    let args = BasicPublishArguments::new(&publication.exchange, &publication.routing_key);
+
+    return match channel.basic_publish(publication.properties, publication.content, args).await {
+        Ok(()) => Ok(()),
+        Err(err) => Err(format!(
+            "Could not publish to {} under {}: {}",
+            publication.exchange, publication.routing_key, err
+        )),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_handler::response::{CommandError, CommandOutput, ResponseEnvelope};
+    use solana_sdk::signature::Signature;
+
+    #[test]
+    fn reply_follows_request_properties() {
+        let envelope = ResponseEnvelope::from_result(Some("delete_user"), Ok(CommandOutput::message("deleted")));
+
+        let response = Publication::reply(&BasicProperties::default(), &envelope, "dao.responses", "user.delete");
+        assert_eq!("dao.responses", response.exchange);
+        assert_eq!("user.delete", response.routing_key);
+        assert_eq!(None, response.properties.correlation_id());
+
+        let request = BasicProperties::default()
+            .with_reply_to("backend.responses")
+            .with_correlation_id("42")
+            .finish();
+        let reply = Publication::reply(&request, &envelope, "dao.responses", "user.delete");
+        assert_eq!("", reply.exchange);
+        assert_eq!("backend.responses", reply.routing_key);
+        assert_eq!(Some(&"42".to_string()), reply.properties.correlation_id());
+        assert_eq!(Some(&"application/json".to_string()), reply.properties.content_type());
+        assert_eq!(Some(PERSISTENT_DELIVERY_MODE), reply.properties.delivery_mode());
+        assert_eq!(envelope.to_json(), reply.content);
+    }
+
+    #[test]
+    fn events_of_commands_that_sent_transactions() {
+        let query = ResponseEnvelope::from_result(Some("get_dao"), Ok(CommandOutput::message("found")));
+        assert!(Publication::event(&query, "dao.events", "dao.get").is_none());
+
+        let failed = ResponseEnvelope::from_result(Some("dao_create"), Err(CommandError::failed("no funds")));
+        assert!(Publication::event(&failed, "dao.events", "dao.create").is_none());
+
+        let created = ResponseEnvelope::from_result(
+            Some("create_dao"),
+            Ok(CommandOutput::message("created").with_signature(Signature::new_unique())),
+        );
+        let event = Publication::event(&created, "dao.events", "dao.create").unwrap();
+        assert_eq!("dao.events", event.exchange);
+        assert_eq!("dao.create", event.routing_key);
+        assert_eq!(created.to_json(), event.content);
+        assert_eq!(Some(PERSISTENT_DELIVERY_MODE), event.properties.delivery_mode());
+    }
+}
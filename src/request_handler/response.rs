@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_sdk::signature::Signature;
+
+use crate::dao_module::error::DaoServiceError;
+use crate::request_handler::validation::Violation;
+
+/// Machine readable reason a command failed, sent as `error_code`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The message body is not UTF-8 or the `command` header is missing
+    MalformedMessage,
+    UnknownCommand,
+    /// The command has no upgrade path from the `schema_version` of the request
+    UnsupportedSchemaVersion,
+    /// The body does not match the schema of the command
+    InvalidPayload,
+    /// The command was understood but could not be carried out
+    CommandFailed,
+    /// A dependency such as the RPC node was unavailable, the request is retried later
+    TransientFailure,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::MalformedMessage => "MALFORMED_MESSAGE",
+            ErrorCode::UnknownCommand => "UNKNOWN_COMMAND",
+            ErrorCode::UnsupportedSchemaVersion => "UNSUPPORTED_SCHEMA_VERSION",
+            ErrorCode::InvalidPayload => "INVALID_PAYLOAD",
+            ErrorCode::CommandFailed => "COMMAND_FAILED",
+            ErrorCode::TransientFailure => "TRANSIENT_FAILURE",
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        *self == ErrorCode::TransientFailure
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub violations: Vec<Violation>,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        CommandError { code, message: message.into(), violations: Vec::new() }
+    }
+
+    pub fn invalid_payload(message: impl Into<String>) -> Self {
+        CommandError::new(ErrorCode::InvalidPayload, message)
+    }
+
+    /// The body of a `command` request was refused for `violations`.
+    pub fn invalid_request(command: &str, violations: Vec<Violation>) -> Self {
+        let mut error = CommandError::invalid_payload(format!(
+            "Invalid {} request: {} violation(s)",
+            command,
+            violations.len()
+        ));
+        error.violations = violations;
+        error
+    }
+
+    pub fn failed(message: impl Into<String>) -> Self {
+        CommandError::new(ErrorCode::CommandFailed, message)
+    }
+
+    pub fn transient(message: impl Into<String>) -> Self {
+        CommandError::new(ErrorCode::TransientFailure, message)
+    }
+}
+
+impl From<DaoServiceError> for CommandError {
+    fn from(err: DaoServiceError) -> Self {
+        if err.is_transient() {
+            return CommandError::transient(err.to_string());
+        }
+
+        CommandError::failed(err.to_string())
+    }
+}
+
+/// What a successful command hands back to the caller.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandOutput {
+    pub message: String,
+    pub result: Option<Value>,
+    pub signatures: Vec<Signature>,
+}
+
+impl CommandOutput {
+    pub fn message(message: impl Into<String>) -> Self {
+        CommandOutput {
+            message: message.into(),
+            result: None,
+            signatures: Vec::new(),
+        }
+    }
+
+    pub fn with_result(mut self, result: Value) -> Self {
+        self.result = Some(result);
+        self
+    }
+
+    pub fn with_signature(mut self, signature: Signature) -> Self {
+        self.signatures.push(signature);
+        self
+    }
+}
+
+pub type CommandResult = Result<CommandOutput, CommandError>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseStatus {
+    Ok,
+    Error,
+}
+
+/// Body of the reply sent to the `reply_to` queue of a request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResponseEnvelope {
+    pub status: ResponseStatus,
+    /// `None` when the request did not name a command
+    pub command: Option<String>,
+    /// `message_id` of the request envelope, `None` for legacy bodies
+    pub message_id: Option<String>,
+    pub result: Option<Value>,
+    pub error_code: Option<ErrorCode>,
+    pub message: String,
+    /// Transactions sent while handling the request, base58 encoded
+    pub signatures: Vec<String>,
+    /// Why the request was refused, empty unless `error_code` is `INVALID_PAYLOAD` or `MALFORMED_MESSAGE`
+    pub violations: Vec<Violation>,
+}
+
+impl ResponseEnvelope {
+    pub fn from_result(command: Option<&str>, result: CommandResult) -> Self {
+        match result {
+            Ok(output) => ResponseEnvelope {
+                status: ResponseStatus::Ok,
+                command: command.map(str::to_string),
+                message_id: None,
+                result: output.result,
+                error_code: None,
+                message: output.message,
+                signatures: output.signatures.iter().map(Signature::to_string).collect(),
+                violations: Vec::new(),
+            },
+            Err(error) => ResponseEnvelope {
+                status: ResponseStatus::Error,
+                command: command.map(str::to_string),
+                message_id: None,
+                result: None,
+                error_code: Some(error.code),
+                message: error.message,
+                signatures: Vec::new(),
+                violations: error.violations,
+            },
+        }
+    }
+
+    pub fn with_message_id(mut self, message_id: Option<&str>) -> Self {
+        self.message_id = message_id.map(str::to_string);
+        self
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status == ResponseStatus::Ok
+    }
+
+    pub fn to_json(&self) -> Vec<u8> {
+        // Only strings, numbers and `Value`s are serialized, which can not fail
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn envelope_json() {
+        let signature = Signature::new_unique();
+        let output = CommandOutput::message("done")
+            .with_result(json!({ "transaction_index": 3 }))
+            .with_signature(signature);
+
+        let envelope = ResponseEnvelope::from_result(Some("approve_proposal"), Ok(output)).with_message_id(Some("b7c1"));
+        assert_eq!(
+            serde_json::from_slice::<Value>(&envelope.to_json()).unwrap(),
+            json!({
+                "status": "ok",
+                "command": "approve_proposal",
+                "message_id": "b7c1",
+                "result": { "transaction_index": 3 },
+                "error_code": null,
+                "message": "done",
+                "signatures": [signature.to_string()],
+                "violations": []
+            })
+        );
+
+        let envelope = ResponseEnvelope::from_result(None, Err(CommandError::new(ErrorCode::MalformedMessage, "no command")));
+        assert_eq!(
+            serde_json::from_slice::<Value>(&envelope.to_json()).unwrap(),
+            json!({
+                "status": "error",
+                "command": null,
+                "message_id": null,
+                "result": null,
+                "error_code": "MALFORMED_MESSAGE",
+                "message": "no command",
+                "signatures": [],
+                "violations": []
+            })
+        );
+
+        let violation = Violation::new("$.threshold", Some("unsigned integer"), "Missing field threshold");
+        let envelope = ResponseEnvelope::from_result(
+            Some("create_dao"),
+            Err(CommandError::invalid_request("create_dao", vec![violation])),
+        );
+        assert_eq!(
+            serde_json::from_slice::<Value>(&envelope.to_json()).unwrap()["violations"],
+            json!([{
+                "path": "$.threshold",
+                "field": "threshold",
+                "expected": "unsigned integer",
+                "message": "Missing field threshold"
+            }])
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use amqprs::channel::{Channel, ExchangeDeclareArguments, ExchangeType, QueueBindArguments, QueueDeclareArguments};
+
+use crate::request_handler::consumers::registry::CommandRegistry;
+
+/// The exchanges of the service and how the request queue is bound to them.
+///
+/// Requests are published to the `request_exchange` topic exchange under the routing key of their
+/// command, e.g. `proposal.approve`, and reach the request queue through `bindings` such as `proposal.*`.
+/// Responses go to the `response_exchange` and events of commands that sent transactions to the
+/// `event_exchange`, both under the routing key of the command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExchangeTopology {
+    pub request_exchange: String,
+    pub request_queue: String,
+    pub bindings: Vec<String>,
+    pub response_exchange: String,
+    /// Declared and bound to every response when set, for callers that do not use `reply_to`
+    pub response_queue: Option<String>,
+    pub event_exchange: String,
+}
+
+impl Default for ExchangeTopology {
+    fn default() -> Self {
+        ExchangeTopology {
+            request_exchange: "dao.requests".to_string(),
+            request_queue: "request.rs".to_string(),
+            bindings: ["dao.*", "proposal.*", "vault.*", "user.*"].iter().map(|binding| binding.to_string()).collect(),
+            response_exchange: "dao.responses".to_string(),
+            response_queue: None,
+            event_exchange: "dao.events".to_string(),
+        }
+    }
+}
+
+/// Whether `routing_key` matches the binding `pattern`, where `*` stands for one word and `#` for
+/// any number of words, like a topic exchange.
+pub fn topic_matches(pattern: &str, routing_key: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    let words: Vec<&str> = routing_key.split('.').collect();
+
+    return matches_words(&pattern, &words);
+}
+
+fn matches_words(pattern: &[&str], words: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => words.is_empty(),
+        Some((&"#", rest)) => (0..=words.len()).any(|skipped| matches_words(rest, &words[skipped..])),
+        Some((&head, rest)) => match words.split_first() {
+            Some((&word, remaining)) if head == "*" || head == word => matches_words(rest, remaining),
+            _ => false,
+        },
+    }
+}
+
+impl ExchangeTopology {
+    /// Commands of `registry` whose routing key no binding matches, so they are only reachable through
+    /// the default exchange.
+    pub fn unbound_commands(&self, registry: &CommandRegistry) -> Vec<&'static str> {
+        registry
+            .commands()
+            .iter()
+            .filter(|command| !self.bindings.iter().any(|binding| topic_matches(binding, command.routing_key)))
+            .map(|command| command.name)
+            .collect()
+    }
+
+    /// Declares the exchanges, the request queue and its bindings, and the response queue if any.
+    /// Returns the name of the request queue.
+    pub async fn declare(&self, channel: &Channel) -> Result<String, String> {
+        for exchange in [&self.request_exchange, &self.response_exchange, &self.event_exchange] {
+            let args = ExchangeDeclareArguments::of_type(exchange, ExchangeType::Topic)
+                .durable(true)
+                .finish();
+            if let Err(err) = channel.exchange_declare(args).await {
+                return Err(format!("Could not declare exchange {}: {}", exchange, err));
+            }
+        }
+
+        // Durable, so queued requests survive a broker restart. A non-durable queue left by an earlier
+        // version is refused by the broker and has to be deleted once
+        let queue_args = QueueDeclareArguments::durable_client_named(&self.request_queue).finish();
+        let queue_name = match channel.queue_declare(queue_args).await {
+            Ok(Some((queue_name, _, _))) => queue_name,
+            Ok(None) => self.request_queue.clone(),
+            Err(err) => return Err(format!("Could not declare queue {}: {}", self.request_queue, err)),
+        };
+        for binding in &self.bindings {
+            let bind_args = QueueBindArguments::new(&queue_name, &self.request_exchange, binding);
+            if let Err(err) = channel.queue_bind(bind_args).await {
+                return Err(format!("Could not bind queue {} to {}: {}", queue_name, binding, err));
+            }
+        }
+
+        if let Some(response_queue) = &self.response_queue {
+            let queue_args = QueueDeclareArguments::durable_client_named(response_queue).finish();
+            if let Err(err) = channel.queue_declare(queue_args).await {
+                return Err(format!("Could not declare queue {}: {}", response_queue, err));
+            }
+            let bind_args = QueueBindArguments::new(response_queue, &self.response_exchange, "#");
+            if let Err(err) = channel.queue_bind(bind_args).await {
+                return Err(format!("Could not bind queue {}: {}", response_queue, err));
+            }
+        }
+
+        return Ok(queue_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_handler::consumers::dao_commands;
+    use crate::rpc_utils::in_process_bank::InProcessBank;
+    use crate::dao_module::repositories::dao_repository::DaoRepository;
+    use crate::dao_module::services::dao_service::DaoService;
+    use solana_sdk::signature::Keypair;
+    use squads_multisig::squads_multisig_program;
+    use std::sync::Arc;
+
+    #[test]
+    fn topic_patterns() {
+        assert!(topic_matches("dao.*", "dao.create"));
+        assert!(!topic_matches("dao.*", "dao.member.add"));
+        assert!(!topic_matches("dao.*", "proposal.approve"));
+        assert!(topic_matches("dao.#", "dao.member.add"));
+        assert!(topic_matches("#", "vault.transfer"));
+        assert!(topic_matches("*.approve", "proposal.approve"));
+        assert!(topic_matches("proposal.approve", "proposal.approve"));
+    }
+
+    #[test]
+    fn default_bindings_cover_every_command() {
+        let service = DaoService::new(
+            Arc::new(InProcessBank::with_squads(squads_multisig_program::ID)),
+            squads_multisig_program::ID,
+            Keypair::new(),
+            DaoRepository::new(std::env::temp_dir().join("dao-keystore-topology")),
+        );
+        let registry = dao_commands(Arc::new(service));
+
+        assert!(ExchangeTopology::default().unbound_commands(&registry).is_empty());
+
+        let topology = ExchangeTopology {
+            bindings: vec!["dao.*".to_string()],
+            ..ExchangeTopology::default()
+        };
+        assert_eq!(
+            vec![
+                "approve_proposal",
+                "cancel_proposal",
+                "create_proposal",
+                "create_user",
+                "delete_user",
+                "execute_proposal",
+                "recreate_proposal",
+                "transfer_from_vault"
+            ],
+            topology.unbound_commands(&registry)
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_sdk::{pubkey::Pubkey, sanitize::Sanitize, transaction::Transaction};
+
+/// One reason a request body was refused.
+/// `path` locates the value from the root of the body (`$`), e.g. `$.members[0].key`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub path: String,
+    /// Name of the field holding the value, `None` for the body itself
+    pub field: Option<String>,
+    /// What the schema asks for, e.g. `unsigned integer` or `initiate | vote | execute`
+    pub expected: Option<String>,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(path: &str, expected: Option<&str>, message: impl Into<String>) -> Self {
+        Violation {
+            path: path.to_string(),
+            field: field_of(path),
+            expected: expected.map(str::to_string),
+            message: message.into(),
+        }
+    }
+}
+
+/// The last named field of `path`, `members` for `$.members[0]`.
+fn field_of(path: &str) -> Option<String> {
+    let name = path.rsplit('.').next()?;
+    if name == "$" {
+        return None;
+    }
+
+    let name = match name.find('[') {
+        Some(index) => &name[..index],
+        None => name,
+    };
+
+    Some(name.to_string())
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_u64() || number.is_i64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Checks `value` against a command schema as listed by the registry: an object maps field names to
+/// schemas, a one element array describes every element, and a string names a type. Type names are
+/// `string`, `integer`, `unsigned integer`, `boolean`, `object` or alternatives like `vote | execute`,
+/// optionally followed by `, optional`.
+pub fn schema_violations(schema: &Value, value: &Value) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    check(schema, value, "$", &mut violations);
+
+    violations
+}
+
+fn check(schema: &Value, value: &Value, path: &str, violations: &mut Vec<Violation>) {
+    match schema {
+        Value::Object(fields) => {
+            let object = match value {
+                Value::Object(object) => object,
+                _ => {
+                    violations.push(Violation::new(path, Some("object"), format!("Expected an object, got {}", type_name(value))));
+                    return;
+                }
+            };
+
+            for (name, field_schema) in fields {
+                let field_path = format!("{}.{}", path, name);
+                match object.get(name) {
+                    Some(Value::Null) | None if is_optional(field_schema) => {}
+                    Some(field_value) => check(field_schema, field_value, &field_path, violations),
+                    None => violations.push(Violation::new(
+                        &field_path,
+                        expected_name(field_schema).as_deref(),
+                        format!("Missing field {}", name),
+                    )),
+                }
+            }
+        }
+        Value::Array(elements) => {
+            let array = match value {
+                Value::Array(array) => array,
+                _ => {
+                    violations.push(Violation::new(path, Some("array"), format!("Expected an array, got {}", type_name(value))));
+                    return;
+                }
+            };
+
+            if let Some(element_schema) = elements.first() {
+                for (index, element) in array.iter().enumerate() {
+                    check(element_schema, element, &format!("{}[{}]", path, index), violations);
+                }
+            }
+        }
+        Value::String(spec) => {
+            let expected = spec.trim_end_matches(", optional");
+            if let Some(message) = type_mismatch(expected, value) {
+                violations.push(Violation::new(path, Some(expected), message));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_optional(schema: &Value) -> bool {
+    matches!(schema, Value::String(spec) if spec.ends_with(", optional"))
+}
+
+fn expected_name(schema: &Value) -> Option<String> {
+    match schema {
+        Value::Object(_) => Some("object".to_string()),
+        Value::Array(_) => Some("array".to_string()),
+        Value::String(spec) => Some(spec.trim_end_matches(", optional").to_string()),
+        _ => None,
+    }
+}
+
+fn type_mismatch(expected: &str, value: &Value) -> Option<String> {
+    let matches = match expected {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "unsigned integer" => value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        alternatives if alternatives.contains(" | ") => {
+            return match value.as_str() {
+                Some(name) if alternatives.split(" | ").any(|alternative| alternative == name) => None,
+                Some(name) => Some(format!("Unknown value {}, expected one of {}", name, alternatives)),
+                None => Some(format!("Expected one of {}, got {}", alternatives, type_name(value))),
+            };
+        }
+        _ => true,
+    };
+
+    if matches {
+        return None;
+    }
+
+    Some(format!("Expected {}, got {}", expected, type_name(value)))
+}
+
+/// Collects the semantic violations of a request that already matches its schema.
+#[derive(Default, Debug)]
+pub struct Validator {
+    violations: Vec<Violation>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Validator::default()
+    }
+
+    pub fn push(&mut self, path: &str, expected: Option<&str>, message: impl Into<String>) -> &mut Self {
+        self.violations.push(Violation::new(path, expected, message));
+        self
+    }
+
+    pub fn pubkey(&mut self, path: &str, value: &str) -> &mut Self {
+        if Pubkey::from_str(value).is_err() {
+            self.push(path, Some("base58 public key"), format!("Invalid public key: {}", value));
+        }
+        self
+    }
+
+    pub fn transaction(&mut self, path: &str, value: &str) -> &mut Self {
+        let decoded = STANDARD.decode(value).ok().and_then(|bytes| bincode::deserialize::<Transaction>(&bytes).ok());
+        match decoded {
+            None => self.push(path, Some("base64 encoded transaction"), "Invalid transaction"),
+            Some(transaction) if transaction.sanitize().is_err() => {
+                self.push(path, Some("base64 encoded transaction"), "Transaction refers to accounts it does not list")
+            }
+            Some(_) => self,
+        }
+    }
+
+    pub fn positive(&mut self, path: &str, value: u64) -> &mut Self {
+        if value == 0 {
+            self.push(path, Some("positive integer"), "Must be greater than 0");
+        }
+        self
+    }
+
+    pub fn range(&mut self, path: &str, value: u64, min: u64, max: u64) -> &mut Self {
+        if value < min || value > max {
+            self.push(
+                path,
+                Some(&format!("integer between {} and {}", min, max)),
+                format!("{} is out of range", value),
+            );
+        }
+        self
+    }
+
+    pub fn one_of(&mut self, path: &str, value: &str, allowed: &[&str]) -> &mut Self {
+        if !allowed.contains(&value) {
+            let expected = allowed.join(" | ");
+            self.push(path, Some(&expected), format!("Unknown value {}, expected one of {}", value, expected));
+        }
+        self
+    }
+
+    pub fn finish(&mut self) -> Vec<Violation> {
+        std::mem::take(&mut self.violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn schema_violations_name_path_and_type() {
+        let schema = json!({
+            "multisig_pda": "string",
+            "members": [{ "key": "string", "permissions": ["initiate | vote | execute"] }],
+            "threshold": "unsigned integer",
+            "time_lock": "unsigned integer, optional"
+        });
+
+        let valid = json!({
+            "multisig_pda": "pda",
+            "members": [{ "key": "key", "permissions": ["vote"] }],
+            "threshold": 1
+        });
+        assert!(schema_violations(&schema, &valid).is_empty());
+
+        let invalid = json!({
+            "members": [{ "key": 7, "permissions": ["vote", "own"] }],
+            "threshold": -1,
+            "time_lock": "never"
+        });
+        assert_eq!(
+            vec![
+                Violation::new("$.members[0].key", Some("string"), "Expected string, got integer"),
+                Violation::new(
+                    "$.members[0].permissions[1]",
+                    Some("initiate | vote | execute"),
+                    "Unknown value own, expected one of initiate | vote | execute"
+                ),
+                Violation::new("$.multisig_pda", Some("string"), "Missing field multisig_pda"),
+                Violation::new("$.threshold", Some("unsigned integer"), "Expected unsigned integer, got integer"),
+                Violation::new("$.time_lock", Some("unsigned integer"), "Expected unsigned integer, got string"),
+            ],
+            schema_violations(&schema, &invalid)
+        );
+        assert_eq!(Some("permissions".to_string()), Violation::new("$.members[0].permissions[1]", None, "").field);
+        assert_eq!(None, Violation::new("$", None, "").field);
+    }
+
+    #[test]
+    fn validator_collects_semantic_violations() {
+        let violations = Validator::new()
+            .pubkey("$.multisig_pda", "not-a-key")
+            .pubkey("$.receiver", &Pubkey::new_unique().to_string())
+            .positive("$.lamports", 0)
+            .range("$.threshold", 3, 1, 2)
+            .one_of("$.role", "owner", &["vote", "execute"])
+            .finish();
+
+        let paths: Vec<&str> = violations.iter().map(|violation| violation.path.as_str()).collect();
+        assert_eq!(vec!["$.multisig_pda", "$.lamports", "$.threshold", "$.role"], paths);
+        assert_eq!(Some("integer between 1 and 2".to_string()), violations[2].expected);
+    }
+
+    #[test]
+    fn malformed_transaction_is_a_violation() {
+        let payer = Pubkey::new_unique();
+        let transfer = solana_sdk::system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+        let mut transaction = Transaction::new_unsigned(solana_sdk::message::Message::new(&[transfer], Some(&payer)));
+        let encode = |transaction: &Transaction| STANDARD.encode(bincode::serialize(transaction).unwrap());
+        assert!(Validator::new().transaction("$.transaction", &encode(&transaction)).finish().is_empty());
+
+        transaction.message.instructions[0].program_id_index = u8::MAX;
+        let violations = Validator::new()
+            .transaction("$.transaction", &encode(&transaction))
+            .transaction("$.other", "not base64")
+            .finish();
+        assert_eq!(
+            vec!["Transaction refers to accounts it does not list", "Invalid transaction"],
+            violations.iter().map(|violation| violation.message.as_str()).collect::<Vec<_>>()
+        );
+    }
+}
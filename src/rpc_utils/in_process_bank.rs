@@ -0,0 +1,443 @@
+use std::{
+    sync::{Mutex, MutexGuard, Once},
+    thread,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use solana_banks_client::{BanksClient, BanksClientError};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    rpc_response::{Response, RpcResponseContext, RpcResult},
+};
+use solana_program_test::{find_file, processor, read_file, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    account_info::AccountInfo,
+    bpf_loader,
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    entrypoint::ProgramResult,
+    hash::Hash,
+    message::Message,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Signature, Signer},
+    system_instruction,
+    transaction::{self, Transaction, TransactionError},
+};
+use squads_multisig::{
+    anchor_lang::AccountSerialize,
+    pda::get_program_config_pda,
+    squads_multisig_program::{self, state::ProgramConfig},
+};
+use tokio::sync::oneshot;
+
+use super::rpc_backend::RpcBackend;
+
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+
+struct BankState {
+    context: ProgramTestContext,
+    clock: Clock,
+    /// Blockhash of the last airdrop, the next one waits for a new blockhash so it is not a duplicate
+    airdrop_blockhash: Hash,
+    /// Processed right before the next transaction sent through the bank
+    front_run: Option<Transaction>,
+}
+
+/// A single-node ledger backed by `solana-program-test`, so transactions go through the real runtime.
+/// The SPL token, Token-2022 and associated token account programs are deployed from their released
+/// binaries; others are added with `add_program` from the `.so` files `cargo build-sbf` produces.
+///
+/// The banks server runs on a runtime of its own, so the bank can be created outside of async code
+/// and shared between tests that each bring their own runtime.
+pub struct InProcessBank {
+    state: Mutex<BankState>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl Default for InProcessBank {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for InProcessBank {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+impl InProcessBank {
+    pub fn new() -> Self {
+        Self::start(program_test())
+    }
+
+    /// A bank with the Squads v4 program deployed under `program_id` and its program config initialized.
+    /// Squads runs natively from the `squads-multisig-program` crate, which refuses any id but the one
+    /// it declares, like the deployed binary does.
+    pub fn with_squads(program_id: Pubkey) -> Self {
+        let mut program_test = program_test();
+        program_test.prefer_bpf(false);
+        program_test.add_program("squads_multisig_program", program_id, processor!(process_squads_instruction));
+
+        let program_config = ProgramConfig {
+            authority: Pubkey::new_from_array([1; 32]),
+            multisig_creation_fee: 0,
+            treasury: Pubkey::new_from_array([2; 32]),
+            _reserved: [0; 64],
+        };
+        let mut data = Vec::new();
+        program_config
+            .try_serialize(&mut data)
+            .expect("ProgramConfig serializes into a Vec");
+
+        let (program_config_pda, _) = get_program_config_pda(Some(&program_id));
+        program_test.add_account(
+            program_config_pda,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        Self::start(program_test)
+    }
+
+    fn start(program_test: ProgramTest) -> Self {
+        let (started_sender, started) = std::sync::mpsc::channel();
+        let (shutdown, stopped) = oneshot::channel::<()>();
+
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("the bank runtime starts");
+
+            runtime.block_on(async move {
+                let mut context = program_test.start_with_context().await;
+                let clock = context.banks_client.get_sysvar::<Clock>().await.expect("the bank has a clock");
+                if started_sender.send((context, clock)).is_err() {
+                    return;
+                }
+
+                // Keeps the banks server running until the bank is dropped
+                let _ = stopped.await;
+            });
+        });
+
+        let (context, clock) = started.recv().expect("the bank starts");
+
+        InProcessBank {
+            state: Mutex::new(BankState { context, clock, airdrop_blockhash: Hash::default(), front_run: None }),
+            shutdown: Some(shutdown),
+        }
+    }
+
+    /// Deploys `<program_name>.so` under `program_id`. The file is looked up in `BPF_OUT_DIR` or
+    /// `SBF_OUT_DIR`, then in `tests/fixtures`.
+    pub fn add_program(&self, program_name: &str, program_id: Pubkey) {
+        let program_file = match find_file(&format!("{program_name}.so")) {
+            Some(program_file) => program_file,
+            None => panic!("{program_name}.so not found, build it with `cargo build-sbf` and set SBF_OUT_DIR"),
+        };
+        let data = read_file(program_file);
+
+        self.set_account(
+            program_id,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()).max(1),
+                data,
+                owner: bpf_loader::id(),
+                executable: true,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    pub fn set_account(&self, pubkey: Pubkey, account: Account) {
+        self.lock().context.set_account(&pubkey, &AccountSharedData::from(account));
+    }
+
+    pub fn get_slot(&self) -> u64 {
+        self.lock().clock.slot
+    }
+
+    /// Jumps forward to `slot` without processing anything, e.g. to get past a deadline.
+    /// The clock follows at one second per slot.
+    pub fn warp_to_slot(&self, slot: u64) {
+        let mut state = self.lock();
+        if slot <= state.clock.slot {
+            return;
+        }
+
+        let clock = Clock {
+            slot,
+            unix_timestamp: state.clock.unix_timestamp + (slot - state.clock.slot) as i64,
+            ..state.clock.clone()
+        };
+        state.context.warp_to_slot(slot).expect("the bank warps forward");
+        state.context.set_sysvar(&clock);
+        state.clock = clock;
+    }
+
+    /// Lands `transaction` right before the next one sent through the bank, like another client
+    /// that wins a race between reading an account and sending a transaction built from it.
+    pub fn front_run(&self, transaction: Transaction) {
+        self.lock().front_run = Some(transaction);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BankState> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Whether no airdrop used `blockhash` yet, in which case it is taken for the next one.
+    fn take_airdrop_blockhash(&self, blockhash: Hash) -> bool {
+        let mut state = self.lock();
+        if blockhash == state.airdrop_blockhash {
+            return false;
+        }
+
+        state.airdrop_blockhash = blockhash;
+        true
+    }
+
+    fn banks_client(&self) -> BanksClient {
+        self.lock().context.banks_client.clone()
+    }
+}
+
+/// `ProgramTest` logs every instruction it runs unless `RUST_LOG` asks for something else
+fn program_test() -> ProgramTest {
+    static QUIET_LOGS: Once = Once::new();
+    QUIET_LOGS.call_once(|| {
+        if std::env::var_os("RUST_LOG").is_none() {
+            std::env::set_var("RUST_LOG", "error");
+        }
+    });
+
+    ProgramTest::default()
+}
+
+fn process_squads_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    // Anchor wants the slice to live as long as the accounts in it, which a leaked copy does
+    let accounts: &[AccountInfo] = Box::leak(accounts.to_vec().into_boxed_slice());
+    squads_multisig_program::entry(program_id, accounts, data)
+}
+
+fn client_error(error: BanksClientError) -> ClientError {
+    match error {
+        BanksClientError::TransactionError(error) | BanksClientError::SimulationError { err: error, .. } => {
+            ClientError::from(error)
+        }
+        error => ClientError::from(ClientErrorKind::Custom(error.to_string())),
+    }
+}
+
+fn account_not_found(pubkey: &Pubkey) -> ClientError {
+    ClientError::from(ClientErrorKind::Custom(format!("AccountNotFound: pubkey={pubkey}")))
+}
+
+#[async_trait]
+impl RpcBackend for InProcessBank {
+    fn url(&self) -> String {
+        "in-process".to_string()
+    }
+
+    fn commitment(&self) -> CommitmentConfig {
+        CommitmentConfig::confirmed()
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        match self.banks_client().get_account(*pubkey).await {
+            Ok(Some(account)) => Ok(account),
+            Ok(None) => Err(account_not_found(pubkey)),
+            Err(error) => Err(client_error(error)),
+        }
+    }
+
+    async fn get_account_with_commitment(&self, pubkey: &Pubkey, _commitment: CommitmentConfig) -> RpcResult<Option<Account>> {
+        let slot = self.get_slot();
+        let value = self.banks_client().get_account(*pubkey).await.map_err(client_error)?;
+
+        Ok(Response {
+            context: RpcResponseContext { slot, api_version: None },
+            value,
+        })
+    }
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> ClientResult<Vec<u8>> {
+        Ok(self.get_account(pubkey).await?.data)
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+        let mut banks_client = self.banks_client();
+        let mut accounts = Vec::with_capacity(pubkeys.len());
+        for pubkey in pubkeys {
+            accounts.push(banks_client.get_account(*pubkey).await.map_err(client_error)?);
+        }
+
+        Ok(accounts)
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> ClientResult<u64> {
+        self.banks_client().get_balance(*pubkey).await.map_err(client_error)
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        let rent = self.banks_client().get_rent().await.map_err(client_error)?;
+
+        Ok(rent.minimum_balance(data_len))
+    }
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        self.banks_client().get_latest_blockhash().await.map_err(client_error)
+    }
+
+    async fn is_blockhash_valid(&self, blockhash: &Hash) -> ClientResult<bool> {
+        let payer = self.lock().context.payer.pubkey();
+        let message = Message::new_with_blockhash(&[], Some(&payer), blockhash);
+
+        // The bank has no fee for a blockhash it does not know anymore
+        let fee = self.banks_client().get_fee_for_message(message).await.map_err(client_error)?;
+
+        Ok(fee.is_some())
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> ClientResult<Option<transaction::Result<()>>> {
+        let status = self.banks_client().get_transaction_status(*signature).await.map_err(client_error)?;
+
+        Ok(status.map(|status| match status.err {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }))
+    }
+
+    async fn get_signature_slot(&self, signature: &Signature) -> ClientResult<Option<u64>> {
+        let status = self.banks_client().get_transaction_status(*signature).await.map_err(client_error)?;
+
+        Ok(status.map(|status| status.slot))
+    }
+
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+        let signature = *transaction.signatures.first().ok_or(TransactionError::SignatureFailure)?;
+
+        let front_run = self.lock().front_run.take();
+        if let Some(front_run) = front_run {
+            self.banks_client().process_transaction(front_run).await.map_err(client_error)?;
+        }
+
+        // Simulated first like `RpcClient` does, so a transaction that fails never lands
+        match self.banks_client().process_transaction_with_preflight(transaction.clone()).await {
+            Ok(()) => Ok(signature),
+            Err(error) => Err(client_error(error)),
+        }
+    }
+
+    /// Transfers `lamports` from the payer of the bank, like the faucet of a test validator.
+    async fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> ClientResult<Signature> {
+        let mut banks_client = self.banks_client();
+        let faucet = self.lock().context.payer.insecure_clone();
+
+        let blockhash = loop {
+            let blockhash = banks_client.get_latest_blockhash().await.map_err(client_error)?;
+            if self.take_airdrop_blockhash(blockhash) {
+                break blockhash;
+            }
+
+            // The same airdrop again under the same blockhash would be taken for a duplicate
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        };
+
+        let transfer = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&faucet.pubkey(), pubkey, lamports)],
+            Some(&faucet.pubkey()),
+            &[&faucet],
+            blockhash,
+        );
+        self.send_and_confirm_transaction(&transfer).await
+    }
+
+    async fn confirm_transaction(&self, signature: &Signature) -> ClientResult<bool> {
+        Ok(matches!(self.get_signature_status(signature).await?, Some(Ok(()))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        native_token::LAMPORTS_PER_SOL, signature::Keypair, signer::Signer, system_instruction, sysvar,
+    };
+
+    #[tokio::test]
+    async fn transfer_between_accounts() {
+        let bank = InProcessBank::new();
+        let sender = Keypair::new();
+        let receiver = Pubkey::new_unique();
+        bank.request_airdrop(&sender.pubkey(), 2 * LAMPORTS_PER_SOL).await.unwrap();
+
+        let tx = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&sender.pubkey(), &receiver, LAMPORTS_PER_SOL)],
+            Some(&sender.pubkey()),
+            &[&sender],
+            bank.get_latest_blockhash().await.unwrap(),
+        );
+        let signature = bank.send_and_confirm_transaction(&tx).await.unwrap();
+
+        assert!(bank.confirm_transaction(&signature).await.unwrap());
+        assert_eq!(LAMPORTS_PER_SOL, bank.get_balance(&receiver).await.unwrap());
+        assert_eq!(
+            LAMPORTS_PER_SOL - LAMPORTS_PER_SIGNATURE,
+            bank.get_balance(&sender.pubkey()).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_transaction_is_rolled_back() {
+        let bank = InProcessBank::new();
+        let sender = Keypair::new();
+        let receiver = Pubkey::new_unique();
+        bank.request_airdrop(&sender.pubkey(), LAMPORTS_PER_SOL).await.unwrap();
+
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(&sender.pubkey(), &receiver, LAMPORTS_PER_SOL / 2),
+                system_instruction::transfer(&sender.pubkey(), &receiver, LAMPORTS_PER_SOL),
+            ],
+            Some(&sender.pubkey()),
+            &[&sender],
+            bank.get_latest_blockhash().await.unwrap(),
+        );
+
+        let error = bank.send_and_confirm_transaction(&tx).await.unwrap_err();
+        assert!(matches!(error.get_transaction_error(), Some(TransactionError::InstructionError(1, _))));
+        assert_eq!(0, bank.get_balance(&receiver).await.unwrap());
+        assert_eq!(None, bank.get_signature_status(&tx.signatures[0]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn warp_advances_clock() {
+        let bank = InProcessBank::new();
+        let start: Clock = solana_sdk::account::from_account(&bank.get_account(&sysvar::clock::id()).await.unwrap()).unwrap();
+        bank.warp_to_slot(100);
+
+        let clock: Clock = solana_sdk::account::from_account(&bank.get_account(&sysvar::clock::id()).await.unwrap()).unwrap();
+        assert_eq!(100, bank.get_slot());
+        assert_eq!(100, clock.slot);
+        assert_eq!(start.unix_timestamp + (100 - start.slot) as i64, clock.unix_timestamp);
+
+        // Never goes back
+        bank.warp_to_slot(50);
+        assert_eq!(100, bank.get_slot());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod rpc_backend;
+
+#[cfg(test)]
+pub mod in_process_bank;
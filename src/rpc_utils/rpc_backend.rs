@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use solana_client::{
+    client_error::{ClientErrorKind, Result as ClientResult},
+    nonblocking::rpc_client::RpcClient,
+    rpc_request::RpcError,
+    rpc_response::RpcResult,
+};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{self, Transaction},
+};
+
+/// Most accounts `getMultipleAccounts` takes in one request.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// The subset of the Solana JSON RPC API the multisig and VentureLaunch code relies on.
+/// Implemented by the nonblocking `RpcClient` for real clusters and by `InProcessBank` in tests.
+#[async_trait]
+pub trait RpcBackend: Send + Sync {
+    fn url(&self) -> String;
+    fn commitment(&self) -> CommitmentConfig;
+
+    async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account>;
+    async fn get_account_with_commitment(&self, pubkey: &Pubkey, commitment: CommitmentConfig) -> RpcResult<Option<Account>>;
+    async fn get_account_data(&self, pubkey: &Pubkey) -> ClientResult<Vec<u8>>;
+    /// `None` for every account that does not exist. Nodes take at most `MAX_MULTIPLE_ACCOUNTS` keys.
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>>;
+    async fn get_balance(&self, pubkey: &Pubkey) -> ClientResult<u64>;
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64>;
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash>;
+    /// Whether a transaction using `blockhash` can still land.
+    async fn is_blockhash_valid(&self, blockhash: &Hash) -> ClientResult<bool>;
+    /// Searches the whole transaction history, so a transaction that landed long ago is still found.
+    async fn get_signature_status(&self, signature: &Signature) -> ClientResult<Option<transaction::Result<()>>>;
+    /// Slot the transaction landed in, `None` if the node does not know the signature.
+    async fn get_signature_slot(&self, signature: &Signature) -> ClientResult<Option<u64>>;
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> ClientResult<Signature>;
+    async fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> ClientResult<Signature>;
+    async fn confirm_transaction(&self, signature: &Signature) -> ClientResult<bool>;
+}
+
+#[async_trait]
+impl RpcBackend for RpcClient {
+    fn url(&self) -> String {
+        RpcClient::url(self)
+    }
+
+    fn commitment(&self) -> CommitmentConfig {
+        RpcClient::commitment(self)
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        RpcClient::get_account(self, pubkey).await
+    }
+
+    async fn get_account_with_commitment(&self, pubkey: &Pubkey, commitment: CommitmentConfig) -> RpcResult<Option<Account>> {
+        RpcClient::get_account_with_commitment(self, pubkey, commitment).await
+    }
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> ClientResult<Vec<u8>> {
+        RpcClient::get_account_data(self, pubkey).await
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+        RpcClient::get_multiple_accounts(self, pubkeys).await
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> ClientResult<u64> {
+        RpcClient::get_balance(self, pubkey).await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        RpcClient::get_minimum_balance_for_rent_exemption(self, data_len).await
+    }
+
+    async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        RpcClient::get_latest_blockhash(self).await
+    }
+
+    async fn is_blockhash_valid(&self, blockhash: &Hash) -> ClientResult<bool> {
+        RpcClient::is_blockhash_valid(self, blockhash, RpcClient::commitment(self)).await
+    }
+
+    async fn get_signature_status(&self, signature: &Signature) -> ClientResult<Option<transaction::Result<()>>> {
+        RpcClient::get_signature_status_with_commitment_and_history(self, signature, RpcClient::commitment(self), true).await
+    }
+
+    async fn get_signature_slot(&self, signature: &Signature) -> ClientResult<Option<u64>> {
+        let response = RpcClient::get_signature_statuses_with_history(self, &[*signature]).await?;
+
+        Ok(response.value.into_iter().next().flatten().map(|status| status.slot))
+    }
+
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> ClientResult<Signature> {
+        RpcClient::send_and_confirm_transaction(self, transaction).await
+    }
+
+    async fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> ClientResult<Signature> {
+        RpcClient::request_airdrop(self, pubkey, lamports).await
+    }
+
+    async fn confirm_transaction(&self, signature: &Signature) -> ClientResult<bool> {
+        RpcClient::confirm_transaction(self, signature).await
+    }
+}
+
+/// Why a sent transaction is not known to have landed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SendFailure {
+    /// The node did not take the transaction, so nothing landed
+    NotSent,
+    /// Rejected in preflight or failed on chain, so nothing changed
+    Failed,
+    /// Sent, but neither a confirmation nor a failure was seen; it may still land
+    NotConfirmed(Signature),
+}
+
+/// Sends and confirms `transaction`. A failed confirmation is checked against the signature status,
+/// so a transaction that landed anyway is reported as sent.
+pub async fn send_and_confirm(rpc_client: &dyn RpcBackend, transaction: &Transaction) -> Result<Signature, SendFailure> {
+    let error = match rpc_client.send_and_confirm_transaction(transaction).await {
+        Ok(signature) => return Ok(signature),
+        Err(error) => error
+    };
+
+    // Landed after all, e.g. by an earlier attempt or before the confirmation timed out
+    let signature = transaction.signatures[0];
+    if let Ok(Some(status)) = rpc_client.get_signature_status(&signature).await {
+        return match status {
+            Ok(()) => Ok(signature),
+            Err(_) => Err(SendFailure::Failed)
+        };
+    }
+
+    if error.get_transaction_error().is_some() {
+        return Err(SendFailure::Failed);
+    }
+    match error.kind() {
+        // What `send_and_confirm_transaction` reports once it gave up waiting for the sent transaction
+        ClientErrorKind::RpcError(RpcError::ForUser(_)) => Err(SendFailure::NotConfirmed(signature)),
+        _ => Err(SendFailure::NotSent)
+    }
+}